@@ -0,0 +1,81 @@
+// Move generation over representative positions, run with `cargo bench`.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use bevy_backgammon::game::{expand_roll, Board, Color, Game};
+
+// Board from `(index, checkers)` pairs for each color.
+fn board(white: &[(usize, i32)], black: &[(usize, i32)], bar: [i32; 2]) -> Board {
+    let mut white_points = [0; 24];
+    for &(index, checkers) in white {
+        white_points[index] = checkers;
+    }
+    let mut black_points = [0; 24];
+    for &(index, checkers) in black {
+        black_points[index] = checkers;
+    }
+
+    Game::from_position(white_points, black_points, bar, Color::White)
+        .unwrap()
+        .board
+}
+
+fn positions() -> Vec<(&'static str, Board)> {
+    vec![
+        ("opening", Game::new().board),
+        (
+            // both sides with blots and an anchor, White on the bar
+            "midgame",
+            board(
+                &[(0, 2), (4, 1), (11, 3), (16, 3), (18, 3), (20, 2)],
+                &[(23, 2), (19, 1), (12, 4), (7, 3), (5, 4), (3, 1)],
+                [1, 0],
+            ),
+        ),
+        (
+            "bear-off",
+            board(
+                &[(18, 3), (19, 3), (20, 2), (21, 2), (22, 2), (23, 1)],
+                &[(0, 4), (1, 3), (2, 3), (3, 2), (4, 2)],
+                [0, 0],
+            ),
+        ),
+    ]
+}
+
+fn move_generation(c: &mut Criterion) {
+    for (name, board) in positions() {
+        let moves_board = board.clone();
+        c.bench_function(&format!("get_possible_moves {}", name), move |b| {
+            b.iter(|| moves_board.get_possible_moves(Color::White, black_box(&[3, 1])))
+        });
+
+        let sequences_board = board.clone();
+        c.bench_function(&format!("legal_turn_sequences {} 3-1", name), move |b| {
+            b.iter(|| sequences_board.legal_turn_sequences(Color::White, black_box(&[3, 1])))
+        });
+
+        // doubles play four dice, the case the enumeration has to stay fast for
+        for die in [1, 6] {
+            let dice = expand_roll([die, die]);
+            let doubles_board = board.clone();
+            c.bench_function(
+                &format!("legal_turn_sequences {} {}-{}", name, die, die),
+                move |b| {
+                    b.iter(|| doubles_board.legal_turn_sequences(Color::White, black_box(&dice)))
+                },
+            );
+        }
+    }
+
+    c.bench_function("expand_roll", |b| {
+        b.iter(|| {
+            (
+                expand_roll(black_box([3, 1])),
+                expand_roll(black_box([4, 4])),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, move_generation);
+criterion_main!(benches);
@@ -0,0 +1,394 @@
+// Move selection for computer players. The position reached after each legal
+// turn is scored by an `Evaluator` and the best scoring turn is played.
+use bevy::prelude::*;
+use itertools::Itertools;
+
+use crate::{
+    game::{Board, Color, Game, GamePhase, Move},
+    mat,
+    toast::ToastEvent,
+};
+
+pub(crate) trait Evaluator: Send + Sync {
+    // Higher is better for `player`.
+    fn evaluate(&self, board: &Board, player: Color) -> f32;
+}
+
+// Race and safety heuristic: pip lead, borne off checkers and exposed blots.
+pub(crate) struct HeuristicEvaluator {
+    pub(crate) pip_weight: f32,
+    pub(crate) borne_off_weight: f32,
+    pub(crate) blot_weight: f32,
+    pub(crate) hit_weight: f32,
+}
+
+impl Default for HeuristicEvaluator {
+    fn default() -> Self {
+        HeuristicEvaluator {
+            pip_weight: 1.0,
+            borne_off_weight: 5.0,
+            blot_weight: 4.0,
+            hit_weight: 10.0,
+        }
+    }
+}
+
+impl Evaluator for HeuristicEvaluator {
+    fn evaluate(&self, board: &Board, player: Color) -> f32 {
+        let opponent = player.opposite();
+        let pip_lead = board.pip_count(opponent) as f32 - board.pip_count(player) as f32;
+        let borne_off = board.borne_off[board.bar_index(player)] as f32;
+        let on_bar = board.bar[board.opposite_bar_index(player)] as f32;
+
+        self.pip_weight * pip_lead + self.borne_off_weight * borne_off + self.hit_weight * on_bar
+            - self.blot_weight * board.blots(player) as f32
+    }
+}
+
+// Pure race play: only the pip count matters once contact is broken.
+pub(crate) struct RaceEvaluator;
+
+impl Evaluator for RaceEvaluator {
+    fn evaluate(&self, board: &Board, player: Color) -> f32 {
+        -(board.pip_count(player) as f32)
+    }
+}
+
+// Training mode where the computer races perfectly by pips once the game
+// becomes a race, so learners can practice racing against it.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub(crate) struct PipLeaderTraining {
+    pub(crate) enabled: bool,
+}
+
+impl PipLeaderTraining {
+    // Evaluator to pick the turn with on `board`.
+    pub(crate) fn evaluator<'a>(
+        &self,
+        board: &Board,
+        evaluator: &'a dyn Evaluator,
+    ) -> &'a dyn Evaluator {
+        if self.enabled && board.is_race() {
+            &RaceEvaluator
+        } else {
+            evaluator
+        }
+    }
+}
+
+// Evaluator used by computer players, replace it to plug in a custom AI.
+#[derive(Resource)]
+pub(crate) struct AiEvaluator(pub(crate) Box<dyn Evaluator>);
+
+impl Default for AiEvaluator {
+    fn default() -> Self {
+        AiEvaluator(Box::<HeuristicEvaluator>::default())
+    }
+}
+
+// Best legal turn for the player on roll, empty when nothing can be played.
+pub(crate) fn select_turn(game: &Game, evaluator: &dyn Evaluator) -> Vec<Move> {
+    let player = game.player;
+    let mut best_turn = vec![];
+    let mut best_score = f32::NEG_INFINITY;
+
+    let turn_key = |turn: &Vec<Move>| {
+        turn.iter()
+            .map(|checker_move| {
+                (
+                    checker_move.source(),
+                    checker_move.destination_index(player),
+                )
+            })
+            .collect_vec()
+    };
+
+    for turn in game.legal_turn_sequences(player, &game.dice_rolls) {
+        let mut board = game.board.clone();
+        for checker_move in turn.iter() {
+            board.make_move(player, *checker_move).unwrap();
+        }
+
+        // equal scores go to the lowest source point, then the lowest destination
+        let score = evaluator.evaluate(&board, player);
+        if score > best_score || (score == best_score && turn_key(&turn) < turn_key(&best_turn)) {
+            best_score = score;
+            best_turn = turn;
+        }
+    }
+
+    best_turn
+}
+
+// Short rationale for a turn taking `before` to `after`, from what changed on
+// the board: checkers hit, points made and blots covered or left.
+pub(crate) fn explain_turn(before: &Board, after: &Board, player: Color) -> String {
+    let sign = player.sign();
+    let opponent_bar = after.opposite_bar_index(player);
+    let mut reasons = vec![];
+
+    if after.bar[opponent_bar] > before.bar[opponent_bar] {
+        reasons.push(String::from("hits"));
+    }
+
+    for index in 0..24 {
+        if after.points[index] * sign >= 2 && before.points[index] * sign < 2 {
+            reasons.push(format!(
+                "makes the {}-point",
+                mat::point_number(player, index)
+            ));
+        }
+    }
+
+    if after.is_race() {
+        let borne_off =
+            after.borne_off[after.bar_index(player)] - before.borne_off[before.bar_index(player)];
+        if borne_off > 0 {
+            reasons.push(format!("bears off {}", borne_off));
+        } else if reasons.is_empty() {
+            reasons.push(String::from("safe in the race"));
+        }
+    } else {
+        let blots_before = before.blots(player);
+        let blots_after = after.blots(player);
+        if blots_after < blots_before {
+            reasons.push(String::from("covers"));
+        } else if blots_after > blots_before {
+            reasons.push(String::from("leaves a blot"));
+        } else if reasons.is_empty() && blots_after == 0 {
+            reasons.push(String::from("stays safe"));
+        }
+    }
+
+    match reasons.split_last() {
+        None => String::from("keeps the position"),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+// Best turn for the player on roll with the reason it was picked.
+pub(crate) fn hint(game: &Game, evaluator: &dyn Evaluator) -> Option<(Vec<Move>, String)> {
+    // without contact the bear-off is played for efficiency
+    let bear_off = game.board.suggest_bear_off(game.player, &game.dice_rolls);
+    let turn = if game.board.is_race() && !bear_off.is_empty() {
+        bear_off
+    } else {
+        select_turn(game, evaluator)
+    };
+    if turn.is_empty() {
+        return None;
+    }
+
+    let mut board = game.board.clone();
+    for checker_move in turn.iter() {
+        board.make_move(game.player, *checker_move).unwrap();
+    }
+
+    let reason = explain_turn(&game.board, &board, game.player);
+    Some((turn, reason))
+}
+
+// H shows the computer's choice for the current roll.
+pub(crate) fn show_hint(
+    keyboard_input: Res<Input<KeyCode>>,
+    game: Res<Game>,
+    ai_evaluator: Res<AiEvaluator>,
+    pip_leader_training: Res<PipLeaderTraining>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::H) || game.phase() != GamePhase::AwaitingMove {
+        return;
+    }
+
+    let evaluator = pip_leader_training.evaluator(&game.board, ai_evaluator.0.as_ref());
+    let text = match hint(&game, evaluator) {
+        Some((turn, reason)) => format!(
+            "Hint: {} — {}",
+            turn.iter()
+                .map(|checker_move| mat::format_move(*checker_move, game.player))
+                .join(" "),
+            reason
+        ),
+        None => String::from("Hint: no legal moves"),
+    };
+    toast_event_writer.send(ToastEvent::new(text));
+}
+
+// T switches the pip leader training for the computer's race play.
+pub(crate) fn toggle_pip_leader_training(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut pip_leader_training: ResMut<PipLeaderTraining>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::T) {
+        pip_leader_training.enabled = !pip_leader_training.enabled;
+        toast_event_writer.send(ToastEvent::new(if pip_leader_training.enabled {
+            "Pip leader training on, races are played by pips"
+        } else {
+            "Pip leader training off"
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white_to_play(white: &[(usize, i32)], dice_rolls: Vec<usize>) -> Game {
+        let mut points = [0; 24];
+        for &(index, checkers) in white {
+            points[index] = checkers;
+        }
+        let mut black = [0; 24];
+        black[23] = 2;
+
+        let mut game = Game::from_position(points, black, [0, 0], Color::White).unwrap();
+        game.dice_rolled = true;
+        game.dice_rolls = dice_rolls;
+        game
+    }
+
+    fn played(game: &Game, turn: &[Move]) -> Board {
+        let mut board = game.board.clone();
+        for checker_move in turn {
+            board.make_move(game.player, *checker_move).unwrap();
+        }
+        board
+    }
+
+    struct FewerBlots;
+
+    impl Evaluator for FewerBlots {
+        fn evaluate(&self, board: &Board, player: Color) -> f32 {
+            -(board.blots(player) as f32)
+        }
+    }
+
+    #[test]
+    fn custom_evaluator_picks_the_turn() {
+        let game = white_to_play(&[(0, 1), (2, 1), (10, 2)], vec![2, 1]);
+        let blots = game
+            .legal_turn_sequences(Color::White, &game.dice_rolls)
+            .iter()
+            .map(|turn| played(&game, turn).blots(Color::White))
+            .collect_vec();
+        let (fewest, most) = (blots.iter().min(), blots.iter().max());
+        assert!(fewest < most);
+
+        let turn = select_turn(&game, &FewerBlots);
+        assert_eq!(Some(&played(&game, &turn).blots(Color::White)), fewest);
+    }
+
+    struct Flat;
+
+    impl Evaluator for Flat {
+        fn evaluate(&self, _board: &Board, _player: Color) -> f32 {
+            0.0
+        }
+    }
+
+    #[test]
+    fn equal_scores_pick_the_lowest_points() {
+        let mut game = Game::new();
+        game.opening_roll = false;
+        game.dice_rolled = true;
+        game.dice_rolls = vec![3, 1];
+
+        // every turn scores the same, the back checkers go first
+        let turn = select_turn(&game, &Flat);
+        assert_eq!(turn[0].source(), 0);
+        let lowest = game
+            .legal_turn_sequences(Color::White, &game.dice_rolls)
+            .into_iter()
+            .min_by_key(|turn| {
+                turn.iter()
+                    .map(|m| (m.source(), m.destination_index(Color::White)))
+                    .collect_vec()
+            })
+            .unwrap();
+        assert_eq!(turn, lowest);
+
+        for _ in 0..5 {
+            assert_eq!(select_turn(&game, &Flat), turn);
+        }
+    }
+
+    #[test]
+    fn pip_leader_training_plays_races_by_pips() {
+        let training = PipLeaderTraining { enabled: true };
+        let mut white = [0; 24];
+        white[20] = 15;
+        let mut black = [0; 24];
+        black[3] = 15;
+        let race = Game::from_position(white, black, [0, 0], Color::White).unwrap();
+        assert!(race.board.is_race());
+
+        // 15 checkers on White's 4-point
+        let evaluator = training.evaluator(&race.board, &Flat);
+        assert_eq!(evaluator.evaluate(&race.board, Color::White), -60.0);
+
+        let opening = Game::new();
+        let evaluator = training.evaluator(&opening.board, &Flat);
+        assert_eq!(evaluator.evaluate(&opening.board, Color::White), 0.0);
+    }
+
+    #[test]
+    fn hint_explains_making_a_home_point() {
+        let mut game = Game::new();
+        game.opening_roll = false;
+        game.dice_rolled = true;
+        game.dice_rolls = vec![3, 1];
+
+        let (turn, reason) = hint(&game, AiEvaluator::default().0.as_ref()).unwrap();
+        assert_eq!(turn.len(), 2);
+        assert!(reason.contains("makes the 5-point"), "{reason}");
+
+        let mut after = game.board.clone();
+        after
+            .make_move(Color::White, Move::between(16, 19))
+            .unwrap();
+        after
+            .make_move(Color::White, Move::between(18, 19))
+            .unwrap();
+        assert_eq!(
+            explain_turn(&game.board, &after, Color::White),
+            "makes the 5-point"
+        );
+
+        let mut white = [0; 24];
+        white[20] = 15;
+        let mut black = [0; 24];
+        black[3] = 15;
+        let race = Game::from_position(white, black, [0, 0], Color::White).unwrap();
+        let mut after = race.board.clone();
+        after
+            .make_move(Color::White, Move::between(20, 22))
+            .unwrap();
+        assert_eq!(
+            explain_turn(&race.board, &after, Color::White),
+            "safe in the race"
+        );
+    }
+
+    #[test]
+    fn t_toggles_the_pip_leader_training() {
+        let mut app = crate::test_support::test_app();
+        app.init_resource::<Input<KeyCode>>()
+            .add_event::<ToastEvent>()
+            .init_resource::<PipLeaderTraining>()
+            .add_system(toggle_pip_leader_training);
+
+        let press_t = |app: &mut App| {
+            let mut input = app.world.resource_mut::<Input<KeyCode>>();
+            input.reset_all();
+            input.press(KeyCode::T);
+            app.update();
+            app.world.resource::<PipLeaderTraining>().enabled
+        };
+
+        assert!(press_t(&mut app));
+        assert_eq!(app.world.resource::<Events<ToastEvent>>().len(), 1);
+        assert!(!press_t(&mut app));
+    }
+}
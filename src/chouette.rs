@@ -0,0 +1,158 @@
+// Chouette: one player, "in the box", plays against a team led by a captain.
+// After each game the line rotates: a losing box hands over to the captain,
+// and a captain who lost to the box goes to the end of the line.
+use bevy::prelude::*;
+
+use crate::{events::GameOverEvent, game::Color, toast::ToastEvent};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct PlayerId(pub(crate) u32);
+
+#[derive(Clone, Debug)]
+pub(crate) struct Chouette {
+    // color the box plays, the team plays the other one
+    pub(crate) box_player: Color,
+    pub(crate) in_the_box: PlayerId,
+    // in turn order, the first one is the captain
+    pub(crate) team: Vec<PlayerId>,
+}
+
+impl Chouette {
+    pub(crate) fn new(box_player: Color, players: &[PlayerId]) -> Result<Self, String> {
+        let [in_the_box, team @ ..] = players else {
+            return Err(String::from("A chouette needs players"));
+        };
+
+        if team.is_empty() {
+            return Err(String::from("A chouette needs at least one team member"));
+        }
+
+        Ok(Chouette {
+            box_player,
+            in_the_box: *in_the_box,
+            team: team.to_vec(),
+        })
+    }
+
+    // Players numbered from 1, the first one starts in the box playing White.
+    pub(crate) fn with_players(count: u32) -> Result<Self, String> {
+        Chouette::new(Color::White, &(1..=count).map(PlayerId).collect::<Vec<_>>())
+    }
+
+    pub(crate) fn captain(&self) -> PlayerId {
+        self.team[0]
+    }
+
+    // Player whose turn it is to act for `color`.
+    pub(crate) fn player_for(&self, color: Color) -> PlayerId {
+        if color == self.box_player {
+            self.in_the_box
+        } else {
+            self.captain()
+        }
+    }
+
+    pub(crate) fn end_game(&mut self, winner: Color) {
+        let captain = self.team.remove(0);
+
+        if winner == self.box_player {
+            self.team.push(captain);
+        } else {
+            self.team.push(self.in_the_box);
+            self.in_the_box = captain;
+        }
+    }
+}
+
+// The chouette being played, none in a regular game.
+#[derive(Clone, Debug, Default, Resource)]
+pub(crate) struct ActiveChouette {
+    pub(crate) chouette: Option<Chouette>,
+}
+
+// Rotates the line after each game and announces who plays the next one.
+pub(crate) fn rotate_chouette(
+    mut game_over_event_reader: EventReader<GameOverEvent>,
+    mut active_chouette: ResMut<ActiveChouette>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    let Some(chouette) = active_chouette.chouette.as_mut() else {
+        return;
+    };
+
+    for event in game_over_event_reader.iter() {
+        chouette.end_game(event.player);
+
+        let box_player = chouette.box_player;
+        toast_event_writer.send(ToastEvent::new(format!(
+            "Player {} is in the box, player {} captains the team",
+            chouette.player_for(box_player).0,
+            chouette.player_for(box_player.opposite()).0
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GameEndReason;
+
+    #[test]
+    fn box_stays_after_a_win_and_rotates_after_a_loss() {
+        let players = [PlayerId(1), PlayerId(2), PlayerId(3)];
+        let mut chouette = Chouette::new(Color::White, &players).unwrap();
+        assert_eq!(chouette.player_for(Color::White), PlayerId(1));
+        assert_eq!(chouette.player_for(Color::Black), PlayerId(2));
+
+        // the box beats the captain, who goes to the end of the line
+        chouette.end_game(Color::White);
+        assert_eq!(chouette.in_the_box, PlayerId(1));
+        assert_eq!(chouette.team, vec![PlayerId(3), PlayerId(2)]);
+
+        // the box loses and the captain takes it over
+        chouette.end_game(Color::Black);
+        assert_eq!(chouette.in_the_box, PlayerId(3));
+        assert_eq!(chouette.team, vec![PlayerId(2), PlayerId(1)]);
+        assert_eq!(chouette.captain(), PlayerId(2));
+
+        assert!(Chouette::new(Color::White, &players[..1]).is_err());
+    }
+
+    #[test]
+    fn game_over_rotates_an_active_chouette() {
+        let mut app = crate::test_support::test_app();
+        app.add_event::<GameOverEvent>()
+            .add_event::<ToastEvent>()
+            .init_resource::<ActiveChouette>()
+            .add_system(rotate_chouette);
+
+        // without a chouette a game over changes nothing
+        app.world.send_event(GameOverEvent {
+            player: Color::Black,
+            reason: GameEndReason::BorneOff,
+        });
+        app.update();
+        assert_eq!(app.world.resource::<Events<ToastEvent>>().len(), 0);
+
+        app.insert_resource(ActiveChouette {
+            chouette: Some(Chouette::with_players(3).unwrap()),
+        });
+        app.world.send_event(GameOverEvent {
+            player: Color::Black,
+            reason: GameEndReason::BorneOff,
+        });
+        app.update();
+
+        let chouette = app
+            .world
+            .resource::<ActiveChouette>()
+            .chouette
+            .clone()
+            .unwrap();
+        assert_eq!(chouette.in_the_box, PlayerId(2));
+        assert_eq!(chouette.team, vec![PlayerId(3), PlayerId(1)]);
+        assert_eq!(app.world.resource::<Events<ToastEvent>>().len(), 1);
+
+        assert!(Chouette::with_players(1).is_err());
+    }
+}
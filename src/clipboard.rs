@@ -0,0 +1,94 @@
+// Copying the current position to the system clipboard for sharing.
+use bevy::prelude::*;
+
+use crate::{game, toast::ToastEvent};
+
+pub(crate) trait Clipboard: Send + Sync {
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+}
+
+pub(crate) struct SystemClipboard;
+
+impl Clipboard for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text))
+            .map_err(|e| e.to_string())
+    }
+}
+
+// Clipboard used by the copy actions, replaced by a fake one in tests.
+#[derive(Resource)]
+pub(crate) struct PositionClipboard(pub(crate) Box<dyn Clipboard>);
+
+impl Default for PositionClipboard {
+    fn default() -> Self {
+        PositionClipboard(Box::new(SystemClipboard))
+    }
+}
+
+// Write the position of `game` to `clipboard` and return what was copied.
+pub(crate) fn copy_position(
+    game: &game::Game,
+    clipboard: &mut dyn Clipboard,
+) -> Result<String, String> {
+    let position = game.to_position();
+    clipboard.set_text(&position)?;
+    Ok(position)
+}
+
+pub(crate) fn copy_position_to_clipboard(
+    keyboard_input: Res<Input<KeyCode>>,
+    game: Res<game::Game>,
+    mut clipboard: ResMut<PositionClipboard>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::X) {
+        return;
+    }
+
+    toast_event_writer.send(ToastEvent::new(
+        match copy_position(&game, clipboard.0.as_mut()) {
+            Ok(_) => String::from("Position copied to clipboard"),
+            Err(e) => format!("Couldn't copy position: {}", e),
+        },
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_app, FakeClipboard};
+
+    #[test]
+    fn x_copies_the_position() {
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+        game.dice_rolls = vec![5, 2];
+        let position = game.to_position();
+        assert!(position.ends_with("White to move, dice 5 2"));
+
+        let clipboard = FakeClipboard::default();
+        let mut app = test_app();
+        app.add_event::<ToastEvent>()
+            .init_resource::<Input<KeyCode>>()
+            .insert_resource(PositionClipboard(Box::new(clipboard.clone())))
+            .insert_resource(game)
+            .add_system(copy_position_to_clipboard);
+
+        app.update();
+        assert_eq!(*clipboard.0.lock().unwrap(), None);
+
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::X);
+        app.update();
+        assert_eq!(*clipboard.0.lock().unwrap(), Some(position));
+
+        let events = app.world.resource::<Events<ToastEvent>>();
+        let toasts = events
+            .get_reader()
+            .iter(events)
+            .map(|toast| toast.text.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(toasts, vec!["Position copied to clipboard"]);
+    }
+}
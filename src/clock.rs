@@ -0,0 +1,235 @@
+use bevy::{prelude::*, window::WindowFocused};
+use std::time::Duration;
+
+use crate::{
+    events::{GameEndReason, GameOverEvent},
+    game,
+};
+
+// Per-player turn clock with a Fischer increment: the player finishing a turn
+// gets `increment` added to their remaining time.
+#[derive(Resource)]
+pub(crate) struct ChessClock {
+    pub(crate) enabled: bool,
+    pub(crate) remaining: [Duration; 2],
+    pub(crate) increment: Duration,
+    pub(crate) running: Option<game::Color>,
+    // stopped while the window is out of focus
+    pub(crate) paused: bool,
+    // color `running` held when the clock was paused
+    resume_color: Option<game::Color>,
+}
+
+impl Default for ChessClock {
+    fn default() -> Self {
+        ChessClock {
+            enabled: false,
+            remaining: [Duration::from_secs(5 * 60); 2],
+            increment: Duration::from_secs(5),
+            running: None,
+            paused: false,
+            resume_color: None,
+        }
+    }
+}
+
+impl ChessClock {
+    // Enabled clock from a `<minutes>+<increment seconds>` time control, e.g. "5+3".
+    pub(crate) fn from_time_control(time_control: &str) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "Invalid time control {:?}, expected <min>+<inc>",
+                time_control
+            )
+        };
+        let (minutes, increment) = time_control.split_once('+').ok_or_else(invalid)?;
+        let minutes = minutes.trim().parse::<u64>().map_err(|_| invalid())?;
+        let increment = increment.trim().parse::<u64>().map_err(|_| invalid())?;
+        if minutes == 0 {
+            return Err(invalid());
+        }
+
+        Ok(ChessClock {
+            enabled: true,
+            remaining: [Duration::from_secs(minutes * 60); 2],
+            increment: Duration::from_secs(increment),
+            ..default()
+        })
+    }
+
+    fn index(player: game::Color) -> usize {
+        match player {
+            game::Color::White => 0,
+            game::Color::Black => 1,
+        }
+    }
+
+    pub(crate) fn remaining(&self, player: game::Color) -> Duration {
+        self.remaining[Self::index(player)]
+    }
+
+    pub(crate) fn end_turn(&mut self, player: game::Color) {
+        self.remaining[Self::index(player)] += self.increment;
+    }
+
+    pub(crate) fn pause(&mut self) {
+        if !self.paused {
+            self.paused = true;
+            self.resume_color = self.running.take();
+        }
+    }
+
+    // `running` is restored so a turn passed while paused still earns its increment.
+    pub(crate) fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.running = self.resume_color.take();
+        }
+    }
+
+    pub(crate) fn format(&self) -> String {
+        let format_duration = |d: Duration| format!("{}:{:02}", d.as_secs() / 60, d.as_secs() % 60);
+
+        format!(
+            "White {} | Black {} (+{}s)",
+            format_duration(self.remaining(game::Color::White)),
+            format_duration(self.remaining(game::Color::Black)),
+            self.increment.as_secs()
+        )
+    }
+}
+
+pub(crate) fn tick_chess_clock(
+    mut clock: ResMut<ChessClock>,
+    game: Res<game::Game>,
+    time: Res<Time>,
+    mut game_over_event_writer: EventWriter<GameOverEvent>,
+) {
+    if !clock.enabled || clock.paused || game.opening_roll {
+        return;
+    }
+
+    if game.is_over() {
+        clock.running = None;
+        return;
+    }
+
+    // the turn passed since the last tick
+    if let Some(running) = clock.running {
+        if running != game.player {
+            clock.end_turn(running);
+        }
+    }
+    clock.running = Some(game.player);
+
+    let index = ChessClock::index(game.player);
+    clock.remaining[index] = clock.remaining[index].saturating_sub(time.delta());
+
+    if clock.remaining[index].is_zero() {
+        // flag fell, stop the clock
+        clock.enabled = false;
+        clock.running = None;
+
+        game_over_event_writer.send(GameOverEvent {
+            player: game.player.opposite(),
+            reason: GameEndReason::Time,
+        });
+    }
+}
+
+// Players aren't penalized for switching away from the game.
+pub(crate) fn pause_chess_clock_on_focus_loss(
+    mut clock: ResMut<ChessClock>,
+    mut window_focused_event_reader: EventReader<WindowFocused>,
+) {
+    for event in window_focused_event_reader.iter() {
+        if event.focused {
+            clock.resume();
+        } else {
+            clock.pause();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_app;
+
+    fn focus(app: &mut App, focused: bool) {
+        app.world.send_event(WindowFocused {
+            window: Entity::PLACEHOLDER,
+            focused,
+        });
+        app.update();
+    }
+
+    #[test]
+    fn ending_a_turn_adds_the_increment() {
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+
+        let mut app = test_app();
+        app.add_event::<GameOverEvent>()
+            .insert_resource(ChessClock {
+                enabled: true,
+                ..default()
+            })
+            .insert_resource(game)
+            .add_system(tick_chess_clock);
+
+        app.update();
+        let white = app
+            .world
+            .resource::<ChessClock>()
+            .remaining(game::Color::White);
+
+        app.world.resource_mut::<game::Game>().switch_turn();
+        app.update();
+        let clock = app.world.resource::<ChessClock>();
+        assert_eq!(clock.remaining(game::Color::White), white + clock.increment);
+        assert_eq!(clock.running, Some(game::Color::Black));
+    }
+
+    #[test]
+    fn focus_loss_pauses_the_running_clock() {
+        let mut app = test_app();
+        app.add_event::<WindowFocused>()
+            .insert_resource(ChessClock {
+                enabled: true,
+                running: Some(game::Color::Black),
+                ..default()
+            })
+            .add_system(pause_chess_clock_on_focus_loss);
+
+        focus(&mut app, false);
+        let clock = app.world.resource::<ChessClock>();
+        assert!(clock.paused);
+        assert_eq!(clock.running, None);
+
+        focus(&mut app, true);
+        let clock = app.world.resource::<ChessClock>();
+        assert!(!clock.paused);
+        assert_eq!(clock.running, Some(game::Color::Black));
+    }
+
+    #[test]
+    fn time_control_starts_an_enabled_clock() {
+        let clock = ChessClock::from_time_control("3+2").unwrap();
+        assert!(clock.enabled);
+        assert_eq!(
+            clock.remaining(game::Color::White),
+            Duration::from_secs(180)
+        );
+        assert_eq!(
+            clock.remaining(game::Color::Black),
+            Duration::from_secs(180)
+        );
+        assert_eq!(clock.increment, Duration::from_secs(2));
+        assert_eq!(clock.format(), "White 3:00 | Black 3:00 (+2s)");
+
+        for time_control in ["3", "0+2", "three+2", "3+"] {
+            assert!(ChessClock::from_time_control(time_control).is_err());
+        }
+    }
+}
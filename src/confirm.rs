@@ -0,0 +1,151 @@
+// Turns arranged move by move and committed at once, like online clients do.
+// While enabled, moves are staged on a working copy of the game and only
+// played on the real one when the Confirm button is pressed.
+use bevy::prelude::*;
+
+use crate::{
+    events::{MovePieceEndEvent, TurnStartEvent},
+    game::{self, StagedTurn},
+    toast::ToastEvent,
+    ui::{HOVERED_BUTTON, NORMAL_BUTTON},
+};
+
+#[derive(Default, Resource)]
+pub(crate) struct ConfirmTurn {
+    pub(crate) enabled: bool,
+    // `None` until a move of the turn is staged
+    pub(crate) staged: Option<StagedTurn>,
+}
+
+impl ConfirmTurn {
+    // The game as the player sees it, with the staged moves played.
+    pub(crate) fn shown<'a>(&'a self, game: &'a game::Game) -> &'a game::Game {
+        self.staged.as_ref().map_or(game, |staged| staged.working())
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct ButtonConfirmTurn;
+
+pub(crate) fn setup_confirm_button(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(ButtonBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(100.0),
+                    right: Val::Px(20.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(8.0)),
+                display: Display::None,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            background_color: NORMAL_BUTTON.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Confirm",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 40.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+        })
+        .insert(ButtonConfirmTurn)
+        .insert(Name::new("ConfirmTurn"));
+}
+
+// K switches between confirming whole turns and moves taking effect at once.
+pub(crate) fn toggle_confirm_turn(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut confirm_turn: ResMut<ConfirmTurn>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::K) {
+        return;
+    }
+
+    let staging = confirm_turn
+        .staged
+        .as_ref()
+        .is_some_and(|staged| !staged.moves().is_empty());
+    if staging {
+        toast_event_writer.send(ToastEvent::new("Confirm or undo the staged moves first"));
+        return;
+    }
+
+    confirm_turn.enabled = !confirm_turn.enabled;
+    confirm_turn.staged = None;
+    toast_event_writer.send(ToastEvent::new(if confirm_turn.enabled {
+        "Turns are played on Confirm"
+    } else {
+        "Moves are played at once"
+    }));
+}
+
+// The button shows once the staged moves use up the roll. The board edit check
+// redraws the pieces after the moves are played.
+pub(crate) fn handle_confirm_turn_button(
+    mut button_query: Query<
+        (
+            Ref<Interaction>,
+            &mut BackgroundColor,
+            &mut Visibility,
+            &mut Style,
+        ),
+        With<ButtonConfirmTurn>,
+    >,
+    mut confirm_turn: ResMut<ConfirmTurn>,
+    mut game: ResMut<game::Game>,
+    mut move_piece_end_event_writer: EventWriter<MovePieceEndEvent>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    for (interaction, mut color, mut visibility, mut style) in button_query.iter_mut() {
+        if interaction.is_changed() {
+            *color = match *interaction {
+                Interaction::Hovered => HOVERED_BUTTON.into(),
+                _ => NORMAL_BUTTON.into(),
+            };
+        }
+
+        if interaction.is_changed() && *interaction == Interaction::Clicked {
+            if let Some(staged) = confirm_turn.staged.take() {
+                match staged.confirm(&mut game) {
+                    Ok(()) => move_piece_end_event_writer.send(MovePieceEndEvent),
+                    Err(e) => toast_event_writer.send(ToastEvent::new(e)),
+                }
+            }
+        }
+
+        if confirm_turn.is_changed() {
+            let offered = confirm_turn
+                .staged
+                .as_ref()
+                .is_some_and(|staged| staged.is_complete());
+            *visibility = if offered {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            style.display = if offered {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
+    }
+}
+
+// Staged moves belong to the turn they were made in.
+pub(crate) fn clear_staged_turn(
+    mut turn_start_event_reader: EventReader<TurnStartEvent>,
+    mut confirm_turn: ResMut<ConfirmTurn>,
+) {
+    if turn_start_event_reader.iter().count() > 0 {
+        confirm_turn.staged = None;
+    }
+}
@@ -0,0 +1,46 @@
+// Doubling cube and match score. Only the state is kept so far, the doubling
+// and match rules aren't played yet.
+use serde::{Deserialize, Serialize};
+
+use crate::game::Color;
+
+// Match lengths offered before the first roll.
+pub const MATCH_LENGTHS: [u32; 5] = [1, 3, 5, 7, 11];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoublingCube {
+    pub value: u32,
+    // `None` while the cube is in the middle and either player may double
+    pub owner: Option<Color>,
+}
+
+impl Default for DoublingCube {
+    fn default() -> Self {
+        DoublingCube {
+            value: 1,
+            owner: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchScore {
+    pub length: u32,
+    pub score: [u32; 2], // Indexed like `Board::bar`.
+    // the game right after a player reaches match point, played without the cube
+    pub crawford: bool,
+}
+
+impl MatchScore {
+    pub fn new(length: u32) -> Self {
+        MatchScore {
+            length,
+            ..Default::default()
+        }
+    }
+
+    // No cube in a single game match or in the Crawford game.
+    pub fn cube_enabled(&self) -> bool {
+        self.length > 1 && !self.crawford
+    }
+}
@@ -0,0 +1,135 @@
+// Sandbox editing of the position. While enabled, a click on a checker stacks
+// another one of its color on the point, up to 15 checkers a color.
+use bevy::prelude::*;
+use bevy_mod_picking::PickingEvent;
+
+use crate::{game, toast::ToastEvent, Piece};
+
+#[derive(Default, Resource)]
+pub(crate) struct EditMode {
+    pub(crate) enabled: bool,
+}
+
+// Run condition for the systems playing moves from clicks.
+pub(crate) fn not_editing(edit_mode: Res<EditMode>) -> bool {
+    !edit_mode.enabled
+}
+
+// E switches editing the position on and off.
+pub(crate) fn toggle_edit_mode(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut edit_mode: ResMut<EditMode>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::E) {
+        return;
+    }
+
+    edit_mode.enabled = !edit_mode.enabled;
+    toast_event_writer.send(ToastEvent::new(if edit_mode.enabled {
+        "Editing: click a checker to add another"
+    } else {
+        "Editing off"
+    }));
+}
+
+// The board edit check redraws the pieces after a checker is added. Checkers
+// on the bar and candidate destinations aren't edited.
+pub(crate) fn place_clicked_checker(
+    mut picking_event_reader: EventReader<PickingEvent>,
+    pieces_query: Query<&Piece>,
+    edit_mode: Res<EditMode>,
+    mut game: ResMut<game::Game>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if !edit_mode.enabled {
+        return;
+    }
+
+    for event in picking_event_reader.iter() {
+        let PickingEvent::Clicked(entity) = event else {
+            continue;
+        };
+        let Ok(piece) = pieces_query.get(*entity) else {
+            continue;
+        };
+        let point = piece.board_index().get();
+        if piece.candidate || point == game::BAR {
+            continue;
+        }
+
+        let mut board = game.board.clone();
+        match board.place_checker(piece.color, point) {
+            Ok(()) => game.board = board,
+            Err(e) => toast_event_writer.send(ToastEvent::new(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_app;
+
+    fn edit_app(game: game::Game) -> App {
+        let mut app = test_app();
+        app.add_event::<PickingEvent>()
+            .add_event::<ToastEvent>()
+            .insert_resource(EditMode { enabled: true })
+            .insert_resource(game)
+            .add_system(place_clicked_checker);
+
+        app
+    }
+
+    fn click_checker(app: &mut App, position: usize, color: game::Color) {
+        let entity = app
+            .world
+            .spawn(Piece {
+                row: 1,
+                position,
+                color,
+                highlighted: false,
+                candidate: false,
+                dimmed: false,
+                chosen: false,
+            })
+            .id();
+        app.world.send_event(PickingEvent::Clicked(entity));
+        app.update();
+    }
+
+    fn toasts(app: &App) -> Vec<String> {
+        let events = app.world.resource::<Events<ToastEvent>>();
+        events
+            .get_reader()
+            .iter(events)
+            .map(|toast| toast.text.clone())
+            .collect()
+    }
+
+    #[test]
+    fn sixteenth_checker_is_rejected() {
+        let mut app = edit_app(game::Game::new());
+        let board = app.world.resource::<game::Game>().board.clone();
+
+        // White's 6-point, board index 18
+        click_checker(&mut app, 19, game::Color::White);
+
+        assert_eq!(app.world.resource::<game::Game>().board, board);
+        assert_eq!(toasts(&app), vec!["White already has 15 checkers"]);
+    }
+
+    #[test]
+    fn checker_is_added_below_the_limit() {
+        let mut game = game::Game::new();
+        game.board.points[18] -= 1;
+        let mut app = edit_app(game);
+
+        click_checker(&mut app, 19, game::Color::White);
+
+        let game = app.world.resource::<game::Game>();
+        assert_eq!(game.board.points[18], 5);
+        assert!(toasts(&app).is_empty());
+    }
+}
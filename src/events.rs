@@ -1,13 +1,22 @@
-use bevy::prelude::*;
+use bevy::{ecs::schedule::SystemSetConfigs, prelude::*, window::PrimaryWindow};
 use bevy_dice::*;
 use bevy_kira_audio::prelude::*;
-use bevy_mod_picking::PickingEvent;
+use bevy_mod_picking::{HoverEvent, PickableBundle, PickingEvent, SelectionEvent};
+use bevy_rapier3d::prelude::{RigidBody, Velocity};
+use itertools::Itertools;
+use rand::Rng;
+use std::time::Duration;
 
 use crate::{
+    confirm::ConfirmTurn,
     game::{self, GameLogEntry},
-    spawn_piece, spawn_pieces,
-    ui::{ButtonBearOff, ButtonRollDice, LabelGameOver, LabelMoveStack, LabelPlayerTurn},
-    GameResources, Piece,
+    redraw_pieces, spawn_piece,
+    stats::SessionStats,
+    toast::ToastEvent,
+    ui::{ButtonBearOff, ButtonRollDice, LabelGameOver, LabelMoveStack, StatusBar, SwapDice},
+    undo::UndoHistory,
+    ActiveScenario, AutoBearOff, AutoClearCandidates, AutoFinishRaces, BeginnerAssist,
+    GameResources, Piece, SceneScale, TournamentOpening,
 };
 
 #[derive(Default, Clone, Resource)]
@@ -16,19 +25,18 @@ pub struct HighlightPickablePiecesEvent;
 #[allow(dead_code)]
 #[derive(Clone, Resource)]
 pub struct TurnStartEvent {
-    player: game::Color,
+    pub(crate) player: game::Color,
 }
 
-#[derive(Default, Clone, Resource)]
+#[derive(Clone, Resource)]
 pub struct DisplayPossibleMovesEvent {
-    pub(crate) position: usize,
+    pub(crate) position: game::PointLabel,
     pub(crate) entity: Option<Entity>,
 }
 
-#[derive(Default, Clone, Resource)]
+#[derive(Clone, Resource)]
 pub struct MovePieceEvent {
-    pub(crate) from: usize,
-    pub(crate) to: i32,
+    pub(crate) checker_move: game::Move,
 }
 
 #[derive(Default, Clone, Resource)]
@@ -36,17 +44,238 @@ pub struct MovePieceEndEvent;
 
 #[derive(Clone, Resource)]
 pub struct GameOverEvent {
-    player: game::Color,
+    pub(crate) player: game::Color,
+    pub(crate) reason: GameEndReason,
+}
+
+// How a game was decided. Only games finished on the board count gammons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GameEndReason {
+    BorneOff,
+    // the loser's clock ran out
+    Time,
+}
+
+// Steps of a turn in the order they run each frame, so an event sent by one
+// step is read by the next one in the same frame rather than the frame after.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TurnSet {
+    // buttons and keys that send move events
+    Input,
+    // dice results and the rolls resolving
+    Dice,
+    // moves being applied to the board and the turn passing
+    Move,
+    // pickable pieces lighting up
+    Highlight,
+    // candidate destinations of a picked piece
+    Display,
+    // reactions to a new turn or the game ending
+    TurnEnd,
+}
+
+impl TurnSet {
+    pub(crate) fn chain() -> SystemSetConfigs {
+        (
+            TurnSet::Input,
+            TurnSet::Dice,
+            TurnSet::Move,
+            TurnSet::Highlight,
+            TurnSet::Display,
+            TurnSet::TurnEnd,
+        )
+            .chain()
+    }
 }
 
+// Resolves a roll once the dice settle, `timer` caps the wait.
 #[derive(Component)]
 pub(crate) struct DiceRollTimer {
     pub(crate) timer: Timer,
+    // game log length when the roll started, the result adds an entry
+    pub(crate) log_length: usize,
+    // dice aren't read before this even when they look settled
+    pub(crate) min_roll: Duration,
+}
+
+impl DiceRollTimer {
+    pub(crate) fn new(log_length: usize, playback_speed: PlaybackSpeed) -> Self {
+        DiceRollTimer {
+            timer: Timer::new(playback_speed.scale(DICE_ROLL_WAIT), TimerMode::Once),
+            log_length,
+            min_roll: playback_speed.scale(DICE_MIN_ROLL),
+        }
+    }
+}
+
+// Counts down before passing a turn that has no legal moves, so the player
+// sees why the turn went over.
+#[derive(Component)]
+pub(crate) struct PassTimer {
+    pub(crate) timer: Timer,
+}
+
+impl PassTimer {
+    pub(crate) fn new(playback_speed: PlaybackSpeed) -> Self {
+        PassTimer {
+            timer: Timer::new(playback_speed.scale(PASS_WAIT), TimerMode::Once),
+        }
+    }
+
+    // Whole seconds left, as shown in the countdown.
+    pub(crate) fn seconds_left(&self) -> u64 {
+        self.timer.remaining().as_secs_f32().ceil() as u64
+    }
+}
+
+// Blocked positions can pass back and forth for a few turns, this many in a
+// row without a move points at a bug instead.
+const MAX_CONSECUTIVE_PASSES: u32 = 20;
+
+// Counts passes since the last move played.
+#[derive(Default, Resource)]
+pub(crate) struct PassWatchdog {
+    consecutive: u32,
+}
+
+impl PassWatchdog {
+    pub(crate) fn record_pass(&mut self, game: &game::Game) {
+        self.consecutive += 1;
+        if self.consecutive > MAX_CONSECUTIVE_PASSES {
+            warn!(
+                "{} passes in a row, {:?} passed {:?} after {} rolls in {}",
+                self.consecutive,
+                game.player.opposite(),
+                game.game_log.last().map(|entry| &entry.dice_rolls),
+                game.game_log.len(),
+                game.to_position()
+            );
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.consecutive = 0;
+    }
+}
+
+pub(crate) fn pass_countdown_message(seconds_left: u64) -> String {
+    format!("No moves — passing in {}…", seconds_left)
+}
+
+// Rolls either throw physics dice or show the rolled faces right away.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub(crate) enum DiceRenderMode {
+    #[default]
+    Physics3D,
+    Instant2D,
+}
+
+// Multiplier for waits between game steps, for watching games faster. It
+// shortens the dice wait and the pass countdown and speeds up the bar cue
+// flashing; nothing else plays on its own timer yet.
+#[derive(Clone, Copy, Resource)]
+pub(crate) struct PlaybackSpeed(pub(crate) f32);
+
+impl Default for PlaybackSpeed {
+    fn default() -> Self {
+        PlaybackSpeed(1.0)
+    }
+}
+
+impl PlaybackSpeed {
+    pub(crate) const MIN: f32 = 0.25;
+    pub(crate) const MAX: f32 = 8.0;
+
+    pub(crate) fn set(&mut self, speed: f32) {
+        self.0 = speed.clamp(Self::MIN, Self::MAX);
+    }
+
+    pub(crate) fn scale(&self, duration: Duration) -> Duration {
+        duration.div_f32(self.0)
+    }
+}
+
+const DICE_SETTLE_SPEED: f32 = 0.05;
+const DICE_ROLL_WAIT: Duration = Duration::from_secs(2);
+const DICE_MIN_ROLL: Duration = Duration::from_millis(500);
+const PASS_WAIT: Duration = Duration::from_secs(2);
+
+pub(crate) fn is_settled(velocity: &Velocity) -> bool {
+    velocity.linvel.length() < DICE_SETTLE_SPEED && velocity.angvel.length() < DICE_SETTLE_SPEED
 }
 
 #[derive(Default, Clone, Resource)]
 pub struct StartGameEvent;
 
+// Puts the active scenario back to its start.
+#[derive(Default, Clone, Resource)]
+pub struct RestartScenarioEvent;
+
+// An instant roll is already in the game log and resolves without waiting.
+#[derive(Default, Clone, Resource)]
+pub struct InstantDiceRollEvent;
+
+// Pointer timing and movement limits, adjustable for players who click slowly
+// or with an unsteady hand.
+#[derive(Clone, Copy, Debug, Resource)]
+pub(crate) struct InputSettings {
+    pub(crate) double_click_ms: u64,
+    // a second click further than this from the first doesn't make a double click
+    pub(crate) drag_threshold_px: f32,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        InputSettings {
+            double_click_ms: 300,
+            drag_threshold_px: 8.0,
+        }
+    }
+}
+
+const DOUBLE_CLICK_STEP_MS: u64 = 50;
+const DOUBLE_CLICK_MS_RANGE: (u64, u64) = (100, 1000);
+
+impl InputSettings {
+    // Longer or shorter double click window by `steps` of `DOUBLE_CLICK_STEP_MS`.
+    pub(crate) fn adjust_double_click(&mut self, steps: i64) {
+        let (min, max) = DOUBLE_CLICK_MS_RANGE;
+        self.double_click_ms = (self.double_click_ms as i64 + steps * DOUBLE_CLICK_STEP_MS as i64)
+            .clamp(min as i64, max as i64) as u64;
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct LastClick {
+    entity: Option<Entity>,
+    time: f32,
+    cursor: Option<Vec2>,
+}
+
+impl LastClick {
+    // Records a click on `entity` at `time` seconds, true when it completes a
+    // double click on it.
+    pub(crate) fn register(
+        &mut self,
+        entity: Entity,
+        time: f32,
+        cursor: Option<Vec2>,
+        input_settings: &InputSettings,
+    ) -> bool {
+        let in_time = (time - self.time) * 1000.0 < input_settings.double_click_ms as f32;
+        let in_place = match (self.cursor, cursor) {
+            (Some(last), Some(cursor)) => last.distance(cursor) <= input_settings.drag_threshold_px,
+            _ => true,
+        };
+        let double_click = self.entity == Some(entity) && in_time && in_place;
+
+        self.entity = Some(entity);
+        self.time = time;
+        self.cursor = cursor;
+        double_click
+    }
+}
+
 pub(crate) fn event_dice_roll_result(
     mut dice_rolls: EventReader<DiceRollResult>,
     mut game: ResMut<game::Game>,
@@ -59,6 +288,7 @@ pub(crate) fn event_dice_roll_result(
         game.game_log.push(GameLogEntry {
             player,
             dice_rolls: event.values[0].clone(),
+            opening_tie: false,
         });
     }
 }
@@ -66,51 +296,183 @@ pub(crate) fn event_dice_roll_result(
 pub(crate) fn event_dice_rolls_complete(
     mut commands: Commands,
     mut dice_roll_timer_query: Query<(Entity, &mut DiceRollTimer)>,
+    mut instant_dice_roll_event_reader: EventReader<InstantDiceRollEvent>,
+    dice_query: Query<(&RigidBody, &Velocity)>,
     time: Res<Time>,
     mut game: ResMut<game::Game>,
     mut turn_start_event_writer: EventWriter<TurnStartEvent>,
     mut highlight_pickable_pieces_event_writer: EventWriter<HighlightPickablePiecesEvent>,
+    mut move_piece_event_writer: EventWriter<MovePieceEvent>,
+    auto_bear_off: Res<AutoBearOff>,
+    playback_speed: Res<PlaybackSpeed>,
 ) {
+    let mut settled = instant_dice_roll_event_reader.iter().count() > 0;
+
     for (entity, mut fuse_timer) in dice_roll_timer_query.iter_mut() {
         fuse_timer.timer.tick(time.delta());
 
-        if fuse_timer.timer.finished() {
-            let last_log_entry = game.game_log.last_mut().unwrap();
-            let mut dice_rolls = last_log_entry.dice_rolls.clone();
+        // the dice have to come to rest, or the fallback run out, before the
+        // result counts
+        let dice_settled = fuse_timer.timer.elapsed() >= fuse_timer.min_roll
+            && dice_query
+                .iter()
+                .filter(|(rigid_body, _)| **rigid_body == RigidBody::Dynamic)
+                .all(|(_, velocity)| is_settled(velocity));
+        if !dice_settled && !fuse_timer.timer.finished() {
+            continue;
+        }
 
-            if dice_rolls[0] == dice_rolls[1] {
-                dice_rolls.push(dice_rolls[0]);
-                dice_rolls.push(dice_rolls[0]);
-            }
+        // bevy_dice logs the faces once it has read them
+        if game.game_log.len() <= fuse_timer.log_length {
+            continue;
+        }
 
-            game.dice_rolls = dice_rolls;
-            commands.entity(entity).despawn();
+        commands.entity(entity).despawn();
+        settled = true;
+        break;
+    }
 
-            let possible_moves = game.get_possible_moves(game.player, game.dice_rolls.clone());
+    if !settled {
+        return;
+    }
 
-            if possible_moves.is_empty() {
-                game.switch_turn();
+    let last_log_entry = game.game_log.last_mut().unwrap();
+    let mut dice_rolls = last_log_entry.dice_rolls.clone();
 
-                turn_start_event_writer.send(TurnStartEvent {
-                    player: game.player,
-                });
-            } else {
-                highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
-            }
+    if game.opening_roll {
+        // first die is White's, second is Black's
+        if !game.resolve_opening_roll(dice_rolls[0], dice_rolls[1]) {
+            game.game_log.last_mut().unwrap().opening_tie = true;
+            turn_start_event_writer.send(TurnStartEvent {
+                player: game.player,
+            });
+            return;
+        }
+
+        let player = game.player;
+        game.game_log.last_mut().unwrap().player = player;
+    } else {
+        if dice_rolls[0] == dice_rolls[1] {
+            dice_rolls.push(dice_rolls[0]);
+            dice_rolls.push(dice_rolls[0]);
+        }
+
+        game.dice_rolls = dice_rolls;
+    }
+
+    let possible_moves = game.get_possible_moves(game.player, game.dice_rolls.clone());
 
-            break;
+    if possible_moves.is_empty() {
+        commands
+            .spawn(PassTimer::new(*playback_speed))
+            .insert(Name::new("Pass Timer"));
+    } else if let Some(moves) = game
+        .forced_bear_off(game.player)
+        .filter(|_| auto_bear_off.enabled)
+    {
+        for checker_move in moves {
+            move_piece_event_writer.send(MovePieceEvent { checker_move });
         }
+    } else {
+        highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
     }
 }
 
+// Tournament games skip rolling for the opening, the seed decides it.
+pub(crate) fn play_seeded_opening(
+    mut game: ResMut<game::Game>,
+    tournament_opening: Res<TournamentOpening>,
+    mut button_roll_dice_query: Query<&mut Visibility, With<ButtonRollDice>>,
+    mut highlight_pickable_pieces_event_writer: EventWriter<HighlightPickablePiecesEvent>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    let Some(seed) = tournament_opening.seed else {
+        return;
+    };
+    if !game.opening_roll || game.dice_rolled {
+        return;
+    }
+
+    let Some([white, black]) = game.seeded_opening_roll(seed) else {
+        return;
+    };
+
+    for mut visibility in button_roll_dice_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    toast_event_writer.send(ToastEvent::new(format!(
+        "Opening roll {}-{} from seed {}, {:?} starts",
+        white, black, seed, game.player
+    )));
+    highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
+}
+
+// Scenario turns take their dice from the script instead of a roll.
+pub(crate) fn play_scenario_rolls(
+    mut game: ResMut<game::Game>,
+    active_scenario: Res<ActiveScenario>,
+    mut button_roll_dice_query: Query<&mut Visibility, With<ButtonRollDice>>,
+    mut highlight_pickable_pieces_event_writer: EventWriter<HighlightPickablePiecesEvent>,
+) {
+    let Some(scenario) = &active_scenario.scenario else {
+        return;
+    };
+    if game.dice_rolled || game.is_over() || !scenario.roll(&mut game) {
+        return;
+    }
+
+    for mut visibility in button_roll_dice_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
+}
+
+// R starts the scenario over, the board edit check redraws the pieces.
+pub(crate) fn restart_scenario(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut restart_scenario_event_writer: EventWriter<RestartScenarioEvent>,
+    mut restart_scenario_event_reader: EventReader<RestartScenarioEvent>,
+    active_scenario: Res<ActiveScenario>,
+    mut game: ResMut<game::Game>,
+    mut undo_history: ResMut<UndoHistory>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::R) {
+        restart_scenario_event_writer.send(RestartScenarioEvent);
+    }
+
+    if restart_scenario_event_reader.iter().count() == 0 {
+        return;
+    }
+    let Some(scenario) = &active_scenario.scenario else {
+        return;
+    };
+
+    *game = scenario.start();
+    undo_history.clear();
+    toast_event_writer.send(ToastEvent::new("Scenario restarted"));
+}
+
 pub(crate) fn handle_piece_picking(
     mut picking_event_reader: EventReader<PickingEvent>,
     mut pieces_query: Query<(Entity, &mut Piece)>,
     mut display_possible_moves_event_writer: EventWriter<DisplayPossibleMovesEvent>,
     mut move_piece_event_writer: EventWriter<MovePieceEvent>,
+    mut last_click: Local<LastClick>,
+    game: Res<game::Game>,
+    time: Res<Time>,
+    input_settings: Res<InputSettings>,
+    primary_window_query: Query<&Window, With<PrimaryWindow>>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
+    confirm_turn: Res<ConfirmTurn>,
 ) {
+    let game = confirm_turn.shown(&game);
+    let cursor = primary_window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
     for event in picking_event_reader.iter() {
         if let PickingEvent::Clicked(e) = event {
             // remove selection from Piece entity
@@ -119,23 +481,49 @@ pub(crate) fn handle_piece_picking(
 
             let all_pieces = pieces_query
                 .iter()
-                .map(|(_, piece)| *piece)
+                .map(|(entity, piece)| (entity, *piece))
                 .collect::<Vec<_>>();
 
+            let target = stack_top(&all_pieces, e);
+
             for (entity, piece) in pieces_query.iter_mut() {
-                if entity.index() == e.index() {
+                if entity.index() == target.index() {
+                    // opponent checkers left pickable aren't the player's to move
+                    if piece.color != game.player && !piece.candidate {
+                        continue;
+                    }
+
                     if piece.highlighted {
+                        let double_click = last_click.register(
+                            entity,
+                            time.elapsed_seconds(),
+                            cursor,
+                            &input_settings,
+                        );
+
+                        // play an only move straight away on double click
+                        let possible_moves = game
+                            .get_possible_moves_for_piece(game.player, piece.board_index().get());
+                        if double_click && possible_moves.len() == 1 {
+                            move_piece_event_writer.send(MovePieceEvent {
+                                checker_move: possible_moves[0],
+                            });
+                            continue;
+                        }
+
                         display_possible_moves_event_writer.send(DisplayPossibleMovesEvent {
-                            position: piece.position,
+                            position: piece.label(),
                             entity: Some(entity),
                         });
                     }
 
                     if piece.candidate {
-                        let chosen_piece = all_pieces.iter().find(|p| p.chosen).unwrap();
+                        let (_, chosen_piece) = all_pieces.iter().find(|(_, p)| p.chosen).unwrap();
                         move_piece_event_writer.send(MovePieceEvent {
-                            from: chosen_piece.position,
-                            to: piece.position as i32,
+                            checker_move: game::Move::between(
+                                chosen_piece.board_index().get(),
+                                piece.board_index().get() as i32,
+                            ),
                         });
                     }
                 }
@@ -144,90 +532,379 @@ pub(crate) fn handle_piece_picking(
     }
 }
 
+// A click that misses the player's checkers and the candidates deselects the
+// chosen checker, along with its candidate destinations and dimmed points.
+pub(crate) fn clear_candidates_on_deselect(
+    mut commands: Commands,
+    mut picking_event_reader: EventReader<PickingEvent>,
+    mut pieces_query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
+    auto_clear_candidates: Res<AutoClearCandidates>,
+    game_resources: Res<GameResources>,
+) {
+    let mut deselected = false;
+    let mut clicked = false;
+    for event in picking_event_reader.iter() {
+        match event {
+            PickingEvent::Selection(SelectionEvent::JustDeselected(_)) => deselected = true,
+            // picking another checker or a candidate is left to `handle_piece_picking`
+            PickingEvent::Clicked(_) => clicked = true,
+            _ => {}
+        }
+    }
+
+    if !deselected || clicked || !auto_clear_candidates.enabled {
+        return;
+    }
+
+    for (entity, mut piece, mut material) in pieces_query.iter_mut() {
+        if piece.candidate || piece.dimmed {
+            commands.entity(entity).despawn();
+        } else if piece.chosen {
+            piece.chosen = false;
+            *material = piece.material(&game_resources);
+        }
+    }
+}
+
+// The highlighted top checker of the stack `clicked` is in, or `clicked` itself.
+pub(crate) fn stack_top(pieces: &[(Entity, Piece)], clicked: &Entity) -> Entity {
+    let Some((_, clicked_piece)) = pieces.iter().find(|(entity, _)| entity == clicked) else {
+        return *clicked;
+    };
+    if clicked_piece.highlighted || clicked_piece.candidate {
+        return *clicked;
+    }
+
+    pieces
+        .iter()
+        .find(|(_, piece)| {
+            piece.highlighted
+                && piece.position == clicked_piece.position
+                && piece.color == clicked_piece.color
+        })
+        .map_or(*clicked, |(entity, _)| *entity)
+}
+
+// Preview of the hovered candidate move: the landing checker is shown as a
+// ghost and an opponent blot it would hit fades, with its ghost on the bar.
+#[derive(Default, Resource)]
+pub(crate) struct GhostPreview {
+    pub(crate) candidate: Option<Entity>,
+    // opponent checker the previewed move hits
+    pub(crate) hit: Option<Entity>,
+}
+
+// Ghost of a hit checker shown on the bar.
+#[derive(Component)]
+pub(crate) struct GhostToBar;
+
+// Opponent blot sitting on the point of `candidate`.
+fn hit_by_candidate(
+    game: &game::Game,
+    pieces_query: &Query<(Entity, &Piece, &mut Handle<StandardMaterial>)>,
+    candidate: &Piece,
+) -> Option<Entity> {
+    let opponent = game.player.opposite();
+    let point = candidate.board_index().get();
+    if point >= 24 || game.board.points[point] != opponent.sign() {
+        return None;
+    }
+
+    pieces_query
+        .iter()
+        .find(|(_, piece, _)| {
+            piece.position == candidate.position
+                && piece.color == opponent
+                && !piece.candidate
+                && !piece.dimmed
+        })
+        .map(|(entity, _, _)| entity)
+}
+
+fn clear_ghost_preview(
+    commands: &mut Commands,
+    ghost_preview: &mut GhostPreview,
+    pieces_query: &mut Query<(Entity, &Piece, &mut Handle<StandardMaterial>)>,
+    ghost_query: &Query<Entity, With<GhostToBar>>,
+    game_resources: &GameResources,
+) {
+    for entity in [ghost_preview.candidate.take(), ghost_preview.hit.take()]
+        .into_iter()
+        .flatten()
+    {
+        if let Ok((_, piece, mut material)) = pieces_query.get_mut(entity) {
+            *material = piece.material(game_resources);
+        }
+    }
+
+    for entity in ghost_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+pub(crate) fn handle_ghost_preview(
+    mut commands: Commands,
+    mut picking_event_reader: EventReader<PickingEvent>,
+    mut ghost_preview: ResMut<GhostPreview>,
+    mut pieces_query: Query<(Entity, &Piece, &mut Handle<StandardMaterial>)>,
+    ghost_query: Query<Entity, With<GhostToBar>>,
+    game: Res<game::Game>,
+    game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
+    confirm_turn: Res<ConfirmTurn>,
+) {
+    let game = confirm_turn.shown(&game);
+    // candidates are despawned without a hover event when another piece is chosen
+    let candidate_gone = ghost_preview
+        .candidate
+        .is_some_and(|candidate| pieces_query.get(candidate).is_err());
+    if candidate_gone {
+        clear_ghost_preview(
+            &mut commands,
+            &mut ghost_preview,
+            &mut pieces_query,
+            &ghost_query,
+            &game_resources,
+        );
+    }
+
+    for event in picking_event_reader.iter() {
+        match event {
+            PickingEvent::Hover(HoverEvent::JustEntered(entity)) => {
+                let Ok((_, candidate, _)) = pieces_query.get(*entity) else {
+                    continue;
+                };
+                if !candidate.candidate {
+                    continue;
+                }
+                let candidate = *candidate;
+
+                clear_ghost_preview(
+                    &mut commands,
+                    &mut ghost_preview,
+                    &mut pieces_query,
+                    &ghost_query,
+                    &game_resources,
+                );
+
+                ghost_preview.candidate = Some(*entity);
+                if let Ok((_, _, mut material)) = pieces_query.get_mut(*entity) {
+                    *material = game_resources.ghost_material.clone();
+                }
+
+                let Some(hit) = hit_by_candidate(game, &pieces_query, &candidate) else {
+                    continue;
+                };
+                ghost_preview.hit = Some(hit);
+                if let Ok((_, _, mut material)) = pieces_query.get_mut(hit) {
+                    *material = game_resources.dimmed_material.clone();
+                }
+
+                let opponent = game.player.opposite();
+                let ghost = spawn_piece(
+                    &mut commands,
+                    Piece {
+                        position: game::BAR + 1,
+                        row: game.board.bar[game.board.bar_index(opponent)] as usize + 1,
+                        color: opponent,
+                        highlighted: false,
+                        candidate: false,
+                        dimmed: true,
+                        chosen: false,
+                    },
+                    game_resources.clone(),
+                    *scene_scale,
+                );
+                commands
+                    .entity(ghost)
+                    .insert(game_resources.ghost_material.clone())
+                    .insert(GhostToBar);
+            }
+            PickingEvent::Hover(HoverEvent::JustLeft(entity))
+                if ghost_preview.candidate == Some(*entity) =>
+            {
+                clear_ghost_preview(
+                    &mut commands,
+                    &mut ghost_preview,
+                    &mut pieces_query,
+                    &ghost_query,
+                    &game_resources,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
 pub(crate) fn handle_display_possible_moves(
     mut commands: Commands,
     mut display_possible_moves_event_reader: EventReader<DisplayPossibleMovesEvent>,
-    mut pieces_query: Query<(Entity, &mut Piece)>,
+    mut pieces_query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
     mut button_bear_off_query: Query<(&mut Visibility, &mut Style, &mut ButtonBearOff)>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
     game: Res<game::Game>,
     game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
+    beginner_assist: Res<BeginnerAssist>,
+    confirm_turn: Res<ConfirmTurn>,
 ) {
+    let game = confirm_turn.shown(&game);
     for event in display_possible_moves_event_reader.iter() {
-        let possible_positions = game.get_possible_moves_for_piece(game.player, event.position - 1);
+        let possible_moves =
+            game.get_possible_moves_for_piece(game.player, event.position.index().get());
+        let combined_moves =
+            game.get_combined_moves_for_piece(game.player, event.position.index().get());
 
-        // Despawn possible candidates
+        // Despawn possible candidates and dimmed points
         pieces_query
             .iter()
-            .filter(|(_, piece)| piece.candidate)
-            .for_each(|(entity, _)| {
+            .filter(|(_, piece, _)| piece.candidate || piece.dimmed)
+            .for_each(|(entity, _, _)| {
                 commands.entity(entity).despawn();
             });
 
         // Set chosen piece
-        pieces_query.iter_mut().for_each(|(entity, mut piece)| {
-            piece.chosen = entity.index() == event.entity.unwrap().index();
-        });
+        pieces_query
+            .iter_mut()
+            .for_each(|(entity, mut piece, mut material)| {
+                let chosen = entity.index() == event.entity.unwrap().index();
+                if piece.chosen != chosen {
+                    piece.chosen = chosen;
+                    *material = piece.material(&game_resources);
+                }
+            });
 
         for (mut visibility, mut style, mut button) in &mut button_bear_off_query.iter_mut() {
             *visibility = Visibility::Hidden;
             style.display = Display::None;
-            button.position_to = None;
+            button.bear_off = None;
         }
 
-        for position in possible_positions.iter() {
-            if *position >= 24 || *position < 0 {
+        for checker_move in possible_moves.iter() {
+            let Some(position) = checker_move.destination() else {
                 for (mut visibility, mut style, mut button) in &mut button_bear_off_query.iter_mut()
                 {
                     *visibility = Visibility::Inherited;
                     style.display = Display::Flex;
-                    button.position_to = Some(*position + 1);
+                    button.bear_off = Some(*checker_move);
                 }
-                break;
-            }
+                continue;
+            };
 
             // Moves on board
-            let row = game.board.get_next_free_row(*position as usize);
+            let row = game.board.get_next_free_row(position);
             spawn_piece(
                 &mut commands,
                 Piece {
-                    position: (*position + 1) as usize,
+                    position: game::BoardIndex(position as u8).label().get(),
                     row,
                     color: game.player,
                     highlighted: false,
                     candidate: true,
+                    dimmed: false,
+                    chosen: false,
+                },
+                game_resources.clone(),
+                *scene_scale,
+            );
+        }
+
+        // Combined moves using both dice
+        let combined_positions = combined_moves
+            .iter()
+            .filter_map(|path| path[1].destination())
+            .filter(|position| {
+                !possible_moves
+                    .iter()
+                    .any(|checker_move| checker_move.destination() == Some(*position))
+            })
+            .unique()
+            .collect::<Vec<_>>();
+
+        for position in combined_positions.iter() {
+            spawn_piece(
+                &mut commands,
+                Piece {
+                    position: game::BoardIndex(*position as u8).label().get(),
+                    row: game.board.get_next_free_row(*position),
+                    color: game.player,
+                    highlighted: false,
+                    candidate: true,
+                    dimmed: false,
                     chosen: false,
                 },
                 game_resources.clone(),
+                *scene_scale,
             );
         }
+
+        if combined_moves.len() > combined_positions.len() {
+            toast_event_writer.send(ToastEvent::new("Press S to swap the dice order"));
+        }
+
+        if beginner_assist.enabled {
+            for position in
+                game.get_illegal_destinations_for_piece(game.player, event.position.index().get())
+            {
+                spawn_piece(
+                    &mut commands,
+                    Piece {
+                        position: game::BoardIndex(position as u8).label().get(),
+                        row: game.board.get_next_free_row(position),
+                        color: game.player,
+                        highlighted: false,
+                        candidate: false,
+                        dimmed: true,
+                        chosen: false,
+                    },
+                    game_resources.clone(),
+                    *scene_scale,
+                );
+            }
+        }
     }
 }
 
+// Highlights pieces in place, so repeated events don't respawn them. Pieces
+// respawned by a move are in the world already, see the schedule in main.
 pub(crate) fn handle_hightlight_choosable_pieces(
     mut commands: Commands,
     game: Res<game::Game>,
-    mut query: Query<(Entity, &Piece)>,
+    mut query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
     game_resources: Res<GameResources>,
     mut player_turn_event_choose_piece_event_reader: EventReader<HighlightPickablePiecesEvent>,
+    confirm_turn: Res<ConfirmTurn>,
 ) {
     if player_turn_event_choose_piece_event_reader.iter().count() == 0 {
         return;
     }
+    let game = confirm_turn.shown(&game);
 
-    let (choosable_points, _) = game.get_choosable_pieces();
+    let (choosable_points, choosable_bar_pieces) = game.get_choosable_pieces();
 
-    for (entity, piece) in &mut query.iter_mut() {
-        for choosable_point in choosable_points.iter() {
-            if piece.position == choosable_point[0] && piece.row == choosable_point[1] {
-                if piece.highlighted {
-                    continue;
-                }
-                commands.entity(entity).despawn();
-                let mut new_piece = *piece;
-                new_piece.highlighted = true;
-                spawn_piece(&mut commands, new_piece, game_resources.clone());
-            }
+    for (entity, mut piece, mut material) in &mut query.iter_mut() {
+        // the top checker of the stack is highlighted, clicks on the ones below
+        // it are passed on to the top one
+        let top_row = if piece.position == game::BAR + 1 {
+            Some(choosable_bar_pieces[game.board.bar_index(piece.color)]).filter(|row| *row > 0)
+        } else {
+            choosable_points
+                .iter()
+                .find(|[position, _]| piece.position == *position)
+                .map(|[_, row]| *row)
+        };
+
+        let Some(top_row) = top_row.filter(|_| piece.color == game.player) else {
+            continue;
+        };
+
+        commands.entity(entity).insert(PickableBundle::default());
+        if piece.row != top_row || piece.highlighted {
+            continue;
         }
+
+        piece.highlighted = true;
+        *material = piece.material(&game_resources);
     }
 }
 
@@ -236,63 +913,173 @@ pub(crate) fn handle_move_piece_event(
     mut display_possible_moves_event_reader: EventReader<MovePieceEvent>,
     mut highlight_pickable_pieces_event_writer: EventWriter<HighlightPickablePiecesEvent>,
     mut move_piece_end_event_writer: EventWriter<MovePieceEndEvent>,
-    pieces_query: Query<(Entity, &Piece)>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+    mut pieces_query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
     mut game: ResMut<game::Game>,
     game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
+    swap_dice: Res<SwapDice>,
+    mut undo_history: ResMut<UndoHistory>,
+    mut confirm_turn: ResMut<ConfirmTurn>,
 ) {
     if display_possible_moves_event_reader.is_empty() {
         return;
     }
 
+    // moves wait on a working copy of the game until the turn is confirmed
+    if confirm_turn.enabled {
+        let staged = confirm_turn
+            .staged
+            .get_or_insert_with(|| game::StagedTurn::new(&game));
+
+        for event in display_possible_moves_event_reader.iter() {
+            let player = staged.working().player;
+            let moves = match single_moves(staged.working(), event.checker_move, swap_dice.0) {
+                Ok(moves) => moves,
+                Err(e) => {
+                    warn!("Rejected {:?}: {}", event.checker_move, e);
+                    continue;
+                }
+            };
+
+            for checker_move in moves {
+                match staged.stage(checker_move) {
+                    Ok(true) => {
+                        toast_event_writer.send(ToastEvent::new(format!("{:?} hits!", player)));
+                    }
+                    Ok(false) => {}
+                    Err(e) => warn!("Couldn't stage {:?}: {}", checker_move, e),
+                }
+            }
+        }
+
+        if !staged.is_complete() {
+            highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
+        }
+
+        redraw_pieces(
+            &mut commands,
+            &mut pieces_query,
+            staged.working(),
+            &game_resources,
+            *scene_scale,
+        );
+        return;
+    }
+
+    let mut moved = false;
     for event in display_possible_moves_event_reader.iter() {
         let player = game.player;
-        game.board
-            .make_move(player, event.from - 1, event.to - 1)
-            .unwrap();
-
-        let move_ = (event.to - event.from as i32).unsigned_abs() as usize;
-        let number_of_same_moves = game.dice_rolls.iter().filter(|&&x| x == move_).count();
-        game.dice_rolls = game
-            .dice_rolls
-            .iter()
-            .filter(|&&x| x != move_)
-            .cloned()
-            .collect();
 
-        if number_of_same_moves > 1 {
-            for _ in 0..number_of_same_moves - 1 {
-                game.dice_rolls.push(move_);
+        // every part of the move is tried on a copy first, so a rejected move
+        // leaves the game as it was
+        let mut trial = game::StagedTurn::new(&game);
+        let hits = single_moves(&game, event.checker_move, swap_dice.0).and_then(|moves| {
+            moves
+                .into_iter()
+                .map(|checker_move| trial.stage(checker_move))
+                .collect::<Result<Vec<_>, _>>()
+        });
+        let hits = match hits {
+            Ok(hits) => hits,
+            Err(e) => {
+                warn!("Rejected {:?}: {}", event.checker_move, e);
+                continue;
             }
+        };
+
+        undo_history.push(&game);
+        if let Err(e) = trial.confirm(&mut game) {
+            warn!("Rejected {:?}: {}", event.checker_move, e);
+            continue;
+        }
+        moved = true;
+
+        for _ in hits.into_iter().filter(|hit| *hit) {
+            toast_event_writer.send(ToastEvent::new(format!("{:?} hits!", player)));
         }
     }
 
+    if !moved {
+        return;
+    }
+
+    // dice left over that no checker can play would leave the turn hanging
+    // without highlights, drop them so the end event passes the turn
+    if !game.dice_rolls.is_empty() && !game.can_move(game.player) {
+        warn!(
+            "{:?} has no move for {:?}, ending the turn",
+            game.player, game.dice_rolls
+        );
+        game.dice_rolls.clear();
+    }
+
     if !game.dice_rolls.is_empty() {
         highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
     }
 
-    // redraw the board
-    pieces_query.iter().for_each(|(entity, _)| {
-        commands.entity(entity).despawn();
-    });
-    spawn_pieces(commands, game, game_resources);
+    redraw_pieces(
+        &mut commands,
+        &mut pieces_query,
+        &game,
+        &game_resources,
+        *scene_scale,
+    );
 
     move_piece_end_event_writer.send(MovePieceEndEvent);
 }
 
+// Moves of one checker using a single die each. A combined move using both
+// dice is split in the order the player prefers.
+fn single_moves(
+    game: &game::Game,
+    checker_move: game::Move,
+    swap_dice: bool,
+) -> Result<Vec<game::Move>, String> {
+    let player = game.player;
+    let (from, to) = (
+        checker_move.source(),
+        checker_move.destination_index(player),
+    );
+    if game
+        .board
+        .is_die_sized_move(player, from, to, &game.dice_rolls)
+    {
+        return Ok(vec![checker_move]);
+    }
+
+    let paths = game
+        .get_combined_moves_for_piece(player, checker_move.source())
+        .into_iter()
+        .filter(|path| path[1].destination() == checker_move.destination())
+        .collect::<Vec<_>>();
+    let path = if swap_dice {
+        paths.last()
+    } else {
+        paths.first()
+    };
+
+    path.map(|path| path.to_vec())
+        .ok_or_else(|| String::from("Move doesn't match any die"))
+}
+
 pub(crate) fn handle_move_piece_end_event(
     mut move_piece_end_event_reader: EventReader<MovePieceEndEvent>,
     mut turn_start_event_writer: EventWriter<TurnStartEvent>,
     mut game_over_event_writer: EventWriter<GameOverEvent>,
     mut game: ResMut<game::Game>,
+    mut pass_watchdog: ResMut<PassWatchdog>,
 ) {
     if move_piece_end_event_reader.is_empty() {
         return;
     }
+    pass_watchdog.reset();
 
     for _ in move_piece_end_event_reader.iter() {
         if game.is_over() {
             game_over_event_writer.send(GameOverEvent {
                 player: game.player,
+                reason: GameEndReason::BorneOff,
             });
             return;
         }
@@ -308,14 +1095,82 @@ pub(crate) fn handle_move_piece_end_event(
     }
 }
 
+pub(crate) fn handle_pass_timer(
+    mut commands: Commands,
+    mut pass_timer_query: Query<(Entity, &mut PassTimer)>,
+    time: Res<Time>,
+    mut game: ResMut<game::Game>,
+    mut pass_watchdog: ResMut<PassWatchdog>,
+    mut turn_start_event_writer: EventWriter<TurnStartEvent>,
+) {
+    for (entity, mut pass_timer) in pass_timer_query.iter_mut() {
+        if !pass_timer.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+
+        // the game moved on during the countdown, e.g. a new game was started
+        if !game.pass_turn() {
+            continue;
+        }
+        pass_watchdog.record_pass(&game);
+
+        turn_start_event_writer.send(TurnStartEvent {
+            player: game.player,
+        });
+    }
+}
+
+// Frames a turn may stay stalled before the watchdog passes it.
+const STALL_FRAMES: u32 = 2;
+
+// Passes the turn when a resolved roll left the player without moves and
+// nothing else switched turns, so the game can't get stuck.
+pub(crate) fn turn_watchdog(
+    mut stalled_frames: Local<u32>,
+    dice_roll_timer_query: Query<(), With<DiceRollTimer>>,
+    pass_timer_query: Query<(), With<PassTimer>>,
+    mut game: ResMut<game::Game>,
+    mut pass_watchdog: ResMut<PassWatchdog>,
+    mut turn_start_event_writer: EventWriter<TurnStartEvent>,
+) {
+    if !dice_roll_timer_query.is_empty() || !pass_timer_query.is_empty() || !game.is_stalled() {
+        *stalled_frames = 0;
+        return;
+    }
+
+    *stalled_frames += 1;
+    if *stalled_frames < STALL_FRAMES {
+        return;
+    }
+
+    warn!(
+        "{:?} can't play {:?} and the turn wasn't passed, passing it",
+        game.player, game.dice_rolls
+    );
+    *stalled_frames = 0;
+    game.pass_turn();
+    pass_watchdog.record_pass(&game);
+
+    turn_start_event_writer.send(TurnStartEvent {
+        player: game.player,
+    });
+}
+
 pub(crate) fn handle_dice_roll_start_event(
     mut dice_roll_start_event_reader: EventReader<DiceRollStartEvent>,
+    mut instant_dice_roll_event_reader: EventReader<InstantDiceRollEvent>,
     mut query_button_roll_dice: Query<&mut Visibility, With<ButtonRollDice>>,
 ) {
-    for _ in dice_roll_start_event_reader.iter() {
-        for mut visibility in query_button_roll_dice.iter_mut() {
-            *visibility = Visibility::Hidden;
-        }
+    let rolls =
+        dice_roll_start_event_reader.iter().count() + instant_dice_roll_event_reader.iter().count();
+    if rolls == 0 {
+        return;
+    }
+
+    for mut visibility in query_button_roll_dice.iter_mut() {
+        *visibility = Visibility::Hidden;
     }
 }
 
@@ -331,17 +1186,65 @@ pub(crate) fn handle_turn_start_event(
     }
 }
 
+// A race that can't be lost any more is played out at the start of the turn.
+pub(crate) fn auto_finish_race(
+    mut commands: Commands,
+    mut turn_start_event_reader: EventReader<TurnStartEvent>,
+    mut game_over_event_writer: EventWriter<GameOverEvent>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+    mut pieces_query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
+    mut game: ResMut<game::Game>,
+    game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
+    auto_finish_races: Res<AutoFinishRaces>,
+) {
+    if turn_start_event_reader.iter().last().is_none()
+        || !auto_finish_races.enabled
+        || game.opening_roll
+    {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let Some(winner) = game.finish_race(|| [rng.gen_range(1..=6), rng.gen_range(1..=6)]) else {
+        return;
+    };
+
+    redraw_pieces(
+        &mut commands,
+        &mut pieces_query,
+        &game,
+        &game_resources,
+        *scene_scale,
+    );
+    toast_event_writer.send(ToastEvent::new(format!(
+        "{:?} can't lose the race, finishing",
+        winner
+    )));
+    game_over_event_writer.send(GameOverEvent {
+        player: winner,
+        reason: GameEndReason::BorneOff,
+    });
+}
+
 pub(crate) fn handle_game_over_event(
     mut event_game_over_reader: EventReader<GameOverEvent>,
+    game: Res<game::Game>,
+    session_stats: Res<SessionStats>,
     mut ui_elements_param_set: ParamSet<(
         Query<(&mut Visibility, With<ButtonRollDice>)>,
         Query<(&mut Visibility, With<ButtonBearOff>)>,
-        Query<(&mut Visibility, With<LabelPlayerTurn>)>,
+        Query<(&mut Visibility, With<StatusBar>)>,
         Query<(&mut Visibility, With<LabelMoveStack>)>,
         Query<(&mut Text, &mut Visibility, With<LabelGameOver>)>,
     )>,
 ) {
     for e in event_game_over_reader.iter() {
+        let mut summary = game.summary(e.player);
+        if e.reason == GameEndReason::Time {
+            summary.win_kind = game::WinKind::Single;
+        }
+
         for (mut v, _) in ui_elements_param_set.p0().iter_mut() {
             *v = Visibility::Hidden;
         }
@@ -365,10 +1268,29 @@ pub(crate) fn handle_game_over_event(
                 game::Color::White => Color::WHITE,
                 game::Color::Black => Color::BLACK,
             };
+            text.sections[1].value = format!(
+                "\n{}\n{}",
+                format_game_summary(&summary),
+                session_stats.summary()
+            );
         }
     }
 }
 
+pub(crate) fn format_game_summary(summary: &game::GameSummary) -> String {
+    format!(
+        "{:?} win, cube {} ({} points) after {} turns\nPips: White {}, Black {}\nWhite hit {}, Black hit {}",
+        summary.win_kind,
+        summary.cube_value,
+        summary.points(),
+        summary.turns,
+        summary.pip_counts[0],
+        summary.pip_counts[1],
+        summary.hits[0],
+        summary.hits[1]
+    )
+}
+
 pub(crate) fn handle_start_game_event(
     mut start_game_event_reader: EventReader<StartGameEvent>,
     asset_server: Res<AssetServer>,
@@ -379,3 +1301,631 @@ pub(crate) fn handle_start_game_event(
         audio.play(sound).looped();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::test_app, PieceId};
+    use std::collections::HashSet;
+
+    fn move_app(game: game::Game) -> App {
+        let mut app = test_app();
+        app.add_event::<MovePieceEvent>()
+            .add_event::<MovePieceEndEvent>()
+            .add_event::<HighlightPickablePiecesEvent>()
+            .add_event::<ToastEvent>()
+            .init_resource::<SwapDice>()
+            .init_resource::<UndoHistory>()
+            .init_resource::<ConfirmTurn>()
+            .insert_resource(game)
+            .add_system(handle_move_piece_event.in_set(TurnSet::Move));
+
+        app
+    }
+
+    fn rolled(dice_rolls: Vec<usize>) -> game::Game {
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+        game.dice_rolled = true;
+        game.dice_rolls = dice_rolls;
+        game
+    }
+
+    #[test]
+    fn move_not_matching_a_die_is_dropped() {
+        let mut app = move_app(rolled(vec![3, 5]));
+        let board = app.world.resource::<game::Game>().board.clone();
+
+        app.world.send_event(MovePieceEvent {
+            checker_move: game::Move::between(0, 4),
+        });
+        app.update();
+
+        let game = app.world.resource::<game::Game>();
+        assert_eq!(game.board, board);
+        assert_eq!(game.dice_rolls, vec![3, 5]);
+        assert_eq!(app.world.resource::<UndoHistory>().len(), 0);
+        assert!(app.world.resource::<Events<MovePieceEndEvent>>().is_empty());
+    }
+
+    #[test]
+    fn die_sized_move_is_played() {
+        let mut app = move_app(rolled(vec![3, 5]));
+
+        app.world.send_event(MovePieceEvent {
+            checker_move: game::Move::between(0, 3),
+        });
+        app.update();
+
+        let game = app.world.resource::<game::Game>();
+        assert_eq!(game.board.points[3], 1);
+        assert_eq!(game.dice_rolls, vec![5]);
+        assert_eq!(app.world.resource::<UndoHistory>().len(), 1);
+        assert_eq!(app.world.resource::<Events<MovePieceEndEvent>>().len(), 1);
+    }
+
+    #[test]
+    fn double_click_plays_an_only_move() {
+        // with a 5 left the back checkers are blocked and the midpoint has one move
+        let mut app = test_app();
+        app.add_event::<PickingEvent>()
+            .add_event::<DisplayPossibleMovesEvent>()
+            .add_event::<MovePieceEvent>()
+            .init_resource::<InputSettings>()
+            .init_resource::<ConfirmTurn>()
+            .init_resource::<Audio>()
+            .insert_resource(rolled(vec![5]))
+            .add_system(handle_piece_picking);
+
+        let midpoint = app
+            .world
+            .spawn(Piece {
+                row: 5,
+                position: 12,
+                color: game::Color::White,
+                highlighted: true,
+                candidate: false,
+                dimmed: false,
+                chosen: false,
+            })
+            .id();
+
+        app.world.send_event(PickingEvent::Clicked(midpoint));
+        app.update();
+        assert!(app.world.resource::<Events<MovePieceEvent>>().is_empty());
+        assert_eq!(
+            app.world
+                .resource::<Events<DisplayPossibleMovesEvent>>()
+                .len(),
+            1
+        );
+
+        app.world.send_event(PickingEvent::Clicked(midpoint));
+        app.update();
+        let events = app.world.resource::<Events<MovePieceEvent>>();
+        let moves = events
+            .get_reader()
+            .iter(events)
+            .map(|event| event.checker_move)
+            .collect::<Vec<_>>();
+        assert_eq!(moves, vec![game::Move::between(11, 16)]);
+    }
+
+    #[test]
+    fn beginner_assist_dims_every_other_point() {
+        let mut app = test_app();
+        app.add_event::<DisplayPossibleMovesEvent>()
+            .add_event::<ToastEvent>()
+            .insert_resource(BeginnerAssist { enabled: true })
+            .init_resource::<ConfirmTurn>()
+            .insert_resource(rolled(vec![3, 1]))
+            .add_system(handle_display_possible_moves);
+
+        let back_checker = app
+            .world
+            .spawn(Piece {
+                row: 2,
+                position: 1,
+                color: game::Color::White,
+                highlighted: true,
+                candidate: false,
+                dimmed: false,
+                chosen: false,
+            })
+            .id();
+        app.world.send_event(DisplayPossibleMovesEvent {
+            position: game::PointLabel(1),
+            entity: Some(back_checker),
+        });
+        app.update();
+
+        let mut dimmed = app
+            .world
+            .query::<&Piece>()
+            .iter(&app.world)
+            .filter(|piece| piece.dimmed)
+            .map(|piece| piece.board_index().get())
+            .collect::<Vec<_>>();
+        dimmed.sort();
+        let legal = [1, 3];
+        let expected = (0..24)
+            .filter(|point| !legal.contains(point))
+            .collect::<Vec<_>>();
+        assert_eq!(dimmed, expected);
+    }
+
+    #[test]
+    fn double_speed_halves_the_dice_wait() {
+        let mut playback_speed = PlaybackSpeed::default();
+        let wait = DiceRollTimer::new(0, playback_speed);
+
+        playback_speed.set(2.0);
+        let fast = DiceRollTimer::new(0, playback_speed);
+        assert_eq!(fast.timer.duration(), wait.timer.duration() / 2);
+        assert_eq!(fast.min_roll, wait.min_roll / 2);
+
+        playback_speed.set(100.0);
+        assert_eq!(playback_speed.0, PlaybackSpeed::MAX);
+    }
+
+    #[test]
+    fn watchdog_passes_a_stalled_turn() {
+        // the dice are used up but nothing passed the turn
+        let game = rolled(vec![]);
+        assert!(game.is_stalled());
+
+        let mut app = test_app();
+        app.add_event::<TurnStartEvent>()
+            .init_resource::<PassWatchdog>()
+            .insert_resource(game)
+            .add_system(turn_watchdog);
+
+        // a pending pass is left to its timer
+        let pass_timer = app
+            .world
+            .spawn(PassTimer::new(PlaybackSpeed::default()))
+            .id();
+        for _ in 0..STALL_FRAMES {
+            app.update();
+        }
+        assert_eq!(
+            app.world.resource::<game::Game>().player,
+            game::Color::White
+        );
+
+        app.world.despawn(pass_timer);
+        app.update();
+        assert_eq!(
+            app.world.resource::<game::Game>().player,
+            game::Color::White
+        );
+        app.update();
+        let game = app.world.resource::<game::Game>();
+        assert_eq!(game.player, game::Color::Black);
+        assert!(!game.is_stalled());
+        assert_eq!(app.world.resource::<Events<TurnStartEvent>>().len(), 1);
+    }
+
+    // Piece entities with whether they're highlighted.
+    fn highlights(app: &mut App) -> Vec<(Entity, bool)> {
+        let mut highlights = app
+            .world
+            .query::<(Entity, &Piece)>()
+            .iter(&app.world)
+            .map(|(entity, piece)| (entity, piece.highlighted))
+            .collect::<Vec<_>>();
+        highlights.sort();
+        highlights
+    }
+
+    #[test]
+    fn repeated_highlight_events_keep_the_pieces() {
+        let mut app = test_app();
+        app.add_event::<HighlightPickablePiecesEvent>()
+            .init_resource::<ConfirmTurn>()
+            .insert_resource(rolled(vec![3, 1]))
+            .add_startup_system(crate::spawn_pieces)
+            .add_system(handle_hightlight_choosable_pieces);
+        app.update();
+
+        app.world.send_event(HighlightPickablePiecesEvent);
+        app.update();
+        let highlighted = highlights(&mut app);
+        assert_eq!(highlighted.len(), 30);
+        assert!(highlighted.iter().any(|(_, highlighted)| *highlighted));
+
+        for _ in 0..2 {
+            app.world.send_event(HighlightPickablePiecesEvent);
+            app.update();
+            assert_eq!(highlights(&mut app), highlighted);
+        }
+    }
+
+    #[test]
+    fn unmoved_checkers_keep_their_identity() {
+        let mut app = move_app(rolled(vec![3, 1]));
+        app.add_startup_system(crate::spawn_pieces);
+        app.update();
+
+        let identities = |app: &mut App| {
+            app.world
+                .query::<(Entity, &PieceId)>()
+                .iter(&app.world)
+                .map(|(entity, piece_id)| (entity, *piece_id))
+                .collect::<HashSet<_>>()
+        };
+        let before = identities(&mut app);
+        assert_eq!(before.len(), 30);
+
+        // one of the two back checkers moves up
+        app.world.send_event(MovePieceEvent {
+            checker_move: game::Move::between(0, 3),
+        });
+        app.update();
+
+        let after = identities(&mut app);
+        assert_eq!(after.len(), 30);
+        assert_eq!(before.intersection(&after).count(), 29);
+    }
+
+    #[test]
+    fn pieces_light_up_in_the_frame_of_the_move() {
+        let mut app = move_app(rolled(vec![3, 1]));
+        app.add_event::<TurnStartEvent>()
+            .add_event::<GameOverEvent>()
+            .init_resource::<PassWatchdog>()
+            .configure_sets(TurnSet::chain())
+            .add_startup_system(crate::spawn_pieces)
+            // added out of order, the sets decide when they run
+            .add_system(handle_hightlight_choosable_pieces.in_set(TurnSet::Highlight))
+            .add_system(
+                apply_system_buffers
+                    .after(TurnSet::Move)
+                    .before(TurnSet::Highlight),
+            )
+            .add_system(
+                handle_move_piece_end_event
+                    .in_set(TurnSet::Move)
+                    .after(handle_move_piece_event),
+            );
+        app.update();
+        assert!(highlights(&mut app)
+            .iter()
+            .all(|(_, highlighted)| !highlighted));
+
+        app.world.send_event(MovePieceEvent {
+            checker_move: game::Move::between(0, 3),
+        });
+        app.update();
+        assert!(highlights(&mut app)
+            .iter()
+            .any(|(_, highlighted)| *highlighted));
+    }
+
+    #[test]
+    fn dice_settle_below_the_cutoff() {
+        assert!(is_settled(&Velocity::zero()));
+        assert!(is_settled(&Velocity::linear(
+            Vec3::X * DICE_SETTLE_SPEED * 0.5
+        )));
+        assert!(!is_settled(&Velocity::linear(
+            Vec3::X * DICE_SETTLE_SPEED * 2.0
+        )));
+    }
+
+    #[test]
+    fn roll_waits_for_the_dice_to_rest() {
+        let mut game = game::Game::new();
+        game.game_log.push(GameLogEntry {
+            player: game::Color::White,
+            dice_rolls: vec![4, 2],
+            opening_tie: false,
+        });
+
+        let mut app = test_app();
+        app.add_event::<InstantDiceRollEvent>()
+            .add_event::<TurnStartEvent>()
+            .add_event::<HighlightPickablePiecesEvent>()
+            .add_event::<MovePieceEvent>()
+            .init_resource::<AutoBearOff>()
+            .init_resource::<PlaybackSpeed>()
+            .insert_resource(game)
+            .add_system(event_dice_rolls_complete);
+
+        let timer = app
+            .world
+            .spawn(DiceRollTimer {
+                timer: Timer::new(Duration::from_secs(60), TimerMode::Once),
+                log_length: 0,
+                min_roll: Duration::ZERO,
+            })
+            .id();
+        let die = app
+            .world
+            .spawn((RigidBody::Dynamic, Velocity::linear(Vec3::X)))
+            .id();
+
+        // the result is logged but the die still rolls
+        app.update();
+        assert!(app.world.get_entity(timer).is_some());
+        assert!(app.world.resource::<game::Game>().opening_roll);
+
+        *app.world.get_mut::<Velocity>(die).unwrap() = Velocity::zero();
+        app.update();
+        assert!(app.world.get_entity(timer).is_none());
+        assert_eq!(
+            app.world.resource::<game::Game>().player,
+            game::Color::White
+        );
+    }
+
+    #[test]
+    fn hovering_a_hit_ghosts_the_blot_to_the_bar() {
+        let mut white = [0; 24];
+        white[0] = 2;
+        let mut black = [0; 24];
+        black[3] = 1;
+        black[10] = 2;
+        let mut game = game::Game::from_position(white, black, [0, 0], game::Color::White).unwrap();
+        game.dice_rolls = vec![3, 1];
+
+        let mut app = test_app();
+        app.add_event::<PickingEvent>()
+            .init_resource::<ConfirmTurn>()
+            .init_resource::<GhostPreview>()
+            .insert_resource(game)
+            .add_system(handle_ghost_preview);
+
+        let piece = |position, color, candidate| Piece {
+            position,
+            row: 1,
+            color,
+            highlighted: false,
+            candidate,
+            dimmed: false,
+            chosen: false,
+        };
+        let blot = app
+            .world
+            .spawn((
+                piece(4, game::Color::Black, false),
+                Handle::<StandardMaterial>::default(),
+            ))
+            .id();
+        let hit = app
+            .world
+            .spawn((
+                piece(4, game::Color::White, true),
+                Handle::<StandardMaterial>::default(),
+            ))
+            .id();
+        let quiet = app
+            .world
+            .spawn((
+                piece(2, game::Color::White, true),
+                Handle::<StandardMaterial>::default(),
+            ))
+            .id();
+
+        let ghosts = |app: &mut App| app.world.query::<&GhostToBar>().iter(&app.world).count();
+
+        app.world
+            .send_event(PickingEvent::Hover(HoverEvent::JustEntered(hit)));
+        app.update();
+        assert_eq!(app.world.resource::<GhostPreview>().hit, Some(blot));
+        assert_eq!(ghosts(&mut app), 1);
+
+        app.world
+            .send_event(PickingEvent::Hover(HoverEvent::JustLeft(hit)));
+        app.update();
+        assert_eq!(app.world.resource::<GhostPreview>().hit, None);
+        assert_eq!(ghosts(&mut app), 0);
+
+        app.world
+            .send_event(PickingEvent::Hover(HoverEvent::JustEntered(quiet)));
+        app.update();
+        assert_eq!(app.world.resource::<GhostPreview>().candidate, Some(quiet));
+        assert_eq!(app.world.resource::<GhostPreview>().hit, None);
+        assert_eq!(ghosts(&mut app), 0);
+    }
+
+    #[test]
+    fn pass_waits_for_the_countdown() {
+        let mut app = test_app();
+        app.add_event::<TurnStartEvent>()
+            .init_resource::<PassWatchdog>()
+            .insert_resource(rolled(vec![]))
+            .add_system(handle_pass_timer);
+
+        let pass_timer = app
+            .world
+            .spawn(PassTimer::new(PlaybackSpeed::default()))
+            .id();
+        let tick = |app: &mut App, millis| {
+            app.world
+                .get_mut::<PassTimer>(pass_timer)
+                .unwrap()
+                .timer
+                .tick(Duration::from_millis(millis));
+            app.update();
+            app.world.resource::<game::Game>().player
+        };
+
+        assert_eq!(pass_countdown_message(2), "No moves — passing in 2…");
+        assert_eq!(tick(&mut app, 0), game::Color::White);
+        assert_eq!(tick(&mut app, 1500), game::Color::White);
+        assert_eq!(
+            app.world
+                .get::<PassTimer>(pass_timer)
+                .unwrap()
+                .seconds_left(),
+            1
+        );
+
+        assert_eq!(tick(&mut app, 500), game::Color::Black);
+        assert!(app.world.get_entity(pass_timer).is_none());
+        assert_eq!(app.world.resource::<Events<TurnStartEvent>>().len(), 1);
+    }
+
+    #[test]
+    fn dead_die_left_over_ends_the_turn() {
+        let mut white = [0; 24];
+        white[0] = 1;
+        white[10] = 1;
+        let mut black = [0; 24];
+        black[4] = 2;
+        black[9] = 2;
+        black[14] = 2;
+        black[23] = 5;
+        let mut game = game::Game::from_position(white, black, [0, 0], game::Color::White).unwrap();
+        game.opening_roll = false;
+        game.dice_rolled = true;
+        game.dice_rolls = vec![3, 4];
+
+        let mut app = move_app(game);
+        app.add_event::<TurnStartEvent>()
+            .add_event::<GameOverEvent>()
+            .init_resource::<PassWatchdog>()
+            .add_system(handle_move_piece_end_event.after(TurnSet::Move));
+
+        // a board that went wrong blocks the only point the 4 could still
+        // play to after 10 -> 13
+        app.world.resource_mut::<game::Game>().board.points[17] = -2;
+        app.world.send_event(MovePieceEvent {
+            checker_move: game::Move::between(10, 13),
+        });
+        app.update();
+
+        let game = app.world.resource::<game::Game>();
+        assert_eq!(game.board.points[13], 1);
+        assert_eq!(game.player, game::Color::Black);
+        assert!(game.dice_rolls.is_empty());
+        assert_eq!(
+            app.world
+                .resource::<Events<HighlightPickablePiecesEvent>>()
+                .len(),
+            0
+        );
+        assert_eq!(app.world.resource::<Events<TurnStartEvent>>().len(), 1);
+    }
+
+    #[test]
+    fn double_click_needs_both_clicks_within_the_threshold() {
+        let input_settings = InputSettings::default();
+        let piece = Entity::from_raw(3);
+        let mut last_click = LastClick::default();
+
+        assert!(!last_click.register(piece, 10.0, Some(Vec2::ZERO), &input_settings));
+        assert!(last_click.register(piece, 10.2, Some(Vec2::new(2.0, 0.0)), &input_settings));
+        // 800ms and 500ms apart are both past the default 300ms
+        assert!(!last_click.register(piece, 11.0, Some(Vec2::ZERO), &input_settings));
+        assert!(!last_click.register(piece, 11.5, Some(Vec2::ZERO), &input_settings));
+
+        let slow = InputSettings {
+            double_click_ms: 800,
+            ..input_settings
+        };
+        assert!(last_click.register(piece, 12.2, Some(Vec2::ZERO), &slow));
+        // in time, but too far from the first click
+        assert!(!last_click.register(piece, 12.3, Some(Vec2::new(20.0, 0.0)), &input_settings));
+
+        let mut input_settings = input_settings;
+        input_settings.adjust_double_click(100);
+        assert_eq!(input_settings.double_click_ms, 1000);
+        input_settings.adjust_double_click(-1);
+        assert_eq!(input_settings.double_click_ms, 950);
+    }
+
+    #[test]
+    fn clicking_an_opponent_checker_does_nothing() {
+        let mut app = test_app();
+        app.add_event::<PickingEvent>()
+            .add_event::<DisplayPossibleMovesEvent>()
+            .add_event::<MovePieceEvent>()
+            .init_resource::<InputSettings>()
+            .init_resource::<ConfirmTurn>()
+            .init_resource::<Audio>()
+            .insert_resource(rolled(vec![3, 1]))
+            .add_system(handle_piece_picking);
+
+        // both left highlighted, as a stale state could
+        let piece = |position, color| Piece {
+            row: 1,
+            position,
+            color,
+            highlighted: true,
+            candidate: false,
+            dimmed: false,
+            chosen: false,
+        };
+        let black = app.world.spawn(piece(24, game::Color::Black)).id();
+        let white = app.world.spawn(piece(1, game::Color::White)).id();
+
+        app.world.send_event(PickingEvent::Clicked(black));
+        app.update();
+        assert!(app
+            .world
+            .resource::<Events<DisplayPossibleMovesEvent>>()
+            .is_empty());
+        assert!(!app.world.get::<Piece>(black).unwrap().chosen);
+
+        app.world.send_event(PickingEvent::Clicked(white));
+        app.update();
+        assert_eq!(
+            app.world
+                .resource::<Events<DisplayPossibleMovesEvent>>()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn deselecting_clears_the_candidates() {
+        let mut app = test_app();
+        app.add_event::<PickingEvent>()
+            .init_resource::<AutoClearCandidates>()
+            .add_system(clear_candidates_on_deselect);
+
+        let piece = |position, candidate: bool, chosen| {
+            (
+                Piece {
+                    row: 1,
+                    position,
+                    color: game::Color::White,
+                    highlighted: !candidate,
+                    candidate,
+                    dimmed: false,
+                    chosen,
+                },
+                Handle::<StandardMaterial>::default(),
+            )
+        };
+        let chosen = app.world.spawn(piece(1, false, true)).id();
+        app.world.spawn(piece(4, true, false));
+        app.world.spawn(piece(5, true, false));
+        let candidates = |app: &mut App| {
+            app.world
+                .query::<&Piece>()
+                .iter(&app.world)
+                .filter(|piece| piece.candidate)
+                .count()
+        };
+
+        // a click that deselects is left to the picking handler
+        app.world
+            .send_event(PickingEvent::Selection(SelectionEvent::JustDeselected(
+                chosen,
+            )));
+        app.world.send_event(PickingEvent::Clicked(chosen));
+        app.update();
+        assert_eq!(candidates(&mut app), 2);
+
+        app.world
+            .send_event(PickingEvent::Selection(SelectionEvent::JustDeselected(
+                chosen,
+            )));
+        app.update();
+        assert_eq!(candidates(&mut app), 0);
+        assert!(!app.world.get::<Piece>(chosen).unwrap().chosen);
+    }
+}
@@ -1,95 +1,389 @@
-use bevy::prelude::Resource;
+use bevy::prelude::{FromReflect, Reflect, ReflectResource, Resource};
 use itertools::Itertools;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::ops::Range;
+use std::sync::Mutex;
+
+use crate::cube::{DoublingCube, MatchScore};
 
 // Define the type of game piece.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect, FromReflect)]
 pub enum Color {
     White,
     Black,
 }
 
 impl Color {
-    fn opposite(&self) -> Color {
+    pub fn opposite(&self) -> Color {
         match self {
             Color::White => Color::Black,
             Color::Black => Color::White,
         }
     }
+
+    // Owner of a point from the sign of its count, `None` for an empty point.
+    pub fn from_sign(count: i32) -> Option<Color> {
+        match count.signum() {
+            1 => Some(Color::White),
+            -1 => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    // Sign of this color's checker counts in `Board::points`.
+    pub fn sign(&self) -> i32 {
+        match self {
+            Color::White => 1,
+            Color::Black => -1,
+        }
+    }
+}
+
+// Move source index standing for the moving player's bar.
+pub const BAR: usize = 24;
+
+// Checkers drawn in a stack, taller stacks show their count instead.
+pub const MAX_STACK_ROWS: usize = 5;
+
+// Checkers a point holds at most, moves onto a full point are blocked.
+pub const MAX_POINT_CHECKERS: u32 = 5;
+
+// Zobrist keys, one per color, checker count and place a checker can be: the
+// 24 points, the bar and off the board. Counts wrap past `ZOBRIST_COUNTS`.
+const ZOBRIST_PLACES: usize = 26;
+const ZOBRIST_COUNTS: usize = 16;
+const ZOBRIST_KEYS: [u64; 2 * ZOBRIST_PLACES * ZOBRIST_COUNTS] =
+    zobrist_keys(0x9e37_79b9_7f4a_7c15);
+
+const fn zobrist_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = seed;
+    let mut i = 0;
+    while i < N {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        keys[i] = state;
+        i += 1;
+    }
+    keys
+}
+
+// Chance of bearing off every checker next turn that makes a last-roll position.
+pub const LAST_ROLL_CHANCE: f32 = 0.75;
+
+// Rows drawn for a stack of `checkers`.
+pub fn stack_rows(checkers: usize) -> usize {
+    checkers.min(MAX_STACK_ROWS)
+}
+
+// Dice played for a roll, doubles are played four times.
+pub fn expand_roll(dice: [usize; 2]) -> Vec<usize> {
+    if dice[0] == dice[1] {
+        vec![dice[0]; 4]
+    } else {
+        dice.to_vec()
+    }
+}
+
+// 0-based index into `Board::points`, with `BAR` just past the points. The
+// engine works in these.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoardIndex(pub u8);
+
+// 1-based position the board draws a point at, 1 to 24 with the bar at 25.
+// Pieces and UI events work in these.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PointLabel(pub u8);
+
+impl BoardIndex {
+    pub fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn label(self) -> PointLabel {
+        PointLabel(self.0 + 1)
+    }
+}
+
+impl PointLabel {
+    pub fn get(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn index(self) -> BoardIndex {
+        BoardIndex(self.0 - 1)
+    }
+}
+
+impl From<BoardIndex> for PointLabel {
+    fn from(index: BoardIndex) -> Self {
+        index.label()
+    }
+}
+
+impl From<PointLabel> for BoardIndex {
+    fn from(label: PointLabel) -> Self {
+        label.index()
+    }
+}
+
+// A single checker move, points are 0-based board indices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Point { from: u8, to: u8 },
+    // from the mover's bar
+    Enter { to: u8 },
+    BearOff { from: u8 },
+}
+
+impl Move {
+    // Move between board indices, `BAR` as the source enters and a destination
+    // past either end of the board bears off.
+    pub fn between(from: usize, to: i32) -> Move {
+        if !(0..24).contains(&to) {
+            return Move::BearOff { from: from as u8 };
+        }
+
+        if from == BAR {
+            Move::Enter { to: to as u8 }
+        } else {
+            Move::Point {
+                from: from as u8,
+                to: to as u8,
+            }
+        }
+    }
+
+    // Index the checker leaves, `BAR` when entering.
+    pub fn source(self) -> usize {
+        match self {
+            Move::Point { from, .. } | Move::BearOff { from } => from as usize,
+            Move::Enter { .. } => BAR,
+        }
+    }
+
+    // Index the checker lands on, `None` when it's borne off.
+    pub fn destination(self) -> Option<usize> {
+        match self {
+            Move::Point { to, .. } | Move::Enter { to } => Some(to as usize),
+            Move::BearOff { .. } => None,
+        }
+    }
+
+    // The same move on a mirrored board, see `Board::mirror`.
+    pub fn mirror(self) -> Move {
+        match self {
+            Move::Point { from, to } => Move::Point {
+                from: 23 - from,
+                to: 23 - to,
+            },
+            Move::Enter { to } => Move::Enter { to: 23 - to },
+            Move::BearOff { from } => Move::BearOff { from: 23 - from },
+        }
+    }
+
+    // Destination with bearing off just past the player's end of the board.
+    pub fn destination_index(self, player: Color) -> i32 {
+        match (self.destination(), player) {
+            (Some(to), _) => to as i32,
+            (None, Color::White) => 24,
+            (None, Color::Black) => -1,
+        }
+    }
+}
+
+// A legal move with what it does on the point it lands on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveInfo {
+    pub from: usize,
+    // `None` when the checker is borne off
+    pub to: Option<usize>,
+    // sends an opponent blot to the bar
+    pub hits: bool,
+    // lands on a single checker of the mover's own
+    pub makes_point: bool,
+}
+
+// Where the game stands, derived from the roll and board state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GamePhase {
+    // both players roll one die to decide who starts
+    OpeningRoll,
+    // the player on roll hasn't rolled yet, or the dice are still rolling
+    AwaitingRoll,
+    AwaitingMove,
+    GameOver,
+}
+
+// How far behind the loser was, scoring one, two or three times the cube.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinKind {
+    Single,
+    // the loser bore off no checkers
+    Gammon,
+    // a gammon with a loser's checker still on the bar or in the winner's home
+    Backgammon,
+}
+
+impl WinKind {
+    pub fn multiplier(self) -> u32 {
+        match self {
+            WinKind::Single => 1,
+            WinKind::Gammon => 2,
+            WinKind::Backgammon => 3,
+        }
+    }
+}
+
+// Key numbers of a finished game, shown on the game over screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameSummary {
+    pub winner: Color,
+    pub win_kind: WinKind,
+    pub cube_value: u32,
+    pub pip_counts: [u32; 2], // Indexed like `Board::bar`.
+    pub hits: [u32; 2],
+    pub turns: usize,
+}
+
+impl GameSummary {
+    pub fn points(&self) -> u32 {
+        self.win_kind.multiplier() * self.cube_value
+    }
+}
+
+// Rule sets differing in the starting layout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameVariant {
+    #[default]
+    Standard,
+    Nackgammon,
+}
+
+impl GameVariant {
+    pub fn opening_points(&self) -> [i32; 24] {
+        // White's checkers, Black's mirror them
+        let white: &[(usize, i32)] = match self {
+            GameVariant::Standard => &[(0, 2), (11, 5), (16, 3), (18, 5)],
+            GameVariant::Nackgammon => &[(0, 2), (1, 2), (11, 4), (16, 3), (18, 4)],
+        };
+
+        let mut points = [0; 24];
+        for &(index, count) in white {
+            points[index] = count;
+            points[23 - index] = -count;
+        }
+        points
+    }
 }
 
 // Define the type of game board.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect, FromReflect)]
 pub struct Board {
-    pub points: [i32; 24], // Number of pieces on each point of the board.
-    pub bar: [i32; 2],     // Number of pieces on the bar.
+    pub points: [i32; 24],  // Number of pieces on each point of the board.
+    pub bar: [i32; 2],      // Number of pieces on the bar.
+    pub borne_off: [u8; 2], // Number of pieces borne off, indexed like `bar`.
 }
 
 impl Board {
-    pub(crate) fn is_player_home_complete(&self, color: Color) -> bool {
-        let mut home_board = if color == Color::White { 18..24 } else { 0..6 };
-        let home_of_same_color = home_board.all(|i| {
-            let clr = self.get_point_color(i as usize);
-            clr.is_none() || clr.unwrap() == color
-        });
+    pub fn is_player_home_complete(&self, color: Color) -> bool {
+        // a checker on the bar has to re-enter before bearing off
+        if self.bar[self.bar_index(color)] > 0 {
+            return false;
+        }
 
+        // opponent checkers anchored in the home board don't matter
         let mut rest_of_board = if color == Color::White { 0..18 } else { 6..24 };
-        let rest_of_board_is_empty = rest_of_board.all(|i| {
+        rest_of_board.all(|i| {
             let clr = self.get_point_color(i as usize);
             clr.is_none() || clr.unwrap() != color
-        });
-        home_of_same_color && rest_of_board_is_empty
+        })
     }
 
-    pub fn make_move(
-        &mut self,
-        player: Color,
-        from_position: usize,
-        to_position: i32,
-    ) -> Result<(), String> {
+    // Whether `color` has a checker on a home point farther from bearing off
+    // than `from`, which rules out bearing off from `from` with a larger die.
+    fn has_checker_farther(&self, color: Color, from: usize) -> bool {
+        let farther = match color {
+            Color::White => 18..from,
+            Color::Black => from + 1..6,
+        };
+        farther
+            .into_iter()
+            .any(|i| self.get_point_color(i) == Some(color))
+    }
+
+    pub fn make_move(&mut self, player: Color, checker_move: Move) -> Result<bool, String> {
+        let from_position = checker_move.source();
+        let to_position = checker_move.destination_index(player);
+
         // check if move is valid
         if !self.can_move_piece(player, from_position, to_position) {
             return Err(String::from("Invalid move"));
         }
 
         let direction = self.direction(player);
-        self.points[from_position] -= direction;
-
-        let is_home_complete = self.is_player_home_complete(player);
-        if is_home_complete && player == Color::White && to_position >= 24 {
-            return Ok(());
+        if from_position == BAR {
+            self.bar[self.bar_index(player)] -= 1;
+        } else {
+            self.points[from_position] -= direction;
         }
 
-        if is_home_complete && player == Color::Black && to_position < 0 {
-            return Ok(());
+        let is_home_complete = self.is_player_home_complete(player);
+        if is_home_complete && !(0..24).contains(&to_position) {
+            self.borne_off[self.bar_index(player)] += 1;
+            return Ok(false);
         }
 
         let to_position = to_position as usize;
 
+        // a single opponent checker is hit and sent to the bar, checkers
+        // entering from the bar included
         if self.points[to_position] == -direction {
             self.points[to_position] = direction;
             self.bar[self.opposite_bar_index(player)] += 1;
-        } else {
-            self.points[to_position] += direction;
+            return Ok(true);
         }
 
-        Ok(())
+        self.points[to_position] += direction;
+        Ok(false)
     }
 
     pub fn can_move_piece(&self, player: Color, from_point: usize, to_point: i32) -> bool {
-        if self.get_point_count(from_point) == 0 {
-            return false;
-        }
+        let on_bar = self.bar[self.bar_index(player)] > 0;
 
-        if self.get_point_color(from_point) != Some(player) {
-            return false;
-        }
+        if from_point == BAR {
+            if !on_bar {
+                return false;
+            }
+        } else {
+            // checkers on the bar must enter before any other checker moves
+            if on_bar {
+                return false;
+            }
 
-        let is_home_complete = self.is_player_home_complete(player);
-        if is_home_complete && player == Color::White && to_point >= 23 {
-            return true;
+            if self.get_point_count(from_point) == 0 {
+                return false;
+            }
+
+            if self.get_point_color(from_point) != Some(player) {
+                return false;
+            }
         }
-        if is_home_complete && player == Color::Black && to_point < 0 {
-            return true;
+
+        let bearing_off = match player {
+            Color::White => to_point >= 24,
+            Color::Black => to_point < 0,
+        };
+        if bearing_off {
+            // a die larger than needed only bears off the farthest checker
+            let exact = to_point == 24 || to_point == -1;
+            return self.is_player_home_complete(player)
+                && (exact || !self.has_checker_farther(player, from_point));
         }
 
         if !(0..24).contains(&to_point) {
@@ -100,24 +394,34 @@ impl Board {
         let to_point_color = self.get_point_color(to_point as usize);
         let to_point_count = self.get_point_count(to_point as usize);
 
-        if to_point_color == Some(opposite_color) && to_point_count > 0 {
+        // points held by two or more opponent checkers are blocked
+        if to_point_color == Some(opposite_color) && to_point_count > 1 {
             return false;
         }
 
         let to_point: usize = to_point as usize;
 
-        let direction = if player == Color::White { 1 } else { -1 };
+        let direction = player.sign();
         if to_point >= 24 {
             return false;
         }
-        if to_point_color == Some(opposite_color) && to_point < from_point && direction == 1 {
+        let entering = from_point == BAR;
+        if to_point_color == Some(opposite_color)
+            && !entering
+            && to_point < from_point
+            && direction == 1
+        {
             return false;
         }
-        if to_point_color == Some(opposite_color) && to_point > from_point && direction == -1 {
+        if to_point_color == Some(opposite_color)
+            && !entering
+            && to_point > from_point
+            && direction == -1
+        {
             return false;
         }
 
-        if self.points[to_point].abs() >= 5 {
+        if self.points[to_point].unsigned_abs() >= MAX_POINT_CHECKERS {
             return false;
         }
 
@@ -125,19 +429,20 @@ impl Board {
     }
 
     fn get_point_color(&self, point: usize) -> Option<Color> {
-        let point_count = self.points[point];
-
-        match point_count {
-            0 => None,
-            _ if point_count > 0 => Some(Color::White),
-            _ => Some(Color::Black),
-        }
+        Color::from_sign(self.points[point])
     }
 
     fn get_point_count(&self, point: usize) -> usize {
         self.points[point].unsigned_abs() as usize
     }
 
+    pub fn bar_index(&self, color: Color) -> usize {
+        match color {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
+
     pub fn opposite_bar_index(&self, color: Color) -> usize {
         match color {
             Color::White => 1,
@@ -146,13 +451,33 @@ impl Board {
     }
 
     fn get_index(&self, color: Color, index: usize, dice_roll_value: usize) -> i32 {
+        // checkers enter from the bar into the opponent's home board
+        if index == BAR {
+            return match color {
+                Color::White => dice_roll_value as i32 - 1,
+                Color::Black => 24 - dice_roll_value as i32,
+            };
+        }
+
         match color {
             Color::White => index as i32 + dice_roll_value as i32,
             Color::Black => index as i32 - dice_roll_value as i32,
         }
     }
 
-    #[allow(dead_code)]
+    // Home points held by two or more of `color`'s checkers, from the 1-point up.
+    pub fn home_points_made(&self, color: Color) -> [bool; 6] {
+        let mut made = [false; 6];
+        for index in self.home(color) {
+            let point = match color {
+                Color::White => 23 - index,
+                Color::Black => index,
+            };
+            made[point] = self.points[index] * color.sign() >= 2;
+        }
+        made
+    }
+
     fn home(&self, player: Color) -> Range<usize> {
         if player == Color::White {
             18..24
@@ -161,6 +486,7 @@ impl Board {
         }
     }
 
+    // Points holding `color`'s checkers, empty when it has none on the board.
     fn get_points_for_color(&self, color: Color) -> Vec<usize> {
         let mut points = vec![];
         for i in 0..24 {
@@ -172,31 +498,371 @@ impl Board {
     }
 
     fn direction(&self, player: Color) -> i32 {
-        if player == Color::White {
-            1
+        player.sign()
+    }
+
+    // Checkers of `color` still in play, on the board and on the bar.
+    pub fn checkers_in_play(&self, color: Color) -> u32 {
+        let on_board: u32 = (0..24)
+            .filter(|&i| self.get_point_color(i) == Some(color))
+            .map(|i| self.get_point_count(i) as u32)
+            .sum();
+
+        on_board + self.bar[self.bar_index(color)].unsigned_abs()
+    }
+
+    // All of `color`'s checkers: in play and borne off.
+    pub fn checker_total(&self, color: Color) -> u32 {
+        self.checkers_in_play(color) + self.borne_off[self.bar_index(color)] as u32
+    }
+
+    pub fn is_opening_position(&self, variant: GameVariant) -> bool {
+        self.points == variant.opening_points() && self.bar == [0, 0] && self.borne_off == [0, 0]
+    }
+
+    // Pips `color` still has to travel to bear everything off.
+    pub fn pip_count(&self, color: Color) -> u32 {
+        let on_board: u32 = self
+            .get_points_for_color(color)
+            .iter()
+            .map(|&i| {
+                let distance = match color {
+                    Color::White => 24 - i,
+                    Color::Black => i + 1,
+                };
+                (distance * self.get_point_count(i)) as u32
+            })
+            .sum();
+
+        on_board + 25 * self.bar[self.bar_index(color)].unsigned_abs()
+    }
+
+    // Whether the two sides have passed each other, so no checker can be hit again.
+    pub fn is_race(&self) -> bool {
+        if self.bar != [0, 0] {
+            return false;
+        }
+
+        let white_back = self.get_points_for_color(Color::White).into_iter().min();
+        let black_back = self.get_points_for_color(Color::Black).into_iter().max();
+
+        match (white_back, black_back) {
+            (Some(white), Some(black)) => white > black,
+            _ => true,
+        }
+    }
+
+    // Pips likely to be wasted bearing off: a checker closer to the edge than an
+    // average die still uses up a whole die. Stacks on the low points waste most.
+    pub fn bear_off_wastage(&self, color: Color) -> i32 {
+        // half of the 8.17 pips an average roll moves, rounded
+        const AVERAGE_DIE_PIPS: i32 = 4;
+
+        self.get_points_for_color(color)
+            .iter()
+            .map(|&i| {
+                let distance = match color {
+                    Color::White => 24 - i as i32,
+                    Color::Black => i as i32 + 1,
+                };
+                (AVERAGE_DIE_PIPS - distance).max(0) * self.get_point_count(i) as i32
+            })
+            .sum()
+    }
+
+    // Points holding a single checker of `color`.
+    pub fn blots(&self, color: Color) -> usize {
+        self.get_points_for_color(color)
+            .iter()
+            .filter(|&&i| self.get_point_count(i) == 1)
+            .count()
+    }
+
+    // Add a single checker of `color` to a point while editing a position.
+    pub fn place_checker(&mut self, color: Color, point: usize) -> Result<(), String> {
+        if self.checker_total(color) >= 15 {
+            return Err(format!("{:?} already has 15 checkers", color));
+        }
+
+        if self.get_point_color(point) == Some(color.opposite()) {
+            return Err(format!(
+                "Point {} is occupied by {:?}",
+                point + 1,
+                color.opposite()
+            ));
+        }
+
+        self.points[point] += self.direction(color);
+        Ok(())
+    }
+
+    // Number of pips a move covers, for bearing off the exact distance off the board.
+    pub fn move_distance(&self, player: Color, checker_move: Move) -> usize {
+        let from_position = checker_move.source();
+        let to_position = checker_move.destination_index(player);
+
+        if from_position == BAR {
+            return match player {
+                Color::White => (to_position + 1) as usize,
+                Color::Black => (24 - to_position) as usize,
+            };
+        }
+
+        (to_position - from_position as i32).unsigned_abs() as usize
+    }
+
+    // Die of `dice_rolls` the move is played with: the one covering its distance,
+    // or for bearing off the farthest checker the smallest one that carries it off.
+    pub fn die_for_move(
+        &self,
+        player: Color,
+        checker_move: Move,
+        dice_rolls: &[usize],
+    ) -> Option<usize> {
+        let distance = self.move_distance(player, checker_move);
+
+        match checker_move {
+            Move::BearOff { from } if !dice_rolls.contains(&distance) => {
+                if self.has_checker_farther(player, from as usize) {
+                    return None;
+                }
+                dice_rolls.iter().copied().filter(|&d| d > distance).min()
+            }
+            _ => dice_rolls.contains(&distance).then_some(distance),
+        }
+    }
+
+    // Whether a move from `from` to `to` covers the pips of one of `dice_rolls`,
+    // independent of whether the points on the way allow it.
+    pub fn is_die_sized_move(
+        &self,
+        player: Color,
+        from: usize,
+        to: i32,
+        dice_rolls: &[usize],
+    ) -> bool {
+        self.die_for_move(player, Move::between(from, to), dice_rolls)
+            .is_some()
+    }
+
+    // Moves available to `player` using any single die of `dice_rolls`.
+    pub fn get_possible_moves(&self, player: Color, dice_rolls: &[usize]) -> Vec<Move> {
+        let mut moves: Vec<Move> = vec![];
+        let indices = if self.bar[self.bar_index(player)] > 0 {
+            vec![BAR]
         } else {
-            -1
+            self.get_points_for_color(player)
+        };
+
+        for &index in indices.iter() {
+            for dice_roll in dice_rolls.iter() {
+                let next_index = self.get_index(player, index, *dice_roll);
+                if self.can_move_piece(player, index, next_index) {
+                    moves.push(Move::between(index, next_index));
+                }
+            }
         }
+
+        // doubles and bearing off with different dice give the same move
+        moves.into_iter().unique().collect()
     }
 
     pub fn get_next_free_row(&self, position: usize) -> usize {
         self.points[position].unsigned_abs() as usize + 1
     }
+
+    // Turns that play as many dice as possible, the higher die when only one can be.
+    pub fn legal_turn_sequences(&self, player: Color, dice_rolls: &[usize]) -> Vec<Vec<Move>> {
+        let mut sequences = vec![];
+        collect_turn_sequences(self, player, dice_rolls, &mut vec![], &mut sequences);
+
+        let max_length = sequences.iter().map(Vec::len).max().unwrap_or(0);
+        if max_length == 0 {
+            return vec![];
+        }
+        sequences.retain(|sequence| sequence.len() == max_length);
+
+        if max_length == 1 && dice_rolls.len() == 2 && dice_rolls[0] != dice_rolls[1] {
+            let higher = dice_rolls[0].max(dice_rolls[1]);
+            let uses_higher =
+                |sequence: &Vec<Move>| self.die_for_move(player, sequence[0], &[higher]).is_some();
+
+            if sequences.iter().any(uses_higher) {
+                sequences.retain(uses_higher);
+            }
+        }
+
+        sequences
+    }
+
+    // Efficient way to play `dice_rolls` while bearing off: the most checkers
+    // off, then the least wastage, then the fewest gaps in the home board.
+    // Empty unless `color` is bearing off.
+    pub fn suggest_bear_off(&self, color: Color, dice_rolls: &[usize]) -> Vec<Move> {
+        if !self.is_player_home_complete(color) {
+            return vec![];
+        }
+
+        self.legal_turn_sequences(color, dice_rolls)
+            .into_iter()
+            .min_by_key(|sequence| {
+                let mut board = self.clone();
+                for checker_move in sequence {
+                    board.make_move(color, *checker_move).unwrap();
+                }
+
+                // empty home points nearer the edge than the farthest checker
+                let farthest = board
+                    .home(color)
+                    .filter(|&i| board.get_point_color(i) == Some(color));
+                let gaps = match color {
+                    Color::White => farthest.min().map_or(0, |back| {
+                        (back..24).filter(|&i| board.points[i] == 0).count()
+                    }),
+                    Color::Black => farthest.max().map_or(0, |back| {
+                        (0..back).filter(|&i| board.points[i] == 0).count()
+                    }),
+                };
+
+                (
+                    Reverse(board.borne_off[board.bar_index(color)]),
+                    board.bear_off_wastage(color),
+                    gaps,
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    // The same position with the colors swapped, point i becoming 23 - i.
+    // Checks that White's and Black's rules agree.
+    pub fn mirror(&self) -> Board {
+        let mut points = [0; 24];
+        for (i, count) in self.points.iter().enumerate() {
+            points[23 - i] = -count;
+        }
+
+        Board {
+            points,
+            bar: [self.bar[1], self.bar[0]],
+            borne_off: [self.borne_off[1], self.borne_off[0]],
+        }
+    }
+
+    // Zobrist hash of the whole position, used to key cached move lists. The
+    // fields are edited directly in places, so it's computed rather than kept
+    // up to date move by move.
+    pub fn position_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for color in [Color::White, Color::Black] {
+            let index = self.bar_index(color);
+            let counts = (0..24)
+                .map(|i| {
+                    if self.get_point_color(i) == Some(color) {
+                        self.get_point_count(i)
+                    } else {
+                        0
+                    }
+                })
+                .chain([
+                    self.bar[index].unsigned_abs() as usize,
+                    self.borne_off[index] as usize,
+                ]);
+
+            for (place, count) in counts.enumerate().filter(|&(_, count)| count > 0) {
+                let key =
+                    (index * ZOBRIST_PLACES + place) * ZOBRIST_COUNTS + count % ZOBRIST_COUNTS;
+                hash ^= ZOBRIST_KEYS[key];
+            }
+        }
+
+        hash
+    }
+}
+
+fn collect_turn_sequences(
+    board: &Board,
+    player: Color,
+    dice_rolls: &[usize],
+    sequence: &mut Vec<Move>,
+    sequences: &mut Vec<Vec<Move>>,
+) {
+    let mut extended = false;
+
+    for (i, dice_roll) in dice_rolls.iter().enumerate().unique_by(|(_, roll)| **roll) {
+        let mut remaining_rolls = dice_rolls.to_vec();
+        remaining_rolls.remove(i);
+
+        for checker_move in board.get_possible_moves(player, &[*dice_roll]) {
+            let mut next_board = board.clone();
+            next_board.make_move(player, checker_move).unwrap();
+
+            sequence.push(checker_move);
+            collect_turn_sequences(&next_board, player, &remaining_rolls, sequence, sequences);
+            sequence.pop();
+            extended = true;
+        }
+    }
+
+    if !extended {
+        sequences.push(sequence.clone());
+    }
+}
+
+// Move lists memoized for the position they were generated from.
+#[derive(Default)]
+struct MoveCache {
+    position_hash: u64,
+    moves: HashMap<(Color, Vec<usize>), Vec<Move>>,
+}
+
+// Read-only view of the board with colors and counts decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoardSnapshot {
+    pub points: [(Option<Color>, u8); 24],
+    pub bar: [u8; 2],
+    pub off: [u8; 2],
+}
+
+impl From<BoardSnapshot> for Board {
+    fn from(snapshot: BoardSnapshot) -> Self {
+        Board {
+            points: snapshot
+                .points
+                .map(|(color, count)| color.map_or(0, |color| color.sign() * count as i32)),
+            bar: snapshot.bar.map(i32::from),
+            borne_off: snapshot.off,
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GameLogEntry {
     pub player: Color,
     pub dice_rolls: Vec<usize>,
+    // a tied opening roll that was rolled again, not a turn
+    #[serde(default)]
+    pub opening_tie: bool,
 }
 
-#[derive(Resource)]
-pub(crate) struct Game {
+// Reflected so the board can be edited live in the inspector.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct Game {
     pub board: Board,
     pub dice_rolls: Vec<usize>,
     pub dice_rolled: bool,
+    pub opening_roll: bool,
     pub player: Color,
+    #[reflect(ignore)]
     pub game_log: Vec<GameLogEntry>,
+    pub hits: [u32; 2], // Number of times each color was hit.
+    #[reflect(ignore)]
+    pub cube: DoublingCube,
+    #[reflect(ignore)]
+    pub match_score: Option<MatchScore>, // `None` for money games.
+    #[reflect(ignore)]
+    move_cache: Mutex<MoveCache>,
 }
 
 impl Default for Game {
@@ -206,100 +872,388 @@ impl Default for Game {
 }
 
 impl Game {
-    pub(crate) fn can_move(&self, player: Color) -> bool {
+    pub fn can_move(&self, player: Color) -> bool {
         let possible_moves = self.get_possible_moves(player, self.dice_rolls.clone());
         !possible_moves.is_empty()
     }
 
-    pub(crate) fn get_possible_moves(
-        &self,
-        player: Color,
-        dice_rolls: Vec<usize>,
-    ) -> Vec<(usize, i32)> {
-        let mut moves: Vec<(usize, i32)> = vec![];
-        let indices = self.board.get_points_for_color(player);
+    pub fn phase(&self) -> GamePhase {
+        if self.is_over() {
+            GamePhase::GameOver
+        } else if self.opening_roll {
+            GamePhase::OpeningRoll
+        } else if !self.dice_rolled || self.dice_rolls.is_empty() {
+            GamePhase::AwaitingRoll
+        } else {
+            GamePhase::AwaitingMove
+        }
+    }
 
-        for &index in indices.iter() {
-            for dice_roll in dice_rolls.iter() {
-                let next_index = self.board.get_index(player, index, *dice_roll);
-                if self.board.can_move_piece(player, index, next_index) {
-                    moves.push((index, next_index));
+    // Bear-off race `color` finishes with at least `LAST_ROLL_CHANCE` of the
+    // rolls of its next turn, when a cube owner considers the last-roll double.
+    // Positions with contact or too many checkers left are never counted.
+    pub fn is_last_roll_situation(&self, color: Color) -> bool {
+        let checkers_left = self.board.checkers_in_play(color);
+        if checkers_left == 0
+            || checkers_left > 4
+            || !self.board.is_race()
+            || !self.board.is_player_home_complete(color)
+        {
+            return false;
+        }
+
+        let finishing_rolls = (1..=6)
+            .cartesian_product(1..=6)
+            .filter(|&(first, second)| {
+                self.legal_turn_sequences(color, &expand_roll([first, second]))
+                    .into_iter()
+                    .any(|turn| {
+                        let mut board = self.board.clone();
+                        for checker_move in turn {
+                            board.make_move(color, checker_move).unwrap();
+                        }
+                        board.checkers_in_play(color) == 0
+                    })
+            })
+            .count();
+
+        finishing_rolls as f32 / 36.0 >= LAST_ROLL_CHANCE
+    }
+
+    // Winner of a race that's decided whatever is rolled, checked before the
+    // player on roll rolls: the leader's slowest finish comes before the
+    // trailer's fastest one.
+    pub fn decided_race_winner(&self) -> Option<Color> {
+        if self.is_over() || !self.board.is_race() {
+            return None;
+        }
+
+        // each die takes at least a pip, and every die plays while no point can
+        // fill up; with more checkers a full point may block one, so the race
+        // isn't counted as decided
+        let most_rolls = |color| {
+            (self.board.checkers_in_play(color) < MAX_POINT_CHECKERS)
+                .then(|| self.board.pip_count(color).div_ceil(2))
+        };
+        // a roll moves at most 24 pips and bears off at most 4 checkers
+        let fewest_rolls = |color| {
+            let pips = self.board.pip_count(color);
+            let checkers = self.board.checkers_in_play(color);
+            pips.div_ceil(24).max(checkers.div_ceil(4))
+        };
+
+        [self.player, self.player.opposite()]
+            .into_iter()
+            .find(|&leader| {
+                // the player on roll finishes first on the same number of rolls
+                let margin = if leader == self.player { 0 } else { 1 };
+                most_rolls(leader)
+                    .is_some_and(|rolls| rolls + margin <= fewest_rolls(leader.opposite()))
+            })
+    }
+
+    // Play out a decided race with dice from `roll`, each checker taking the
+    // first legal move, and return the winner. The game is left as it was
+    // unless the race plays out to the end.
+    pub fn finish_race(&mut self, mut roll: impl FnMut() -> [usize; 2]) -> Option<Color> {
+        let winner = self.decided_race_winner()?;
+        let mut game = self.play_from_here();
+        game.game_log = self.game_log.clone();
+        game.hits = self.hits;
+
+        while !game.is_over() {
+            let dice = roll();
+            game.dice_rolls = expand_roll(dice);
+            game.dice_rolled = true;
+            game.game_log.push(GameLogEntry {
+                player: game.player,
+                dice_rolls: dice.to_vec(),
+                opening_tie: false,
+            });
+
+            let player = game.player;
+            while let Some(&checker_move) = game
+                .get_possible_moves(player, game.dice_rolls.clone())
+                .first()
+            {
+                game.play_move(player, checker_move).ok()?;
+                if game.is_over() {
+                    break;
                 }
             }
+
+            if !game.is_over() {
+                game.switch_turn();
+            }
+        }
+
+        debug_assert_eq!(game.player, winner);
+        self.board = game.board;
+        self.player = game.player;
+        self.dice_rolls = game.dice_rolls;
+        self.dice_rolled = game.dice_rolled;
+        self.game_log = game.game_log;
+        self.hits = game.hits;
+        Some(winner)
+    }
+
+    // The dice are rolled but can't be played, and the turn wasn't passed.
+    pub fn is_stalled(&self) -> bool {
+        self.dice_rolled && !self.opening_roll && !self.is_over() && !self.can_move(self.player)
+    }
+
+    // Pass a rolled turn that can't be played, false when there's nothing to pass.
+    pub fn pass_turn(&mut self) -> bool {
+        if !self.is_stalled() {
+            return false;
         }
 
-        moves
+        self.switch_turn();
+        true
     }
 
-    pub(crate) fn get_possible_moves_for_piece(&self, player: Color, piece: usize) -> Vec<i32> {
-        let unique_rolls: Vec<usize> = self
-            .dice_rolls
+    pub fn get_possible_moves(&self, player: Color, dice_rolls: Vec<usize>) -> Vec<Move> {
+        let position_hash = self.board.position_hash();
+        let mut cache = self.move_cache.lock().unwrap();
+
+        // board changed since the cache was filled
+        if cache.position_hash != position_hash {
+            cache.position_hash = position_hash;
+            cache.moves.clear();
+        }
+
+        cache
+            .moves
+            .entry((player, dice_rolls))
+            .or_insert_with_key(|(player, dice_rolls)| self.generate_moves(*player, dice_rolls))
             .clone()
+    }
+
+    // Possible moves with whether each hits or makes a point.
+    pub fn get_possible_moves_detailed(
+        &self,
+        player: Color,
+        dice_rolls: Vec<usize>,
+    ) -> Vec<MoveInfo> {
+        self.get_possible_moves(player, dice_rolls)
+            .into_iter()
+            .map(|checker_move| {
+                let landing = checker_move
+                    .destination()
+                    .map_or(0, |to| self.board.points[to] * player.sign());
+
+                MoveInfo {
+                    from: checker_move.source(),
+                    to: checker_move.destination(),
+                    hits: landing == -1,
+                    makes_point: landing == 1,
+                }
+            })
+            .collect()
+    }
+
+    // Dice the player on roll can move a checker from one of `from_candidates`
+    // to `to` with this roll, as the first move of a legal turn.
+    pub fn dice_reaching(&self, from_candidates: &[usize], to: usize) -> Vec<usize> {
+        self.get_possible_moves(self.player, self.dice_rolls.clone())
+            .into_iter()
+            .filter(|checker_move| {
+                from_candidates.contains(&checker_move.source())
+                    && checker_move.destination() == Some(to)
+            })
+            .filter_map(|checker_move| {
+                self.board
+                    .die_for_move(self.player, checker_move, &self.dice_rolls)
+            })
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    // Single-die moves that start a turn using as many dice as possible.
+    fn generate_moves(&self, player: Color, dice_rolls: &[usize]) -> Vec<Move> {
+        let first_moves = self
+            .legal_turn_sequences(player, dice_rolls)
             .iter()
-            .unique()
-            .copied()
+            .map(|sequence| sequence[0])
             .collect_vec();
 
-        let possible_moves = self.get_possible_moves(player, unique_rolls);
+        self.board
+            .get_possible_moves(player, dice_rolls)
+            .into_iter()
+            .filter(|first_move| first_moves.contains(first_move))
+            .collect()
+    }
+
+    // All move sequences playing the largest possible number of dice. When only one
+    // of two different dice can be played, the higher one has to be used.
+    pub fn legal_turn_sequences(&self, player: Color, dice_rolls: &[usize]) -> Vec<Vec<Move>> {
+        self.board.legal_turn_sequences(player, dice_rolls)
+    }
 
-        let mut possible_moves: Vec<i32> = possible_moves
-            .iter()
-            .filter(|(from, _)| *from == piece)
-            .map(|(_, to)| *to)
+    // Whether `player` may play `checker_move` now: on roll with the dice
+    // rolled, and the move among the possible ones.
+    pub fn is_legal_move(&self, player: Color, checker_move: Move) -> bool {
+        player == self.player
+            && self.phase() == GamePhase::AwaitingMove
+            && self
+                .get_possible_moves_for_piece(player, checker_move.source())
+                .contains(&checker_move)
+    }
+
+    pub fn get_possible_moves_for_piece(&self, player: Color, piece: usize) -> Vec<Move> {
+        // the whole stack is needed to tell which moves keep the most dice playable
+        let possible_moves = self.get_possible_moves(player, self.dice_rolls.clone());
+
+        let mut possible_moves: Vec<Move> = possible_moves
+            .into_iter()
+            .filter(|checker_move| checker_move.source() == piece)
             .collect();
 
-        possible_moves.sort();
+        possible_moves.sort_by_key(|checker_move| checker_move.destination_index(player));
         if player == Color::Black {
             possible_moves.reverse();
         }
         possible_moves
     }
 
-    pub(crate) fn get_choosable_pieces(&self) -> (Vec<[usize; 2]>, [usize; 2]) {
+    // Moves of the turn when every legal way to play it only bears off checkers
+    // and ends in the same position.
+    pub fn forced_bear_off(&self, player: Color) -> Option<Vec<Move>> {
+        let sequences = self.legal_turn_sequences(player, &self.dice_rolls);
+        let all_bear_off = sequences
+            .iter()
+            .flatten()
+            .all(|checker_move| matches!(checker_move, Move::BearOff { .. }));
+        if sequences.is_empty() || !all_bear_off {
+            return None;
+        }
+
+        let outcomes = sequences
+            .iter()
+            .map(|sequence| {
+                let mut board = self.board.clone();
+                for checker_move in sequence.iter() {
+                    board.make_move(player, *checker_move).unwrap();
+                }
+                board
+            })
+            .unique()
+            .count();
+
+        (outcomes == 1).then(|| sequences[0].clone())
+    }
+
+    // Ways to move the checker on `piece` by both dice to another point, one entry
+    // per legal die order. Orders differ in the intermediate point they touch.
+    pub fn get_combined_moves_for_piece(&self, player: Color, piece: usize) -> Vec<[Move; 2]> {
+        if self.dice_rolls.len() != 2 || self.dice_rolls[0] == self.dice_rolls[1] {
+            return vec![];
+        }
+
+        self.legal_turn_sequences(player, &self.dice_rolls)
+            .into_iter()
+            .filter(|sequence| {
+                let [first, second] = [sequence[0], sequence[1]];
+                first.source() == piece
+                    && matches!(second, Move::Point { from, .. } if first.destination() == Some(from as usize))
+            })
+            .map(|sequence| [sequence[0], sequence[1]])
+            .collect()
+    }
+
+    // Dice still to play and the number the roll started with, four for doubles.
+    pub fn moves_left(&self) -> Option<(usize, usize)> {
+        let last_roll = &self.game_log.last()?.dice_rolls;
+        if self.dice_rolls.is_empty() {
+            return None;
+        }
+
+        let total = match last_roll.as_slice() {
+            [a, b] if a == b => 4,
+            dice_rolls => dice_rolls.len(),
+        };
+        Some((self.dice_rolls.len(), total))
+    }
+
+    pub fn use_dice_roll(&mut self, dice_roll: usize) {
+        if let Some(index) = self.dice_rolls.iter().position(|&x| x == dice_roll) {
+            self.dice_rolls.remove(index);
+        }
+    }
+
+    // Board points the checker on `piece` can't move to with the current dice.
+    pub fn get_illegal_destinations_for_piece(&self, player: Color, piece: usize) -> Vec<usize> {
+        let possible_moves = self.get_possible_moves_for_piece(player, piece);
+
+        (0..24)
+            .filter(|&point| {
+                !possible_moves
+                    .iter()
+                    .any(|checker_move| checker_move.destination() == Some(point))
+            })
+            .collect()
+    }
+
+    pub fn board_snapshot(&self) -> BoardSnapshot {
+        let board = &self.board;
+        let mut points = [(None, 0); 24];
+        for (i, point) in points.iter_mut().enumerate() {
+            *point = (board.get_point_color(i), board.get_point_count(i) as u8);
+        }
+
+        let colors = [Color::White, Color::Black];
+
+        BoardSnapshot {
+            points,
+            bar: colors.map(|color| board.bar[board.bar_index(color)] as u8),
+            off: colors.map(|color| board.borne_off[board.bar_index(color)]),
+        }
+    }
+
+    pub fn get_choosable_pieces(&self) -> (Vec<[usize; 2]>, [usize; 2]) {
         let mut choosable_pieces_on_board: Vec<[usize; 2]> = vec![];
-        let choosable_bar_pieces = [0, 0];
+        let mut choosable_bar_pieces = [0, 0];
 
         let possible_moves = self.get_possible_moves(self.player, self.dice_rolls.clone());
 
+        // only the top checker of the bar stack is offered
+        if possible_moves
+            .iter()
+            .any(|checker_move| matches!(checker_move, Move::Enter { .. }))
+        {
+            let bar_index = self.board.bar_index(self.player);
+            choosable_bar_pieces[bar_index] = stack_rows(self.board.bar[bar_index] as usize);
+        }
+
         // fill choosable_pieces_on_board with pieces that can be chosen according to their color (value)
         for i in 0..24 {
             let point_count = self.board.points[i];
 
-            if point_count == 0 {
-                continue;
-            }
-
-            if point_count < 0 && self.player != Color::Black {
+            if Color::from_sign(point_count) != Some(self.player) {
                 continue;
             }
 
-            if point_count > 0 && self.player != Color::White {
-                continue;
-            }
-
-            let position_in_possible_moveset =
-                possible_moves.iter().filter(|(from, _)| *from == i).count();
+            let position_in_possible_moveset = possible_moves
+                .iter()
+                .filter(|checker_move| checker_move.source() == i)
+                .count();
 
             if position_in_possible_moveset == 0 {
                 continue;
             }
 
-            choosable_pieces_on_board.push([i + 1, point_count.unsigned_abs() as usize]);
+            choosable_pieces_on_board.push([
+                BoardIndex(i as u8).label().get(),
+                stack_rows(point_count.unsigned_abs() as usize),
+            ]);
         }
 
         (choosable_pieces_on_board, choosable_bar_pieces)
     }
 
-    pub(crate) fn new() -> Self {
-        let mut points = [0; 24];
-        points[0] = 2;
-        points[23] = -2;
-        points[5] = -5;
-        points[18] = 5;
-        points[16] = 3;
-        points[7] = -3;
-        points[12] = -5;
-        points[11] = 5;
+    pub fn new() -> Self {
+        let points = GameVariant::Standard.opening_points();
 
         // points[18] = 5;
         // points[19] = 5;
@@ -309,41 +1263,224 @@ impl Game {
         // points[1] -= 5;
         // points[2] -= 5;
 
-        // Create a new game instance with an empty board and start with player 1
+        // Create a new game instance; the first player is decided by the opening roll
         Game {
             board: Board {
                 points,
                 bar: [0, 0],
+                borne_off: [0, 0],
             },
             dice_rolls: vec![],
             player: Color::White,
             dice_rolled: false,
+            opening_roll: true,
             game_log: vec![],
+            hits: [0, 0],
+            cube: DoublingCube::default(),
+            match_score: None,
+            move_cache: Mutex::new(MoveCache::default()),
         }
     }
 
-    pub(crate) fn switch_turn(&mut self) {
+    // Build a game from per-color checker counts, indexed by 0-based point,
+    // with `player` on roll and the opening roll already played.
+    pub fn from_position(
+        white: [i32; 24],
+        black: [i32; 24],
+        bar: [i32; 2],
+        player: Color,
+    ) -> Result<Self, String> {
+        let mut points = [0; 24];
+
+        for i in 0..24 {
+            if white[i] < 0 || black[i] < 0 {
+                return Err(format!("Negative checker count on point {}", i + 1));
+            }
+
+            if white[i] > 0 && black[i] > 0 {
+                return Err(format!("Both colors on point {}", i + 1));
+            }
+
+            points[i] = white[i] - black[i];
+        }
+
+        if bar.iter().any(|&count| count < 0) {
+            return Err(String::from("Negative checker count on the bar"));
+        }
+
+        let mut board = Board {
+            points,
+            bar,
+            borne_off: [0, 0],
+        };
+
+        // checkers missing from the position count as borne off
+        for color in [Color::White, Color::Black] {
+            let total = board.checkers_in_play(color);
+            if total > 15 {
+                return Err(format!(
+                    "{:?} has {} checkers, at most 15 allowed",
+                    color, total
+                ));
+            }
+            board.borne_off[board.bar_index(color)] = 15 - total as u8;
+        }
+
+        let mut game = Game::new();
+        game.board = board;
+        game.player = player;
+        game.opening_roll = false;
+
+        Ok(game)
+    }
+
+    // New game from this one's board with the same player on roll, for playing
+    // on from a position reached in a replay. Dice, log and cube start afresh.
+    pub fn play_from_here(&self) -> Self {
+        let mut game = Game::new();
+        game.board = self.board.clone();
+        game.player = self.player;
+        game.opening_roll = false;
+
+        game
+    }
+
+    // One line text form of the position for sharing, e.g.
+    // `White 1:2 12:5 17:3 19:5 bar:0 off:0; Black 6:5 8:3 13:5 24:2 bar:0 off:0; White to move`
+    // with 1-based points, the counterpart of `from_position`.
+    pub fn to_position(&self) -> String {
+        let board = &self.board;
+        let mut sides = vec![];
+
+        for color in [Color::White, Color::Black] {
+            let mut side = format!("{:?}", color);
+            for point in 0..24 {
+                if board.get_point_color(point) == Some(color) {
+                    side += &format!(" {}:{}", point + 1, board.get_point_count(point));
+                }
+            }
+            let index = board.bar_index(color);
+            side += &format!(" bar:{} off:{}", board.bar[index], board.borne_off[index]);
+            sides.push(side);
+        }
+
+        let mut to_move = format!("{:?} to move", self.player);
+        if !self.dice_rolls.is_empty() {
+            to_move += &format!(", dice {}", self.dice_rolls.iter().join(" "));
+        }
+        sides.push(to_move);
+
+        sides.join("; ")
+    }
+
+    // Play a match to `length` points instead of a money game.
+    pub fn select_match_length(&mut self, length: u32) {
+        self.match_score = Some(MatchScore::new(length));
+        self.cube = DoublingCube::default();
+    }
+
+    pub fn cube_enabled(&self) -> bool {
+        self.match_score
+            .is_none_or(|match_score| match_score.cube_enabled())
+    }
+
+    pub fn make_move(&mut self, player: Color, checker_move: Move) -> Result<bool, String> {
+        let (from, to) = (
+            checker_move.source(),
+            checker_move.destination_index(player),
+        );
+        if !self
+            .board
+            .is_die_sized_move(player, from, to, &self.dice_rolls)
+        {
+            return Err(String::from("Move doesn't match any die"));
+        }
+
+        let hit = self.board.make_move(player, checker_move)?;
+        if hit {
+            self.hits[self.board.opposite_bar_index(player)] += 1;
+        }
+
+        Ok(hit)
+    }
+
+    // Make the move and use up the die it's played with.
+    pub fn play_move(&mut self, player: Color, checker_move: Move) -> Result<bool, String> {
+        let die = self
+            .board
+            .die_for_move(player, checker_move, &self.dice_rolls);
+        let hit = self.make_move(player, checker_move)?;
+
+        if let Some(die) = die {
+            self.use_dice_roll(die);
+        }
+        Ok(hit)
+    }
+
+    // Each player rolls a single die and the higher one starts, playing both values.
+    // Returns false on a tie, in which case the opening roll has to be repeated.
+    pub fn resolve_opening_roll(&mut self, white_roll: usize, black_roll: usize) -> bool {
+        if white_roll == black_roll {
+            self.dice_rolled = false;
+            return false;
+        }
+
+        self.player = if white_roll > black_roll {
+            Color::White
+        } else {
+            Color::Black
+        };
+        self.dice_rolls = vec![white_roll, black_roll];
+        self.opening_roll = false;
+        true
+    }
+
+    // Opening roll from `seed` instead of the dice, for tournaments. Every roll,
+    // ties included, goes to the log, so the opening can be checked against the
+    // seed. Returns the deciding roll, White's die first.
+    pub fn seeded_opening_roll(&mut self, seed: u64) -> Option<[usize; 2]> {
+        if !self.opening_roll {
+            return None;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        loop {
+            let dice = [rng.gen_range(1..=6), rng.gen_range(1..=6)];
+            self.game_log.push(GameLogEntry {
+                player: self.player,
+                dice_rolls: dice.to_vec(),
+                opening_tie: false,
+            });
+
+            if self.resolve_opening_roll(dice[0], dice[1]) {
+                self.game_log.last_mut().unwrap().player = self.player;
+                self.dice_rolled = true;
+                return Some(dice);
+            }
+            self.game_log.last_mut().unwrap().opening_tie = true;
+        }
+    }
+
+    pub fn switch_turn(&mut self) {
         self.player = self.player.opposite();
         self.dice_rolled = false;
         self.dice_rolls = vec![];
     }
 
-    pub(crate) fn highest_point_in_home_zone(&self) -> (usize, i32) {
-        let mut highest_index = 0;
-        let mut highest_value = 0;
-        for i in self.board.home(self.player) {
-            if self.board.points[i] > highest_value * self.board.direction(self.player) {
-                highest_index = i;
-                highest_value = self.board.points[i];
-            }
-        }
+    // Home point with the most of the player's checkers and its signed count,
+    // `None` rather than point 0 when none of them are home.
+    pub fn highest_point_in_home_zone(&self) -> Option<(usize, i32)> {
+        let sign = self.player.sign();
 
-        (highest_index, highest_value)
+        self.board
+            .home(self.player)
+            .filter(|&i| self.board.points[i] * sign > 0)
+            .max_by_key(|&i| self.board.points[i] * sign)
+            .map(|i| (i, self.board.points[i]))
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn bear_off_piece(&mut self, from: i32, roll: i32) {
-        let direction = if self.player == Color::White { 1 } else { -1 };
+    pub fn bear_off_piece(&mut self, from: i32, roll: i32) {
+        let direction = self.player.sign();
         let index = from - direction;
         let value = self.board.points[index as usize];
 
@@ -355,12 +1492,11 @@ impl Game {
                 .board
                 .can_move_piece(self.player, next_index, next_destination)
             {
-                self.board
-                    .make_move(self.player, next_index, next_destination)
+                self.make_move(self.player, Move::between(next_index, next_destination))
                     .unwrap();
-            } else {
+            } else if let Some((highest_index, _highest_value)) = self.highest_point_in_home_zone()
+            {
                 // remove a piece from the highest point on which one of this checkers resides
-                let (highest_index, _highest_value) = self.highest_point_in_home_zone();
                 self.board.points[highest_index] -= direction;
             }
         } else {
@@ -368,9 +1504,168 @@ impl Game {
         }
     }
 
-    pub(crate) fn is_over(&self) -> bool {
-        let player1_borne_off = self.board.points[0..18].iter().all(|&x| x == 0);
-        let player2_borne_off = self.board.points[6..24].iter().all(|&x| x == 0);
-        player1_borne_off || player2_borne_off
+    pub fn is_over(&self) -> bool {
+        self.board.borne_off.contains(&15)
+    }
+
+    pub fn win_kind(&self, winner: Color) -> WinKind {
+        let loser = winner.opposite();
+        if self.board.borne_off[self.board.bar_index(loser)] > 0 {
+            return WinKind::Single;
+        }
+
+        let stuck_behind = self.board.bar[self.board.bar_index(loser)] > 0
+            || self
+                .board
+                .home(winner)
+                .any(|i| self.board.get_point_color(i) == Some(loser));
+        if stuck_behind {
+            WinKind::Backgammon
+        } else {
+            WinKind::Gammon
+        }
+    }
+
+    pub fn summary(&self, winner: Color) -> GameSummary {
+        GameSummary {
+            winner,
+            win_kind: self.win_kind(winner),
+            cube_value: self.cube.value,
+            pip_counts: [
+                self.board.pip_count(Color::White),
+                self.board.pip_count(Color::Black),
+            ],
+            hits: self.hits,
+            turns: self
+                .game_log
+                .iter()
+                .filter(|entry| !entry.opening_tie)
+                .count(),
+        }
+    }
+}
+
+// Moves of a turn arranged on a working copy of the game, the real one only
+// changes once they're confirmed.
+pub struct StagedTurn {
+    // the game as it was when the first move was staged
+    start: Game,
+    working: Game,
+    moves: Vec<Move>,
+}
+
+impl StagedTurn {
+    pub fn new(game: &Game) -> Self {
+        StagedTurn {
+            start: Self::working_copy(game),
+            working: Self::working_copy(game),
+            moves: vec![],
+        }
+    }
+
+    fn working_copy(game: &Game) -> Game {
+        let mut working = game.play_from_here();
+        working.dice_rolls = game.dice_rolls.clone();
+        working.dice_rolled = game.dice_rolled;
+        working.hits = game.hits;
+
+        working
+    }
+
+    pub fn working(&self) -> &Game {
+        &self.working
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    // Play a move on the working copy, returns whether it hit.
+    pub fn stage(&mut self, checker_move: Move) -> Result<bool, String> {
+        let player = self.working.player;
+        let hit = self.working.play_move(player, checker_move)?;
+        self.moves.push(checker_move);
+
+        Ok(hit)
+    }
+
+    // Take back the last staged move by playing the others again from the start.
+    pub fn undo(&mut self) -> Option<Move> {
+        let undone = self.moves.pop()?;
+
+        self.working = Self::working_copy(&self.start);
+        for checker_move in self.moves.iter() {
+            let player = self.working.player;
+            self.working.play_move(player, *checker_move).unwrap();
+        }
+
+        Some(undone)
+    }
+
+    // Nothing left to play with the dice, the turn can be confirmed.
+    pub fn is_complete(&self) -> bool {
+        self.working.is_over() || !self.working.can_move(self.working.player)
+    }
+
+    // Play the staged moves on `game`, which has to be where staging started.
+    pub fn confirm(self, game: &mut Game) -> Result<(), String> {
+        if game.board != self.start.board
+            || game.player != self.start.player
+            || game.dice_rolls != self.start.dice_rolls
+        {
+            return Err(String::from(
+                "The game moved on since the moves were staged",
+            ));
+        }
+
+        let player = game.player;
+        for checker_move in self.moves {
+            game.play_move(player, checker_move)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_moves_match_generated_ones() {
+        let mut game = Game::new();
+        game.opening_roll = false;
+        let dice_rolls = vec![3, 5];
+
+        let moves = game.get_possible_moves(Color::White, dice_rolls.clone());
+        assert_eq!(moves, game.generate_moves(Color::White, &dice_rolls));
+        assert_eq!(
+            moves,
+            game.get_possible_moves(Color::White, dice_rolls.clone())
+        );
+
+        // a move changes the position the cache was filled for
+        let hash = game.board.position_hash();
+        game.board
+            .make_move(Color::White, Move::between(0, 3))
+            .unwrap();
+        assert_ne!(game.board.position_hash(), hash);
+
+        let moves_after = game.get_possible_moves(Color::White, dice_rolls.clone());
+        assert_eq!(moves_after, game.generate_moves(Color::White, &dice_rolls));
+        assert_ne!(moves_after, moves);
+    }
+
+    #[test]
+    fn colors_convert_from_and_to_the_point_sign() {
+        assert_eq!(Color::from_sign(3), Some(Color::White));
+        assert_eq!(Color::from_sign(-2), Some(Color::Black));
+        assert_eq!(Color::from_sign(0), None);
+
+        assert_eq!(Color::White.sign(), 1);
+        assert_eq!(Color::Black.sign(), -1);
+        for color in [Color::White, Color::Black] {
+            assert_eq!(Color::from_sign(color.sign()), Some(color));
+        }
     }
 }
@@ -0,0 +1,7 @@
+// Headless engine shared by the game and its tests: the rules, saves and the
+// channel service, without any of the Bevy scene.
+pub mod cube;
+pub mod game;
+pub mod save;
+pub mod scenario;
+pub mod service;
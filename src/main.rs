@@ -1,13 +1,28 @@
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
+mod ai;
+mod chouette;
+mod clipboard;
+mod clock;
+mod confirm;
+mod edit;
 mod events;
-mod game;
+mod mat;
+mod replay;
+mod stats;
+#[cfg(test)]
+mod test_support;
+mod toast;
 mod ui;
+mod undo;
+mod window;
 
 use crate::ui::setup_ui;
 use bevy::{
     pbr::{CascadeShadowConfigBuilder, DirectionalLightShadowMap},
     prelude::*,
+    render::camera::ScalingMode,
 };
+use bevy_backgammon::{cube, game, scenario};
 
 use bevy_dice::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
@@ -15,17 +30,63 @@ use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_kira_audio::AudioPlugin;
 use bevy_mod_picking::*;
 use bevy_rapier3d::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
+use clipboard::*;
+use clock::*;
+use confirm::*;
+use edit::*;
 use events::*;
+use replay::*;
+use stats::*;
+use toast::*;
 use ui::*;
+use undo::*;
+use window::*;
 
 #[derive(Clone, Debug, Resource)]
 pub(crate) struct GameResources {
     white_material: Handle<StandardMaterial>,
     black_material: Handle<StandardMaterial>,
     highlighted_material: Handle<StandardMaterial>,
+    chosen_material: Handle<StandardMaterial>,
     candidate_material: Handle<StandardMaterial>,
+    dimmed_material: Handle<StandardMaterial>,
+    ghost_material: Handle<StandardMaterial>,
+    white_army_material: Handle<StandardMaterial>,
+    black_army_material: Handle<StandardMaterial>,
     checkers_model: Handle<Mesh>,
+    checkers_model_low: Handle<Mesh>,
+    checker_lod: CheckerLod,
+}
+
+// Checkers high up a stack are drawn with a low-poly mesh, so positions with
+// tall stacks stay cheap to render.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CheckerLod {
+    enabled: bool,
+    // checkers of a stack drawn with the full mesh
+    piece_threshold: usize,
+}
+
+impl Default for CheckerLod {
+    fn default() -> Self {
+        CheckerLod {
+            enabled: true,
+            piece_threshold: 3,
+        }
+    }
+}
+
+impl GameResources {
+    pub(crate) fn checker_mesh(&self, piece: &Piece) -> Handle<Mesh> {
+        if self.checker_lod.enabled && piece.row > self.checker_lod.piece_threshold {
+            self.checkers_model_low.clone()
+        } else {
+            self.checkers_model.clone()
+        }
+    }
 }
 
 impl FromWorld for GameResources {
@@ -35,20 +96,453 @@ impl FromWorld for GameResources {
         let mut materials = world
             .get_resource_mut::<Assets<StandardMaterial>>()
             .unwrap();
+        let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
 
         let checkers_model = asset_server.load("models/piece.glb#Mesh0/Primitive0");
+        // same extent as the model, with an octagonal rim
+        let checkers_model_low = meshes.add(
+            shape::Cylinder {
+                radius: 1.0,
+                height: 2.0,
+                resolution: 8,
+                segments: 1,
+            }
+            .into(),
+        );
 
         GameResources {
             white_material: materials.add(bevy::prelude::Color::WHITE.into()),
             black_material: materials.add(bevy::prelude::Color::BLACK.into()),
             highlighted_material: materials.add(bevy::prelude::Color::RED.into()),
+            chosen_material: materials.add(bevy::prelude::Color::ORANGE.into()),
             candidate_material: materials
                 .add(bevy::prelude::Color::rgba(0.0, 0.9, 0.0, 0.5).into()),
+            dimmed_material: materials.add(bevy::prelude::Color::rgba(0.3, 0.3, 0.3, 0.4).into()),
+            ghost_material: materials.add(bevy::prelude::Color::rgba(0.8, 0.8, 0.8, 0.3).into()),
+            white_army_material: materials.add(bevy::prelude::Color::rgb(1.0, 0.93, 0.8).into()),
+            black_army_material: materials.add(bevy::prelude::Color::rgb(0.12, 0.1, 0.05).into()),
             checkers_model,
+            checkers_model_low,
+            checker_lod: CheckerLod::default(),
+        }
+    }
+}
+
+const DEFAULT_BOARD_ROTATION: f32 = std::f32::consts::FRAC_PI_2;
+const DEFAULT_BOARD_SCALE: f32 = 0.6;
+const DEFAULT_BOARD_Y_OFFSET: f32 = 0.05;
+const DEFAULT_PIECE_SCALE: f32 = 0.03;
+
+// Placement of the board in the scene. Point positions are measured on the board
+// in its default placement and go through the same rotation, scale and height,
+// so changing any of them keeps the pieces on the points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct BoardTransform {
+    // about the y axis
+    rotation: f32,
+    scale: f32,
+    y_offset: f32,
+}
+
+impl Default for BoardTransform {
+    fn default() -> Self {
+        BoardTransform {
+            rotation: DEFAULT_BOARD_ROTATION,
+            scale: DEFAULT_BOARD_SCALE,
+            y_offset: DEFAULT_BOARD_Y_OFFSET,
         }
     }
 }
 
+impl BoardTransform {
+    // Scale relative to the one point positions were measured at.
+    fn ratio(&self) -> f32 {
+        self.scale / DEFAULT_BOARD_SCALE
+    }
+
+    // Rotation relative to the one point positions were measured at.
+    fn relative_rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.rotation - DEFAULT_BOARD_ROTATION)
+    }
+
+    // Transform of the board model, `model_scale` is the model's own scale factor.
+    fn transform(&self, model_scale: f32) -> Transform {
+        Transform::from_xyz(0.0, self.y_offset, 0.0)
+            .with_rotation(Quat::from_rotation_y(self.rotation))
+            .with_scale(Vec3::splat(self.scale * model_scale))
+    }
+
+    // Scene position of a point measured on the board in its default placement,
+    // `x` running along the board and `y` across it, `height` above the pieces' level.
+    fn scene_position(&self, [x, y]: [f32; 2], height: f32) -> Vec3 {
+        let offset = Vec3::new(y * self.ratio(), height, x * self.ratio());
+        self.relative_rotation() * offset + Vec3::Y * (self.y_offset - DEFAULT_BOARD_Y_OFFSET)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Resource)]
+pub(crate) struct SceneScale {
+    board: BoardTransform,
+    piece: f32,
+}
+
+impl Default for SceneScale {
+    fn default() -> Self {
+        SceneScale {
+            board: BoardTransform::default(),
+            piece: DEFAULT_PIECE_SCALE,
+        }
+    }
+}
+
+// Board model, can be replaced by a custom GLB. `scale` is relative to the
+// board transform and `y_offset` lifts the model in scene units, for models
+// whose playing surface isn't at their origin. Point positions still follow
+// `SceneScale`.
+#[derive(Clone, Debug, Resource)]
+pub(crate) struct BoardAsset {
+    scene_path: String,
+    scale: f32,
+    y_offset: f32,
+}
+
+impl Default for BoardAsset {
+    fn default() -> Self {
+        BoardAsset {
+            scene_path: String::from("models/board.glb"),
+            scale: 1.0,
+            y_offset: 0.0,
+        }
+    }
+}
+
+impl BoardAsset {
+    // Board from `--board <file>`, `--board-scale <factor>` and
+    // `--board-y-offset <units>`, the default model for any flag left out.
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let flag = |name: &str| {
+            args.iter()
+                .skip_while(|arg| *arg != name)
+                .nth(1)
+                .map(String::as_str)
+        };
+        let number = |name: &str, default: f32| match flag(name) {
+            Some(value) => value
+                .parse::<f32>()
+                .map_err(|_| format!("{} needs a number, got {:?}", name, value)),
+            None => Ok(default),
+        };
+
+        let default = BoardAsset::default();
+        Ok(BoardAsset {
+            scene_path: flag("--board").map_or(default.scene_path, String::from),
+            scale: number("--board-scale", default.scale)?,
+            y_offset: number("--board-y-offset", default.y_offset)?,
+        })
+    }
+
+    // Asset path of the scene to spawn, the first scene of the file unless one is named.
+    fn scene(&self) -> String {
+        if self.scene_path.contains('#') {
+            self.scene_path.clone()
+        } else {
+            format!("{}#Scene0", self.scene_path)
+        }
+    }
+
+    fn transform(&self, scene_scale: SceneScale) -> Transform {
+        let mut transform = scene_scale.board.transform(self.scale);
+        transform.translation.y += self.y_offset;
+        transform
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct BoardScene;
+
+#[derive(Component)]
+pub(crate) struct MainCamera;
+
+// Strip along the edge of the board on the side of the player on roll.
+#[derive(Component)]
+pub(crate) struct OnRollGlow;
+
+// Flashes the bar while the player on roll has checkers to enter.
+#[derive(Component)]
+pub(crate) struct BarCue {
+    timer: Timer,
+}
+
+const BAR_CUE_SECONDS: f32 = 2.0;
+const BAR_CUE_FLASHES_PER_SECOND: f32 = 3.0;
+
+pub(crate) fn needs_bar_cue(game: &game::Game) -> bool {
+    game.board.bar[game.board.bar_index(game.player)] > 0
+}
+
+// Board half holding `player`'s home board, along the scene's x axis.
+pub(crate) fn on_roll_side(player: game::Color) -> f32 {
+    match player {
+        game::Color::White => 1.0,
+        game::Color::Black => -1.0,
+    }
+}
+
+fn on_roll_glow_transform(player: game::Color, scene_scale: SceneScale) -> Transform {
+    let board = scene_scale.board;
+
+    Transform::from_translation(board.scene_position([0.0, on_roll_side(player) * 0.42], 0.06))
+        .with_rotation(board.relative_rotation())
+        .with_scale(Vec3::splat(board.ratio()))
+}
+
+// Doubling cube beside the board, with its value drawn over it as a label.
+#[derive(Component)]
+pub(crate) struct CubeModel;
+
+#[derive(Component)]
+pub(crate) struct LabelCubeValue;
+
+const CUBE_SIZE: f32 = 0.04;
+// just past the outer edge of the points, by the middle of the board
+const CUBE_X: f32 = 0.6;
+// distance towards the owner's side of the board
+const CUBE_SIDE_OFFSET: f32 = 0.3;
+
+// The cube sits in the middle until someone owns it, then on their side.
+pub(crate) fn cube_transform(cube: &cube::DoublingCube, scene_scale: SceneScale) -> Transform {
+    let board = scene_scale.board;
+    let side = cube.owner.map_or(0.0, on_roll_side);
+
+    Transform::from_translation(board.scene_position([CUBE_X, side * CUBE_SIDE_OFFSET], 0.0))
+        .with_rotation(board.relative_rotation())
+        .with_scale(Vec3::splat(board.ratio()))
+}
+
+// Scene lighting, brightened or dimmed at runtime with = and -.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub(crate) struct LightingSettings {
+    ambient: f32,
+    spot_intensity: f32,
+}
+
+impl Default for LightingSettings {
+    fn default() -> Self {
+        LightingSettings {
+            ambient: 1.0 / 5.0,
+            spot_intensity: 800.0,
+        }
+    }
+}
+
+impl LightingSettings {
+    const AMBIENT_STEP: f32 = 0.05;
+    const MAX_AMBIENT: f32 = 1.0;
+    const SPOT_STEP: f32 = 200.0;
+
+    // Brighter for positive `steps`, darker for negative ones.
+    fn adjust(&mut self, steps: f32) {
+        self.ambient = (self.ambient + steps * Self::AMBIENT_STEP).clamp(0.0, Self::MAX_AMBIENT);
+        self.spot_intensity = (self.spot_intensity + steps * Self::SPOT_STEP).max(0.0);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub(crate) enum CameraMode {
+    #[default]
+    Perspective,
+    TopDown,
+}
+
+impl CameraMode {
+    fn camera(&self) -> (Projection, Transform) {
+        match self {
+            CameraMode::Perspective => (
+                Projection::Perspective(PerspectiveProjection::default()),
+                Transform::from_xyz(-1.7, 1.7, 0.0).looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
+            ),
+            // straight down on the board, keeping the same side facing up on screen
+            CameraMode::TopDown => (
+                Projection::Orthographic(OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical(1.4),
+                    ..default()
+                }),
+                Transform::from_xyz(0.0, 3.0, 0.0).looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::X),
+            ),
+        }
+    }
+}
+
+// Marks points a selected checker can't move to, for learners.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub(crate) struct BeginnerAssist {
+    enabled: bool,
+}
+
+// Makes choosable pieces glow on and off, `speed` in pulses per second.
+#[derive(Clone, Copy, Debug, Resource)]
+pub(crate) struct HighlightPulse {
+    enabled: bool,
+    speed: f32,
+}
+
+impl Default for HighlightPulse {
+    fn default() -> Self {
+        HighlightPulse {
+            enabled: true,
+            speed: 1.0,
+        }
+    }
+}
+
+impl HighlightPulse {
+    // Glow strength in 0..=1 at `seconds`, a steady full glow when disabled.
+    fn intensity(&self, seconds: f32) -> f32 {
+        if !self.enabled {
+            return 1.0;
+        }
+
+        0.5 + 0.5 * (seconds * self.speed * std::f32::consts::TAU).sin()
+    }
+}
+
+// Faintly tints all of the active player's checkers so they can see their army
+// at a glance, below the choosable and candidate highlights.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub(crate) struct OwnArmyHighlight {
+    enabled: bool,
+}
+
+const CHECKER_ROTATION_SEED: u64 = 0x5eed;
+const MAX_CHECKER_ROTATION: f32 = 0.25;
+
+// Small turn around the vertical axis so stacked checkers don't look perfectly
+// aligned, the same for a given spot on the board and seed.
+pub(crate) fn checker_rotation(position: usize, row: usize, seed: u64) -> f32 {
+    // splitmix64 of the inputs
+    let mut x = seed ^ ((position as u64) << 32 | row as u64);
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^= x >> 31;
+
+    let unit = (x >> 40) as f32 / (1u64 << 24) as f32;
+    (unit * 2.0 - 1.0) * MAX_CHECKER_ROTATION
+}
+
+// Plays the turn automatically when it can only be spent bearing off.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub(crate) struct AutoBearOff {
+    enabled: bool,
+}
+
+// Plays out races that can no longer be lost with random dice, straight to
+// the final board.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub(crate) struct AutoFinishRaces {
+    enabled: bool,
+}
+
+// Clicking away from the chosen checker drops its candidate destinations.
+#[derive(Clone, Copy, Debug, Resource)]
+pub(crate) struct AutoClearCandidates {
+    enabled: bool,
+}
+
+impl Default for AutoClearCandidates {
+    fn default() -> Self {
+        AutoClearCandidates { enabled: true }
+    }
+}
+
+// Tournament games take the opening roll from a seed, so it can be reproduced.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub(crate) struct TournamentOpening {
+    seed: Option<u64>,
+}
+
+// Puzzle being played, its dice come from the script.
+#[derive(Clone, Debug, Default, Resource)]
+pub(crate) struct ActiveScenario {
+    scenario: Option<scenario::Scenario>,
+}
+
+// Redraws the pieces when the board was edited outside of a move, e.g. live in
+// the inspector. Moves redraw the pieces themselves, so those already match.
+pub(crate) fn handle_board_edit(
+    mut commands: Commands,
+    game: Res<game::Game>,
+    mut pieces_query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
+    game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
+    mut highlight_pickable_pieces_event_writer: EventWriter<HighlightPickablePiecesEvent>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+
+    let shown = pieces_query
+        .iter()
+        .filter(|(_, piece, _)| !piece.candidate && !piece.dimmed)
+        .map(|(_, piece, _)| piece.id())
+        .collect::<HashSet<_>>();
+    let expected = board_pieces(&game)
+        .iter()
+        .map(Piece::id)
+        .collect::<HashSet<_>>();
+    if shown == expected {
+        return;
+    }
+
+    redraw_pieces(
+        &mut commands,
+        &mut pieces_query,
+        &game,
+        &game_resources,
+        *scene_scale,
+    );
+
+    if !game.dice_rolls.is_empty() {
+        highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
+    }
+}
+
+// Quick casual start where White rolls first, instead of the opening roll
+// contest deciding who starts.
+#[derive(Clone, Copy, Debug, Resource)]
+pub(crate) struct SkipOpeningRoll {
+    enabled: bool,
+}
+
+impl Default for SkipOpeningRoll {
+    fn default() -> Self {
+        SkipOpeningRoll { enabled: true }
+    }
+}
+
+impl SkipOpeningRoll {
+    pub(crate) fn new_game(&self) -> game::Game {
+        let mut game = game::Game::new();
+        game.opening_roll = !self.enabled;
+        game
+    }
+}
+
+// Identity of a checker that stays the same across redraws while it isn't moved.
+// Both bars share a point, so the color is part of it.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct PieceId {
+    point: usize,
+    row: usize,
+    color: game::Color,
+}
+
+// Row a piece is drawn at. Rows past the drawn stack, like a candidate landing
+// on a tall stack, sit just above it instead of running off the board.
+pub(crate) fn render_row(row: usize) -> usize {
+    row.min(game::MAX_STACK_ROWS + 1)
+}
+
 #[derive(Component, Clone, Copy)]
 pub(crate) struct Piece {
     row: usize,
@@ -56,19 +550,36 @@ pub(crate) struct Piece {
     color: game::Color,
     highlighted: bool,
     candidate: bool,
+    dimmed: bool,
     chosen: bool,
 }
 
 impl Piece {
-    fn board_coordinates(&self) -> [f32; 2] {
+    fn board_coordinates(&self, scene_scale: SceneScale) -> [f32; 2] {
         const DELTA_Y: f32 = 0.07;
 
+        // offsets below are measured on the board at its default scale,
+        // while stacked checkers are spaced by their own size
+        let delta_y = DELTA_Y * scene_scale.piece / DEFAULT_PIECE_SCALE / scene_scale.board.ratio();
+
+        let row = render_row(self.row);
         let mut coordinates: [f32; 2] = [0.0, 0.0];
 
         let mut y_start;
         let mut x_start;
         let mut x_end;
 
+        // checkers on the bar stack up in the middle of the board, on their entry side
+        if self.position == game::BAR + 1 {
+            let side = match self.color {
+                game::Color::White => -1.0,
+                game::Color::Black => 1.0,
+            };
+            coordinates[1] = side * delta_y * row as f32;
+
+            return coordinates;
+        }
+
         if (1..=12).contains(&self.position) {
             y_start = -0.34;
             x_start = 0.08;
@@ -77,7 +588,7 @@ impl Piece {
             let delta = (x_end - x_start) / 5.0;
             let offset = -1.0 * (self.position as f32) + 6.0;
             coordinates[0] = x_start + delta * offset;
-            coordinates[1] = y_start + DELTA_Y * (self.row - 1) as f32;
+            coordinates[1] = y_start + delta_y * (row - 1) as f32;
 
             if self.position >= 7 {
                 coordinates[0] -= 0.06;
@@ -92,7 +603,7 @@ impl Piece {
             let delta = (x_end - x_start) / 5.0;
             let offset = 1.0 * (self.position as f32) - 1.0;
             coordinates[0] = x_start + delta * offset - 0.718 - 0.3 + 0.017 - 0.06;
-            coordinates[1] = y_start - DELTA_Y * (self.row - 1) as f32;
+            coordinates[1] = y_start - delta_y * (row - 1) as f32;
 
             if self.position >= 19 {
                 coordinates[0] += 0.039;
@@ -103,21 +614,93 @@ impl Piece {
 
         coordinates
     }
+
+    fn label(&self) -> game::PointLabel {
+        game::PointLabel(self.position as u8)
+    }
+
+    // Index the engine knows the piece's point by, `BAR` on the bar.
+    fn board_index(&self) -> game::BoardIndex {
+        self.label().index()
+    }
+
+    fn id(&self) -> PieceId {
+        PieceId {
+            point: self.position,
+            row: self.row,
+            color: self.color,
+        }
+    }
+
+    fn base_material(&self, game_resources: &GameResources) -> Handle<StandardMaterial> {
+        match self.color {
+            game::Color::White => game_resources.white_material.clone(),
+            game::Color::Black => game_resources.black_material.clone(),
+        }
+    }
+
+    fn army_material(&self, game_resources: &GameResources) -> Handle<StandardMaterial> {
+        match self.color {
+            game::Color::White => game_resources.white_army_material.clone(),
+            game::Color::Black => game_resources.black_army_material.clone(),
+        }
+    }
+
+    // A piece can be in several states at once, the material shown goes by
+    // dimmed, candidate, chosen, highlighted, then the plain color. The chosen
+    // and highlighted materials both pulse, so a chosen piece keeps blinking.
+    fn material(&self, game_resources: &GameResources) -> Handle<StandardMaterial> {
+        if self.dimmed {
+            return game_resources.dimmed_material.clone();
+        }
+
+        if self.candidate {
+            return game_resources.candidate_material.clone();
+        }
+
+        if self.chosen {
+            return game_resources.chosen_material.clone();
+        }
+
+        if self.highlighted {
+            return game_resources.highlighted_material.clone();
+        }
+
+        self.base_material(game_resources)
+    }
+
+    fn transform(&self, scene_scale: SceneScale) -> Transform {
+        let board = scene_scale.board;
+
+        let rotation =
+            std::f32::consts::PI + checker_rotation(self.position, self.row, CHECKER_ROTATION_SEED);
+
+        Transform::from_translation(board.scene_position(self.board_coordinates(scene_scale), 0.0))
+            .with_scale(Vec3::splat(scene_scale.piece))
+            .with_rotation(board.relative_rotation() * Quat::from_rotation_y(rotation))
+    }
 }
 
 fn spawn_board(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    scene_scale: Res<SceneScale>,
+    board_asset: Res<BoardAsset>,
+    camera_mode: Res<CameraMode>,
+    lighting: Res<LightingSettings>,
     mut start_game_event_writer: EventWriter<StartGameEvent>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
 ) {
+    let (projection, transform) = camera_mode.camera();
     commands
         .spawn((Camera3dBundle {
-            transform: Transform::from_xyz(-1.7, 1.7, 0.0)
-                .looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
+            projection,
+            transform,
             ..default()
         },))
-        .insert(PickingCameraBundle::default());
+        .insert(PickingCameraBundle::default())
+        .insert(MainCamera);
 
     commands.spawn(DirectionalLightBundle {
         directional_light: DirectionalLight {
@@ -135,18 +718,91 @@ fn spawn_board(
 
     commands
         .spawn(SceneBundle {
-            scene: asset_server.load("models/board.glb#Scene0"),
+            scene: asset_server.load(board_asset.scene()),
             // material: materials.add(Color::CRIMSON.into()),
-            transform: Transform::from_xyz(0.0, 0.05, 0.0)
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2))
-                .with_scale(Vec3::splat(0.6)),
+            transform: board_asset.transform(*scene_scale),
+            ..default()
+        })
+        .insert(Name::new("Board"))
+        .insert(BoardScene);
+
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(shape::Box::new(0.015, 0.002, 1.2).into()),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(1.0, 0.8, 0.3, 0.6),
+                emissive: Color::rgb(1.0, 0.7, 0.2),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            transform: on_roll_glow_transform(game::Color::White, *scene_scale),
+            visibility: Visibility::Hidden,
             ..default()
         })
-        .insert(Name::new("Board"));
+        .insert(Name::new("OnRollGlow"))
+        .insert(OnRollGlow);
+
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(shape::Cube::new(CUBE_SIZE).into()),
+            material: materials.add(Color::rgb(0.95, 0.93, 0.85).into()),
+            transform: cube_transform(&cube::DoublingCube::default(), *scene_scale),
+            ..default()
+        })
+        .insert(Name::new("DoublingCube"))
+        .insert(CubeModel);
+
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 22.0,
+                    color: Color::BLACK,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            }),
+        )
+        .insert(Name::new("CubeValue"))
+        .insert(LabelCubeValue);
+
+    let mut bar_cue_timer = Timer::from_seconds(BAR_CUE_SECONDS, TimerMode::Once);
+    bar_cue_timer.tick(bar_cue_timer.duration());
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(shape::Box::new(0.8, 0.002, 0.06).into()),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(1.0, 0.2, 0.2, 0.5),
+                emissive: Color::rgb(1.0, 0.1, 0.1),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            transform: Transform::from_translation(
+                scene_scale.board.scene_position([0.0, 0.0], 0.06),
+            )
+            .with_rotation(scene_scale.board.relative_rotation())
+            .with_scale(Vec3::splat(scene_scale.board.ratio())),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(Name::new("BarCue"))
+        .insert(BarCue {
+            timer: bar_cue_timer,
+        });
 
     // Spawn lights
     commands
         .spawn(SpotLightBundle {
+            spot_light: SpotLight {
+                intensity: lighting.spot_intensity,
+                ..default()
+            },
             transform: Transform::from_xyz(0.0, 1.0, 3.0),
             ..Default::default()
         })
@@ -155,28 +811,17 @@ fn spawn_board(
     start_game_event_writer.send(StartGameEvent);
 }
 
-pub(crate) fn spawn_piece(commands: &mut Commands, piece: Piece, game_resources: GameResources) {
-    let [x, y] = piece.board_coordinates();
-
-    let transform = Transform::from_xyz(y, 0.0, x)
-        .with_scale(Vec3::splat(0.03))
-        .with_rotation(Quat::from_rotation_y(std::f32::consts::PI));
-
-    let mut material = match piece.color {
-        game::Color::White => game_resources.white_material.clone(),
-        game::Color::Black => game_resources.black_material.clone(),
-    };
-
-    if piece.highlighted {
-        material = game_resources.highlighted_material.clone();
-    }
-
-    if piece.candidate {
-        material = game_resources.candidate_material.clone();
-    }
+pub(crate) fn spawn_piece(
+    commands: &mut Commands,
+    piece: Piece,
+    game_resources: GameResources,
+    scene_scale: SceneScale,
+) -> Entity {
+    let transform = piece.transform(scene_scale);
+    let material = piece.material(&game_resources);
 
     let bundle = PbrBundle {
-        mesh: game_resources.checkers_model,
+        mesh: game_resources.checker_mesh(&piece),
         material,
         transform,
         ..Default::default()
@@ -186,48 +831,617 @@ pub(crate) fn spawn_piece(commands: &mut Commands, piece: Piece, game_resources:
 
     cmd.insert(Name::new("Piece")).insert(piece);
 
+    if !piece.candidate && !piece.dimmed {
+        cmd.insert(piece.id());
+    }
+
     if piece.highlighted || piece.candidate {
         cmd.insert(PickableBundle::default());
     }
+
+    cmd.id()
+}
+
+// Count shown over a stack too tall to draw every checker.
+#[derive(Component)]
+pub(crate) struct StackLabel {
+    position: usize,
+    color: game::Color,
+}
+
+// Checker stacks as (position, color, checkers), both bars at position 25.
+fn board_stacks(game: &game::Game) -> Vec<(usize, game::Color, usize)> {
+    let mut stacks = vec![];
+
+    for (position, piece) in game.board.points.iter().enumerate() {
+        if let Some(color) = game::Color::from_sign(*piece) {
+            stacks.push((position + 1, color, piece.unsigned_abs() as usize));
+        }
+    }
+
+    for color in [game::Color::White, game::Color::Black] {
+        let num_pieces = game.board.bar[game.board.bar_index(color)] as usize;
+        stacks.push((game::BAR + 1, color, num_pieces));
+    }
+
+    stacks
+}
+
+// Pieces showing the checkers on the board and on the bar.
+fn board_pieces(game: &game::Game) -> Vec<Piece> {
+    board_stacks(game)
+        .into_iter()
+        .flat_map(|(position, color, num_pieces)| {
+            (1..=game::stack_rows(num_pieces)).map(move |row| Piece {
+                position,
+                row,
+                color,
+                highlighted: false,
+                candidate: false,
+                dimmed: false,
+                chosen: false,
+            })
+        })
+        .collect()
 }
 
 pub(crate) fn spawn_pieces(
     mut commands: Commands,
     game: ResMut<game::Game>,
     game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
 ) {
-    for (position, piece) in game.board.points.iter().enumerate() {
-        let mut color = game::Color::White;
-        if *piece < 0 {
-            color = game::Color::Black;
-        }
-
-        let position = position + 1_usize;
-        let num_pieces = piece.unsigned_abs() as usize;
-
-        for row in 1..=num_pieces {
-            spawn_piece(
-                &mut commands,
-                Piece {
-                    position,
-                    row,
-                    color,
-                    highlighted: false,
-                    candidate: false,
-                    chosen: false,
-                },
-                game_resources.clone(),
-            );
+    for piece in board_pieces(&game) {
+        spawn_piece(&mut commands, piece, game_resources.clone(), *scene_scale);
+    }
+}
+
+// Bring the pieces in line with the board after a move. Checkers that stay in
+// place keep their entity, the rest are despawned or spawned.
+pub(crate) fn redraw_pieces(
+    commands: &mut Commands,
+    pieces_query: &mut Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
+    game: &game::Game,
+    game_resources: &GameResources,
+    scene_scale: SceneScale,
+) {
+    let mut missing = board_pieces(game)
+        .into_iter()
+        .map(|piece| (piece.id(), piece))
+        .collect::<HashMap<_, _>>();
+
+    for (entity, mut piece, mut material) in pieces_query.iter_mut() {
+        let kept = !piece.candidate && !piece.dimmed && missing.remove(&piece.id()).is_some();
+        if !kept {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        if piece.highlighted || piece.chosen {
+            piece.highlighted = false;
+            piece.chosen = false;
+            *material = piece.material(game_resources);
+        }
+        // checkers under a highlighted one were pickable too
+        commands.entity(entity).remove::<PickableBundle>();
+    }
+
+    for piece in missing.into_values() {
+        spawn_piece(commands, piece, game_resources.clone(), scene_scale);
+    }
+}
+
+// Keeps one label per stack taller than `MAX_STACK_ROWS`, including the
+// starting position.
+pub(crate) fn update_stack_labels(
+    mut commands: Commands,
+    game: Res<game::Game>,
+    asset_server: Res<AssetServer>,
+    mut label_query: Query<(Entity, &StackLabel, &mut Text)>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+
+    let mut tall_stacks = board_stacks(&game)
+        .into_iter()
+        .filter(|(_, _, num_pieces)| *num_pieces > game::MAX_STACK_ROWS)
+        .map(|(position, color, num_pieces)| ((position, color), num_pieces))
+        .collect::<HashMap<_, _>>();
+
+    for (entity, label, mut text) in label_query.iter_mut() {
+        match tall_stacks.remove(&(label.position, label.color)) {
+            Some(num_pieces) => text.sections[0].value = num_pieces.to_string(),
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    for ((position, color), num_pieces) in tall_stacks {
+        commands
+            .spawn(
+                TextBundle::from_section(
+                    num_pieces.to_string(),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 24.0,
+                        color: Color::rgb(0.9, 0.8, 0.2),
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                }),
+            )
+            .insert(StackLabel { position, color })
+            .insert(Name::new("Stack Label"));
+    }
+}
+
+// Places the labels on screen just above the top drawn checker of their stack.
+pub(crate) fn position_stack_labels(
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    scene_scale: Res<SceneScale>,
+    mut label_query: Query<(&StackLabel, &mut Style)>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+
+    for (label, mut style) in label_query.iter_mut() {
+        let above_top = Piece {
+            position: label.position,
+            row: game::MAX_STACK_ROWS + 1,
+            color: label.color,
+            highlighted: false,
+            candidate: false,
+            dimmed: false,
+            chosen: false,
+        };
+        let translation = above_top.transform(*scene_scale).translation;
+
+        // viewport coordinates start at the bottom left
+        if let Some(viewport) = camera.world_to_viewport(camera_transform, translation) {
+            style.position = UiRect {
+                left: Val::Px(viewport.x),
+                bottom: Val::Px(viewport.y),
+                ..default()
+            };
+        }
+    }
+}
+
+pub(crate) fn handle_board_asset_change(
+    board_asset: Res<BoardAsset>,
+    scene_scale: Res<SceneScale>,
+    asset_server: Res<AssetServer>,
+    mut board_query: Query<(&mut Handle<Scene>, &mut Transform), With<BoardScene>>,
+) {
+    if !board_asset.is_changed() || board_asset.is_added() {
+        return;
+    }
+
+    for (mut scene, mut transform) in board_query.iter_mut() {
+        *scene = asset_server.load(board_asset.scene());
+        *transform = board_asset.transform(*scene_scale);
+    }
+}
+
+pub(crate) fn handle_scene_scale_change(
+    scene_scale: Res<SceneScale>,
+    board_asset: Res<BoardAsset>,
+    mut board_query: Query<&mut Transform, (With<BoardScene>, Without<Piece>)>,
+    mut pieces_query: Query<(&mut Transform, &Piece)>,
+) {
+    if !scene_scale.is_changed() || scene_scale.is_added() {
+        return;
+    }
+
+    for mut transform in board_query.iter_mut() {
+        *transform = board_asset.transform(*scene_scale);
+    }
+
+    for (mut transform, piece) in pieces_query.iter_mut() {
+        *transform = piece.transform(*scene_scale);
+    }
+}
+
+pub(crate) fn update_on_roll_glow(
+    mut turn_start_event_reader: EventReader<TurnStartEvent>,
+    mut highlight_event_reader: EventReader<HighlightPickablePiecesEvent>,
+    mut game_over_event_reader: EventReader<GameOverEvent>,
+    mut glow_query: Query<(&mut Transform, &mut Visibility), With<OnRollGlow>>,
+    game: Res<game::Game>,
+    scene_scale: Res<SceneScale>,
+) {
+    // a turn also begins right after the opening roll, without a TurnStartEvent
+    let turn_changed =
+        turn_start_event_reader.iter().count() + highlight_event_reader.iter().count() > 0;
+    let game_over = game_over_event_reader.iter().count() > 0;
+
+    if !turn_changed && !game_over && !scene_scale.is_changed() {
+        return;
+    }
+
+    for (mut transform, mut visibility) in glow_query.iter_mut() {
+        *transform = on_roll_glow_transform(game.player, *scene_scale);
+        *visibility = if game.opening_roll || game.is_over() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+// Moves the cube to its owner's side and hides it when the cube isn't used.
+pub(crate) fn update_cube_model(
+    game: Res<game::Game>,
+    scene_scale: Res<SceneScale>,
+    mut cube_query: Query<(&mut Transform, &mut Visibility), With<CubeModel>>,
+) {
+    if !game.is_changed() && !scene_scale.is_changed() {
+        return;
+    }
+
+    for (mut transform, mut visibility) in cube_query.iter_mut() {
+        *transform = cube_transform(&game.cube, *scene_scale);
+        *visibility = if game.cube_enabled() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// Keeps the value label over the cube, shown only along with it.
+pub(crate) fn position_cube_label(
+    game: Res<game::Game>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    cube_query: Query<(&GlobalTransform, &Visibility), With<CubeModel>>,
+    mut label_query: Query<
+        (&mut Text, &mut Style, &mut Visibility),
+        (With<LabelCubeValue>, Without<CubeModel>),
+    >,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Ok((cube_transform, cube_visibility)) = cube_query.get_single() else {
+        return;
+    };
+
+    for (mut text, mut style, mut visibility) in label_query.iter_mut() {
+        *visibility = *cube_visibility;
+        text.sections[0].value = game.cube.value.to_string();
+
+        // viewport coordinates start at the bottom left
+        let top = cube_transform.translation() + Vec3::Y * CUBE_SIZE;
+        if let Some(viewport) = camera.world_to_viewport(camera_transform, top) {
+            style.position = UiRect {
+                left: Val::Px(viewport.x),
+                bottom: Val::Px(viewport.y),
+                ..default()
+            };
+        }
+    }
+}
+
+pub(crate) fn update_bar_cue(
+    mut turn_start_event_reader: EventReader<TurnStartEvent>,
+    mut bar_cue_query: Query<(&mut BarCue, &mut Visibility)>,
+    game: Res<game::Game>,
+    time: Res<Time>,
+    playback_speed: Res<PlaybackSpeed>,
+) {
+    let turn_started = turn_start_event_reader.iter().count() > 0;
+
+    for (mut bar_cue, mut visibility) in bar_cue_query.iter_mut() {
+        if turn_started && needs_bar_cue(&game) {
+            bar_cue.timer.reset();
         }
+
+        bar_cue.timer.tick(time.delta().mul_f32(playback_speed.0));
+
+        let flash_on = (bar_cue.timer.elapsed_secs() * BAR_CUE_FLASHES_PER_SECOND).fract() < 0.5;
+        *visibility = if !bar_cue.timer.finished() && flash_on {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub(crate) fn pulse_highlighted_pieces(
+    time: Res<Time>,
+    highlight_pulse: Res<HighlightPulse>,
+    game_resources: Res<GameResources>,
+    pieces_query: Query<&Piece>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !pieces_query.iter().any(|piece| piece.highlighted) && !highlight_pulse.is_changed() {
+        return;
+    }
+
+    let intensity = highlight_pulse.intensity(time.elapsed_seconds());
+    if let Some(material) = materials.get_mut(&game_resources.highlighted_material) {
+        material.emissive = Color::rgb(intensity, 0.0, 0.0);
+    }
+    if let Some(material) = materials.get_mut(&game_resources.chosen_material) {
+        material.emissive = Color::rgb(intensity, 0.5 * intensity, 0.0);
+    }
+}
+
+// Swaps checkers between their plain and tinted material as the turn changes.
+// Pieces showing any other material are highlighted, candidates or ghosts and
+// are left alone.
+pub(crate) fn handle_own_army_highlight(
+    own_army_highlight: Res<OwnArmyHighlight>,
+    game: Res<game::Game>,
+    game_resources: Res<GameResources>,
+    mut pieces_query: Query<(&Piece, &mut Handle<StandardMaterial>)>,
+) {
+    for (piece, mut material) in pieces_query.iter_mut() {
+        let base = piece.base_material(&game_resources);
+        let army = piece.army_material(&game_resources);
+        if *material != base && *material != army {
+            continue;
+        }
+
+        let tinted = own_army_highlight.enabled && piece.color == game.player;
+        let wanted = if tinted { army } else { base };
+        if *material != wanted {
+            *material = wanted;
+        }
+    }
+}
+
+pub(crate) fn toggle_own_army_highlight(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut own_army_highlight: ResMut<OwnArmyHighlight>,
+) {
+    if keyboard_input.just_pressed(KeyCode::A) {
+        own_army_highlight.enabled = !own_army_highlight.enabled;
+    }
+}
+
+pub(crate) fn toggle_auto_finish_races(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut auto_finish_races: ResMut<AutoFinishRaces>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F) {
+        auto_finish_races.enabled = !auto_finish_races.enabled;
+        toast_event_writer.send(ToastEvent::new(if auto_finish_races.enabled {
+            "Decided races finish automatically"
+        } else {
+            "Decided races are played out"
+        }));
+    }
+}
+
+// B switches dimming the points a chosen checker can't reach.
+pub(crate) fn toggle_beginner_assist(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut beginner_assist: ResMut<BeginnerAssist>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::B) {
+        beginner_assist.enabled = !beginner_assist.enabled;
+        toast_event_writer.send(ToastEvent::new(if beginner_assist.enabled {
+            "Beginner assist on, unreachable points are dimmed"
+        } else {
+            "Beginner assist off"
+        }));
+    }
+}
+
+// O switches playing forced bear-off turns automatically.
+pub(crate) fn toggle_auto_bear_off(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut auto_bear_off: ResMut<AutoBearOff>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::O) {
+        auto_bear_off.enabled = !auto_bear_off.enabled;
+        toast_event_writer.send(ToastEvent::new(if auto_bear_off.enabled {
+            "Forced bear-offs play automatically"
+        } else {
+            "Forced bear-offs are played by hand"
+        }));
+    }
+}
+
+// G switches clearing candidates on a click away from the chosen checker.
+pub(crate) fn toggle_auto_clear_candidates(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut auto_clear_candidates: ResMut<AutoClearCandidates>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::G) {
+        auto_clear_candidates.enabled = !auto_clear_candidates.enabled;
+        toast_event_writer.send(ToastEvent::new(if auto_clear_candidates.enabled {
+            "Clicking away clears candidate moves"
+        } else {
+            "Candidate moves stay until another checker is picked"
+        }));
+    }
+}
+
+// L switches the low-poly checkers of tall stacks on and off.
+pub(crate) fn toggle_checker_lod(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game_resources: ResMut<GameResources>,
+    mut pieces_query: Query<(&Piece, &mut Handle<Mesh>)>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::L) {
+        return;
+    }
+
+    game_resources.checker_lod.enabled = !game_resources.checker_lod.enabled;
+    for (piece, mut mesh) in pieces_query.iter_mut() {
+        *mesh = game_resources.checker_mesh(piece);
+    }
+
+    toast_event_writer.send(ToastEvent::new(if game_resources.checker_lod.enabled {
+        "Low-poly checkers on tall stacks"
+    } else {
+        "Full detail checkers"
+    }));
+}
+
+pub(crate) fn toggle_highlight_pulse(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut highlight_pulse: ResMut<HighlightPulse>,
+) {
+    if keyboard_input.just_pressed(KeyCode::P) {
+        highlight_pulse.enabled = !highlight_pulse.enabled;
+    }
+}
+
+pub(crate) fn handle_lighting_keys(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut lighting: ResMut<LightingSettings>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    let steps = if keyboard_input.just_pressed(KeyCode::Equals) {
+        1.0
+    } else if keyboard_input.just_pressed(KeyCode::Minus) {
+        -1.0
+    } else {
+        return;
+    };
+
+    lighting.adjust(steps);
+    toast_event_writer.send(ToastEvent::new(format!(
+        "Brightness {:.0}%",
+        lighting.ambient * 100.0
+    )));
+}
+
+pub(crate) fn apply_lighting_settings(
+    lighting: Res<LightingSettings>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut spot_light_query: Query<&mut SpotLight>,
+) {
+    if !lighting.is_changed() {
+        return;
+    }
+
+    ambient_light.brightness = lighting.ambient;
+    for mut spot_light in spot_light_query.iter_mut() {
+        spot_light.intensity = lighting.spot_intensity;
+    }
+}
+
+pub(crate) fn toggle_camera_mode(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut camera_mode: ResMut<CameraMode>,
+) {
+    if keyboard_input.just_pressed(KeyCode::C) {
+        *camera_mode = match *camera_mode {
+            CameraMode::Perspective => CameraMode::TopDown,
+            CameraMode::TopDown => CameraMode::Perspective,
+        };
+    }
+}
+
+pub(crate) fn handle_camera_mode_change(
+    camera_mode: Res<CameraMode>,
+    mut camera_query: Query<(&mut Projection, &mut Transform), With<MainCamera>>,
+) {
+    if !camera_mode.is_changed() || camera_mode.is_added() {
+        return;
+    }
+
+    for (mut projection, mut transform) in camera_query.iter_mut() {
+        (*projection, *transform) = camera_mode.camera();
     }
 }
 
 fn main() {
+    let skip_opening_roll = SkipOpeningRoll::default();
+
+    // `--scenario <file>` plays a puzzle from its JSON script
+    let active_scenario = ActiveScenario {
+        scenario: std::env::args()
+            .skip_while(|arg| arg != "--scenario")
+            .nth(1)
+            .and_then(|path| {
+                std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| scenario::Scenario::from_json(&json))
+                    .map_err(|e| eprintln!("Couldn't load {}: {}", path, e))
+                    .ok()
+            }),
+    };
+    let game = match &active_scenario.scenario {
+        Some(scenario) => scenario.start(),
+        None => skip_opening_roll.new_game(),
+    };
+    let title = window_title(&game);
+    let lighting = LightingSettings::default();
+
+    // a `.mat` transcript given on the command line opens in replay mode
+    let replay_state = std::env::args()
+        .nth(1)
+        .filter(|arg| arg.ends_with(".mat"))
+        .map(|path| {
+            ReplayState::load(Path::new(&path)).unwrap_or_else(|e| {
+                eprintln!("Couldn't load {}: {}", path, e);
+                ReplayState::default()
+            })
+        })
+        .unwrap_or_default();
+
+    // `--seed <n>` plays the opening roll from the seed
+    let tournament_opening = TournamentOpening {
+        seed: std::env::args()
+            .skip_while(|arg| arg != "--seed")
+            .nth(1)
+            .and_then(|seed| seed.parse().ok()),
+    };
+
+    // `--board <file>` swaps in a custom board model, placed with
+    // `--board-scale` and `--board-y-offset`
+    let args = std::env::args().collect::<Vec<_>>();
+    let board_asset = BoardAsset::from_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        BoardAsset::default()
+    });
+
+    // `--chouette <players>` plays a chouette, rotating the box after each game
+    let active_chouette = chouette::ActiveChouette {
+        chouette: std::env::args()
+            .skip_while(|arg| arg != "--chouette")
+            .nth(1)
+            .and_then(|players| {
+                players
+                    .parse()
+                    .map_err(|_| format!("needs a player count, got {:?}", players))
+                    .and_then(chouette::Chouette::with_players)
+                    .map_err(|e| eprintln!("Couldn't start the chouette: {}", e))
+                    .ok()
+            }),
+    };
+
+    // `--clock <min>+<inc>` plays with a chess clock and Fischer increment
+    let chess_clock = std::env::args()
+        .skip_while(|arg| arg != "--clock")
+        .nth(1)
+        .map(|time_control| {
+            ChessClock::from_time_control(&time_control).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                ChessClock::default()
+            })
+        })
+        .unwrap_or_default();
+
     App::new()
         .insert_resource(AmbientLight {
             color: Color::WHITE,
-            brightness: 1.0 / 5.0f32,
+            brightness: lighting.ambient,
         })
+        .insert_resource(lighting)
         .add_plugin(DicePlugin)
         .insert_resource(DicePluginSettings {
             render_size: (640, 640),
@@ -237,7 +1451,39 @@ fn main() {
             ..default()
         })
         .insert_resource(DirectionalLightShadowMap { size: 4096 })
-        .insert_resource(game::Game::new())
+        .insert_resource(game)
+        .insert_resource(skip_opening_roll)
+        .register_type::<game::Game>()
+        .register_type::<game::Board>()
+        .init_resource::<SceneScale>()
+        .insert_resource(board_asset)
+        .insert_resource(chess_clock)
+        .init_resource::<BeginnerAssist>()
+        .init_resource::<AutoBearOff>()
+        .init_resource::<AutoFinishRaces>()
+        .init_resource::<AutoClearCandidates>()
+        .init_resource::<ConfirmTurn>()
+        .init_resource::<EditMode>()
+        .init_resource::<HighlightPulse>()
+        .init_resource::<OwnArmyHighlight>()
+        .init_resource::<CameraMode>()
+        .init_resource::<FocusedButton>()
+        .init_resource::<SwapDice>()
+        .init_resource::<GhostPreview>()
+        .init_resource::<UndoHistory>()
+        .init_resource::<PositionClipboard>()
+        .init_resource::<PlaybackSpeed>()
+        .init_resource::<PassWatchdog>()
+        .init_resource::<DiceRenderMode>()
+        .init_resource::<DieDisplay>()
+        .init_resource::<InputSettings>()
+        .init_resource::<ai::AiEvaluator>()
+        .init_resource::<ai::PipLeaderTraining>()
+        .insert_resource(replay_state)
+        .insert_resource(tournament_opening)
+        .insert_resource(active_scenario)
+        .insert_resource(active_chouette)
+        .insert_resource(SessionStats::load(Path::new(STATS_FILE)).unwrap_or_default())
         .add_event::<HighlightPickablePiecesEvent>()
         .add_event::<DisplayPossibleMovesEvent>()
         .add_event::<MovePieceEvent>()
@@ -245,7 +1491,13 @@ fn main() {
         .add_event::<TurnStartEvent>()
         .add_event::<GameOverEvent>()
         .add_event::<StartGameEvent>()
-        .add_plugins(DefaultPlugins)
+        .add_event::<InstantDiceRollEvent>()
+        .add_event::<RestartScenarioEvent>()
+        .add_event::<ToastEvent>()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window { title, ..default() }),
+            ..default()
+        }))
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugin(WorldInspectorPlugin::new())
         .add_plugin(AudioPlugin)
@@ -254,17 +1506,827 @@ fn main() {
         .add_startup_system(spawn_board)
         .add_startup_system(spawn_pieces)
         .add_startup_system(setup_ui)
-        .add_system(ui_logic)
-        .add_system(event_dice_roll_result)
-        .add_system(event_dice_rolls_complete)
-        .add_system(handle_hightlight_choosable_pieces)
-        .add_system(handle_piece_picking.in_base_set(CoreSet::PostUpdate))
-        .add_system(handle_display_possible_moves)
-        .add_system(handle_move_piece_event)
-        .add_system(handle_move_piece_end_event)
-        .add_system(handle_dice_roll_start_event)
-        .add_system(handle_turn_start_event)
-        .add_system(handle_game_over_event)
+        .add_startup_system(setup_replay_slider)
+        .add_startup_system(setup_confirm_button)
+        .add_startup_system(setup_toasts)
+        .add_startup_system(set_window_icon)
+        .configure_sets(TurnSet::chain())
+        .add_system(ui_logic.in_set(TurnSet::Input))
+        .add_system(keyboard_navigation.in_set(TurnSet::Input))
+        .add_system(handle_bear_off_all_button.in_set(TurnSet::Input))
+        .add_system(handle_match_length_buttons.in_set(TurnSet::Input))
+        .add_system(handle_board_edit.in_set(TurnSet::Input))
+        .add_system(handle_replay_slider.in_set(TurnSet::Input))
+        .add_system(handle_play_from_here.in_set(TurnSet::Input))
+        .add_system(handle_confirm_turn_button.in_set(TurnSet::Input))
+        .add_system(
+            apply_replay_index
+                .in_set(TurnSet::Input)
+                .after(handle_replay_slider),
+        )
+        .add_system(record_base_ui_sizes)
+        .add_system(scale_ui_to_window)
+        .add_system(toggle_swap_dice)
+        .add_system(toggle_edit_mode)
+        .add_system(toggle_dice_render_mode)
+        .add_system(toggle_die_display)
+        .add_system(update_die_faces.in_set(TurnSet::Display))
+        .add_system(change_playback_speed)
+        .add_system(change_double_click_time)
+        .add_system(
+            event_dice_roll_result
+                .in_set(TurnSet::Dice)
+                .before(event_dice_rolls_complete),
+        )
+        .add_system(event_dice_rolls_complete.in_set(TurnSet::Dice))
+        .add_system(play_seeded_opening.in_set(TurnSet::Dice))
+        .add_system(play_scenario_rolls.in_set(TurnSet::Dice))
+        .add_system(restart_scenario.in_set(TurnSet::Input))
+        .add_system(
+            handle_pass_timer
+                .in_set(TurnSet::Dice)
+                .after(event_dice_rolls_complete),
+        )
+        .add_system(
+            undo_move
+                .in_set(TurnSet::Move)
+                .before(handle_move_piece_event),
+        )
+        .add_system(handle_move_piece_event.in_set(TurnSet::Move))
+        .add_system(
+            handle_move_piece_end_event
+                .in_set(TurnSet::Move)
+                .after(handle_move_piece_event),
+        )
+        .add_system(
+            turn_watchdog
+                .in_set(TurnSet::Move)
+                .after(handle_move_piece_end_event),
+        )
+        // pieces respawned by a move have to exist before they are highlighted
+        .add_system(
+            apply_system_buffers
+                .after(TurnSet::Move)
+                .before(TurnSet::Highlight),
+        )
+        .add_system(handle_hightlight_choosable_pieces.in_set(TurnSet::Highlight))
+        .add_system(
+            handle_piece_picking
+                .run_if(not_editing)
+                .in_base_set(CoreSet::PostUpdate),
+        )
+        .add_system(place_clicked_checker.in_base_set(CoreSet::PostUpdate))
+        .add_system(clear_candidates_on_deselect.in_base_set(CoreSet::PostUpdate))
+        .add_system(handle_ghost_preview.in_base_set(CoreSet::PostUpdate))
+        .add_system(
+            update_pip_distance_label
+                .in_base_set(CoreSet::PostUpdate)
+                .after(handle_ghost_preview),
+        )
+        .add_system(handle_display_possible_moves.in_set(TurnSet::Display))
+        .add_system(update_legal_plays_label.in_set(TurnSet::Display))
+        .add_system(update_status_bar.in_set(TurnSet::Display))
+        .add_system(update_home_board_pips.in_set(TurnSet::Display))
+        .add_system(update_point_tooltip.in_set(TurnSet::Display))
+        .add_system(update_pass_countdown_label.in_set(TurnSet::Display))
+        .add_system(clear_undo_history.in_set(TurnSet::TurnEnd))
+        .add_system(clear_staged_turn.in_set(TurnSet::TurnEnd))
+        .add_system(copy_position_to_clipboard)
+        .add_system(copy_replay_transcript)
+        .add_system(ai::show_hint)
+        .add_system(ai::toggle_pip_leader_training)
+        // hidden before an instant opening tie shows the button again
+        .add_system(
+            handle_dice_roll_start_event
+                .in_set(TurnSet::Dice)
+                .before(event_dice_rolls_complete),
+        )
+        .add_system(handle_turn_start_event.in_set(TurnSet::TurnEnd))
+        .add_system(
+            auto_finish_race
+                .in_set(TurnSet::TurnEnd)
+                .after(handle_turn_start_event)
+                .before(record_session_stats),
+        )
+        .add_system(
+            record_session_stats
+                .in_set(TurnSet::TurnEnd)
+                .before(handle_game_over_event),
+        )
+        .add_system(handle_game_over_event.in_set(TurnSet::TurnEnd))
         .add_system(handle_start_game_event)
+        .add_system(handle_scene_scale_change)
+        .add_system(handle_board_asset_change)
+        .add_system(update_stack_labels.in_set(TurnSet::Display))
+        .add_system(position_stack_labels.after(update_stack_labels))
+        .add_system(pause_chess_clock_on_focus_loss.before(tick_chess_clock))
+        .add_system(tick_chess_clock)
+        .add_system(pulse_highlighted_pieces)
+        .add_system(toggle_highlight_pulse)
+        .add_system(toggle_auto_finish_races)
+        .add_system(toggle_auto_clear_candidates)
+        .add_system(toggle_beginner_assist)
+        .add_system(toggle_auto_bear_off)
+        .add_system(toggle_confirm_turn)
+        .add_system(toggle_checker_lod)
+        .add_system(handle_own_army_highlight.in_set(TurnSet::Display))
+        .add_system(toggle_own_army_highlight)
+        .add_system(handle_lighting_keys)
+        .add_system(apply_lighting_settings.after(handle_lighting_keys))
+        .add_system(toggle_camera_mode)
+        .add_system(handle_camera_mode_change)
+        .add_system(handle_toast_event)
+        .add_system(update_toasts)
+        .add_system(update_window_title)
+        .add_system(update_on_roll_glow)
+        .add_system(update_bar_cue)
+        .add_system(update_cube_model)
+        .add_system(position_cube_label.after(update_cube_model))
+        .add_system(chouette::rotate_chouette)
         .run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn white_checker(position: usize, row: usize) -> Piece {
+        Piece {
+            row,
+            position,
+            color: game::Color::White,
+            highlighted: false,
+            candidate: false,
+            dimmed: false,
+            chosen: false,
+        }
+    }
+
+    // Distance from the first checker of a stack to the one on `row`.
+    fn stack_offset(row: usize, scene_scale: SceneScale) -> f32 {
+        let first = white_checker(19, 1).transform(scene_scale).translation;
+        let stacked = white_checker(19, row).transform(scene_scale).translation;
+        first.distance(stacked)
+    }
+
+    #[test]
+    fn stacks_scale_with_the_piece_scale() {
+        let scene_scale = SceneScale::default();
+        let doubled = SceneScale {
+            piece: scene_scale.piece * 2.0,
+            ..scene_scale
+        };
+
+        let offset = stack_offset(3, scene_scale);
+        assert!(offset > 0.0);
+        assert!((stack_offset(3, doubled) - 2.0 * offset).abs() < 1e-5);
+        assert_eq!(
+            white_checker(19, 3).transform(doubled).scale,
+            Vec3::splat(doubled.piece)
+        );
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut input = app.world.resource_mut::<Input<KeyCode>>();
+        input.clear();
+        input.press(key);
+        app.update();
+        app.world.resource_mut::<Input<KeyCode>>().release(key);
+    }
+
+    #[test]
+    fn c_switches_the_camera_to_top_down_and_back() {
+        let mut app = test_support::test_app();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<CameraMode>()
+            .add_system(toggle_camera_mode)
+            .add_system(handle_camera_mode_change.after(toggle_camera_mode));
+
+        let (projection, transform) = CameraMode::Perspective.camera();
+        let camera = app.world.spawn((projection, transform, MainCamera)).id();
+        app.update();
+
+        press(&mut app, KeyCode::C);
+        assert_eq!(*app.world.resource::<CameraMode>(), CameraMode::TopDown);
+        assert!(matches!(
+            app.world.get::<Projection>(camera).unwrap(),
+            Projection::Orthographic(_)
+        ));
+        let transform = *app.world.get::<Transform>(camera).unwrap();
+        assert_eq!(transform.translation, Vec3::new(0.0, 3.0, 0.0));
+        assert!(transform.forward().abs_diff_eq(Vec3::NEG_Y, 1e-5));
+
+        press(&mut app, KeyCode::C);
+        assert_eq!(*app.world.resource::<CameraMode>(), CameraMode::Perspective);
+        assert!(matches!(
+            app.world.get::<Projection>(camera).unwrap(),
+            Projection::Perspective(_)
+        ));
+        assert_eq!(
+            *app.world.get::<Transform>(camera).unwrap(),
+            CameraMode::Perspective.camera().1
+        );
+    }
+
+    #[test]
+    fn on_roll_glow_switches_sides_with_the_turn() {
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+
+        let mut app = test_support::test_app();
+        app.add_event::<TurnStartEvent>()
+            .add_event::<HighlightPickablePiecesEvent>()
+            .add_event::<GameOverEvent>()
+            .insert_resource(game)
+            .add_system(update_on_roll_glow);
+        let glow = app
+            .world
+            .spawn((Transform::default(), Visibility::Hidden, OnRollGlow))
+            .id();
+
+        let glow_after_turn_start = |app: &mut App| {
+            let player = app.world.resource::<game::Game>().player;
+            app.world.send_event(TurnStartEvent { player });
+            app.update();
+            assert_eq!(
+                *app.world.get::<Visibility>(glow).unwrap(),
+                Visibility::Inherited
+            );
+            *app.world.get::<Transform>(glow).unwrap()
+        };
+
+        let white = glow_after_turn_start(&mut app);
+        app.world.resource_mut::<game::Game>().switch_turn();
+        let black = glow_after_turn_start(&mut app);
+
+        let scene_scale = SceneScale::default();
+        assert_eq!(
+            white,
+            on_roll_glow_transform(game::Color::White, scene_scale)
+        );
+        assert_eq!(
+            black,
+            on_roll_glow_transform(game::Color::Black, scene_scale)
+        );
+        assert_ne!(white.translation, black.translation);
+    }
+
+    #[test]
+    fn highlight_pulse_varies_within_bounds() {
+        let mut app = test_support::test_app();
+        app.init_resource::<HighlightPulse>()
+            .add_system(pulse_highlighted_pieces);
+        app.world.spawn(Piece {
+            highlighted: true,
+            ..white_checker(1, 2)
+        });
+
+        let highlighted_material = app
+            .world
+            .resource::<GameResources>()
+            .highlighted_material
+            .clone();
+        let mut glow = vec![];
+        for step in 0..5 {
+            let delta = Duration::from_millis(if step == 0 { 0 } else { 125 });
+            test_support::update_after(&mut app, delta);
+            let materials = app.world.resource::<Assets<StandardMaterial>>();
+            glow.push(materials.get(&highlighted_material).unwrap().emissive.r());
+        }
+
+        assert!(glow.iter().all(|intensity| (0.0..=1.0).contains(intensity)));
+        assert!((glow[0] - 0.5).abs() < 1e-3);
+        assert!((glow[2] - 1.0).abs() < 1e-3);
+        assert!((glow[4] - 0.5).abs() < 1e-3);
+
+        let steady = HighlightPulse {
+            enabled: false,
+            ..default()
+        };
+        assert_eq!(steady.intensity(0.3), 1.0);
+    }
+
+    // Whether the bar cue shows right after a turn starts for `player`.
+    fn bar_cue_shown(player: game::Color, bar: [i32; 2]) -> bool {
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+        game.player = player;
+        game.board.bar = bar;
+
+        let mut app = test_support::test_app();
+        app.add_event::<TurnStartEvent>()
+            .init_resource::<PlaybackSpeed>()
+            .insert_resource(game)
+            .add_system(update_bar_cue);
+        let mut timer = Timer::from_seconds(BAR_CUE_SECONDS, TimerMode::Once);
+        timer.tick(timer.duration());
+        let bar_cue = app.world.spawn((BarCue { timer }, Visibility::Hidden)).id();
+
+        app.world.send_event(TurnStartEvent { player });
+        app.update();
+        *app.world.get::<Visibility>(bar_cue).unwrap() == Visibility::Inherited
+    }
+
+    #[test]
+    fn bar_cue_only_for_the_player_on_the_bar() {
+        assert!(bar_cue_shown(game::Color::White, [1, 0]));
+        assert!(bar_cue_shown(game::Color::Black, [0, 2]));
+        assert!(!bar_cue_shown(game::Color::Black, [1, 0]));
+        assert!(!bar_cue_shown(game::Color::White, [0, 0]));
+    }
+
+    #[test]
+    fn bar_cue_flashes_faster_at_double_speed() {
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+        game.board.bar = [1, 0];
+
+        let mut app = test_support::test_app();
+        app.add_event::<TurnStartEvent>()
+            .insert_resource(PlaybackSpeed(2.0))
+            .insert_resource(game)
+            .add_system(update_bar_cue);
+        let bar_cue = app
+            .world
+            .spawn((
+                BarCue {
+                    timer: Timer::from_seconds(BAR_CUE_SECONDS, TimerMode::Once),
+                },
+                Visibility::Hidden,
+            ))
+            .id();
+        test_support::update_after(&mut app, Duration::ZERO);
+
+        let half = Duration::from_secs_f32(BAR_CUE_SECONDS / 2.0);
+        test_support::update_after(&mut app, half - Duration::from_millis(100));
+        assert!(!app.world.get::<BarCue>(bar_cue).unwrap().timer.finished());
+        test_support::update_after(&mut app, Duration::from_millis(200));
+        assert!(app.world.get::<BarCue>(bar_cue).unwrap().timer.finished());
+        assert_eq!(
+            *app.world.get::<Visibility>(bar_cue).unwrap(),
+            Visibility::Hidden
+        );
+    }
+
+    #[test]
+    fn checker_rotation_is_stable_per_spot_and_seed() {
+        let rotation = checker_rotation(3, 2, 7);
+        assert_eq!(rotation, checker_rotation(3, 2, 7));
+        assert!(rotation.abs() <= MAX_CHECKER_ROTATION);
+
+        assert_ne!(rotation, checker_rotation(3, 3, 7));
+        assert_ne!(rotation, checker_rotation(4, 2, 7));
+        assert_ne!(rotation, checker_rotation(3, 2, 8));
+    }
+
+    #[test]
+    fn changing_the_board_asset_reloads_its_scene() {
+        let mut app = test_support::test_app();
+        app.init_resource::<BoardAsset>()
+            .add_system(handle_board_asset_change);
+        let board = app
+            .world
+            .spawn((Handle::<Scene>::default(), Transform::default(), BoardScene))
+            .id();
+        app.update();
+
+        {
+            let mut board_asset = app.world.resource_mut::<BoardAsset>();
+            board_asset.scene_path = String::from("custom/table.glb");
+            board_asset.scale = 2.0;
+        }
+        app.update();
+
+        let expected = app
+            .world
+            .resource::<AssetServer>()
+            .load::<Scene, _>("custom/table.glb#Scene0");
+        assert_eq!(*app.world.get::<Handle<Scene>>(board).unwrap(), expected);
+        assert_eq!(
+            app.world.get::<Transform>(board).unwrap().scale,
+            Vec3::splat(1.2)
+        );
+    }
+
+    #[test]
+    fn board_asset_comes_from_the_command_line() {
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+
+        let board_asset = BoardAsset::from_args(&args(
+            "backgammon --board custom/table.glb --board-scale 2 --board-y-offset 0.5",
+        ))
+        .unwrap();
+        assert_eq!(board_asset.scene(), "custom/table.glb#Scene0");
+        let scene_scale = SceneScale::default();
+        let transform = board_asset.transform(scene_scale);
+        assert_eq!(transform.scale, Vec3::splat(1.2));
+        assert_eq!(
+            transform.translation.y,
+            BoardAsset::default().transform(scene_scale).translation.y + 0.5
+        );
+
+        let default = BoardAsset::from_args(&args("backgammon --seed 3")).unwrap();
+        assert_eq!(default.scene(), BoardAsset::default().scene());
+        assert!(BoardAsset::from_args(&args("backgammon --board-scale big")).is_err());
+    }
+
+    #[test]
+    fn both_starts_give_a_player_and_dice() {
+        // White rolls both dice straight away
+        let mut game = SkipOpeningRoll::default().new_game();
+        assert_eq!(game.phase(), game::GamePhase::AwaitingRoll);
+        assert_eq!(game.player, game::Color::White);
+        game.dice_rolls = vec![5, 2];
+        assert!(!game
+            .get_possible_moves(game.player, game.dice_rolls.clone())
+            .is_empty());
+
+        // one die each, the higher starts with both
+        let mut game = SkipOpeningRoll { enabled: false }.new_game();
+        assert_eq!(game.phase(), game::GamePhase::OpeningRoll);
+        assert!(!game.resolve_opening_roll(3, 3));
+        assert!(game.resolve_opening_roll(2, 5));
+        assert_eq!(game.player, game::Color::Black);
+        assert_eq!(game.dice_rolls, vec![2, 5]);
+        assert!(!game.opening_roll);
+    }
+
+    #[test]
+    fn reflected_board_edit_redraws_the_pieces() {
+        use bevy::reflect::GetPath;
+
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+
+        let mut app = test_support::test_app();
+        app.add_event::<HighlightPickablePiecesEvent>()
+            .insert_resource(game)
+            .add_startup_system(spawn_pieces)
+            .add_system(handle_board_edit);
+        app.update();
+
+        let pieces_on = |app: &mut App, position: usize| {
+            app.world
+                .query::<&Piece>()
+                .iter(&app.world)
+                .filter(|piece| piece.position == position)
+                .count()
+        };
+        assert_eq!(pieces_on(&mut app, 1), 2);
+
+        {
+            let mut game = app.world.resource_mut::<game::Game>();
+            let reflected: &mut dyn Reflect = game.as_mut();
+            *reflected.path_mut::<i32>("board.points[0]").unwrap() = 5;
+        }
+        app.update();
+        assert_eq!(pieces_on(&mut app, 1), 5);
+        assert_eq!(app.world.query::<&Piece>().iter(&app.world).count(), 33);
+    }
+
+    #[test]
+    fn pieces_and_board_share_the_board_transform() {
+        let scene_scale = SceneScale::default();
+        let first = white_checker(1, 1);
+
+        // the default placement keeps the original layout
+        let [x, y] = first.board_coordinates(scene_scale);
+        assert!((first.transform(scene_scale).translation - Vec3::new(y, 0.0, x)).length() < 1e-6);
+        assert_eq!(
+            BoardAsset::default().transform(scene_scale).rotation,
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2)
+        );
+
+        let mut doubled = scene_scale;
+        doubled.board.scale *= 2.0;
+        assert_eq!(
+            BoardAsset::default().transform(doubled).scale,
+            Vec3::splat(1.2)
+        );
+        for position in [1, 7, 13, 24] {
+            let checker = white_checker(position, 1);
+            let before = checker.transform(scene_scale).translation;
+            let after = checker.transform(doubled).translation;
+            assert!((after - before * 2.0).length() < 1e-5, "{position}");
+        }
+
+        let mut rotated = scene_scale;
+        rotated.board.rotation += std::f32::consts::FRAC_PI_2;
+        let before = first.transform(scene_scale).translation;
+        let after = first.transform(rotated).translation;
+        assert!(
+            (after - Quat::from_rotation_y(std::f32::consts::FRAC_PI_2) * before).length() < 1e-5
+        );
+
+        let mut raised = scene_scale;
+        raised.board.y_offset += 0.1;
+        assert!((first.transform(raised).translation.y - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn own_army_tint_leaves_highlights_alone() {
+        let mut app = test_support::test_app();
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+        app.insert_resource(game)
+            .insert_resource(OwnArmyHighlight { enabled: true })
+            .add_system(handle_own_army_highlight);
+
+        let game_resources = app.world.resource::<GameResources>().clone();
+        for mut piece in board_pieces(&game::Game::new()) {
+            piece.highlighted = piece.color == game::Color::White && piece.position == 1;
+            app.world.spawn((piece, piece.material(&game_resources)));
+        }
+        let candidate = Piece {
+            candidate: true,
+            ..white_checker(5, 1)
+        };
+        app.world
+            .spawn((candidate, candidate.material(&game_resources)));
+        app.update();
+
+        let mut pieces = app.world.query::<(&Piece, &Handle<StandardMaterial>)>();
+        let mut tinted = 0;
+        for (piece, material) in pieces.iter(&app.world) {
+            let expected = if piece.candidate {
+                &game_resources.candidate_material
+            } else if piece.highlighted {
+                &game_resources.highlighted_material
+            } else if piece.color == game::Color::White {
+                tinted += 1;
+                &game_resources.white_army_material
+            } else {
+                &game_resources.black_material
+            };
+            assert_eq!(material, expected);
+        }
+        assert_eq!(tinted, 13);
+
+        // the tint follows the player on roll and goes away when switched off
+        app.world.resource_mut::<game::Game>().switch_turn();
+        app.update();
+        for (piece, material) in pieces.iter(&app.world) {
+            match piece.color {
+                game::Color::White if !piece.candidate && !piece.highlighted => {
+                    assert_eq!(*material, game_resources.white_material)
+                }
+                game::Color::Black => assert_eq!(*material, game_resources.black_army_material),
+                _ => {}
+            }
+        }
+
+        app.world.resource_mut::<OwnArmyHighlight>().enabled = false;
+        app.update();
+        for (piece, material) in pieces.iter(&app.world) {
+            if piece.color == game::Color::Black {
+                assert_eq!(*material, game_resources.black_material);
+            }
+        }
+    }
+
+    #[test]
+    fn tall_stacks_get_a_count_label() {
+        let mut white = [0; 24];
+        white[0] = 7;
+        white[5] = 5;
+        white[11] = 3;
+        let mut black = [0; 24];
+        black[23] = 15;
+        let game = game::Game::from_position(white, black, [0, 0], game::Color::White).unwrap();
+
+        let mut app = test_support::test_app();
+        app.insert_resource(game)
+            .add_startup_system(spawn_pieces)
+            .add_system(update_stack_labels);
+        app.update();
+
+        let labels = app
+            .world
+            .query::<(&StackLabel, &Text)>()
+            .iter(&app.world)
+            .map(|(label, text)| (label.position, label.color, text.sections[0].value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(labels.len(), 2);
+        assert!(labels.contains(&(1, game::Color::White, String::from("7"))));
+        assert!(labels.contains(&(24, game::Color::Black, String::from("15"))));
+
+        let on_first_point = app
+            .world
+            .query::<&Piece>()
+            .iter(&app.world)
+            .filter(|piece| piece.position == 1)
+            .count();
+        assert_eq!(on_first_point, game::MAX_STACK_ROWS);
+
+        // a stack shrinking to the threshold loses its label
+        app.world.resource_mut::<game::Game>().board.points[0] = 5;
+        app.update();
+        assert_eq!(app.world.query::<&StackLabel>().iter(&app.world).count(), 1);
+    }
+
+    #[test]
+    fn render_row_saturates_while_the_logical_row_grows() {
+        let mut board = game::Board {
+            points: [0; 24],
+            bar: [0, 0],
+            borne_off: [0, 0],
+        };
+        for checkers in 0..15 {
+            board.points[0] = checkers;
+            let row = board.get_next_free_row(0);
+            assert_eq!(row, checkers as usize + 1);
+            assert_eq!(render_row(row), row.min(game::MAX_STACK_ROWS + 1));
+        }
+
+        let scene_scale = SceneScale::default();
+        let top = game::MAX_STACK_ROWS + 1;
+        assert_eq!(
+            white_checker(1, top).board_coordinates(scene_scale),
+            white_checker(1, 12).board_coordinates(scene_scale)
+        );
+        assert_ne!(
+            white_checker(1, top - 1).board_coordinates(scene_scale),
+            white_checker(1, top).board_coordinates(scene_scale)
+        );
+    }
+
+    #[test]
+    fn lighting_settings_update_the_lights() {
+        let mut app = test_support::test_app();
+        app.insert_resource(AmbientLight {
+            color: Color::WHITE,
+            brightness: 1.0,
+        })
+        .init_resource::<LightingSettings>()
+        .add_system(apply_lighting_settings);
+        let spot_light = app.world.spawn(SpotLight::default()).id();
+        app.update();
+        assert!((app.world.resource::<AmbientLight>().brightness - 0.2).abs() < 1e-6);
+        assert_eq!(
+            app.world.get::<SpotLight>(spot_light).unwrap().intensity,
+            800.0
+        );
+
+        app.world.resource_mut::<LightingSettings>().ambient = 0.6;
+        app.update();
+        assert_eq!(app.world.resource::<AmbientLight>().brightness, 0.6);
+
+        app.world.resource_mut::<LightingSettings>().adjust(-1.0);
+        app.update();
+        assert!((app.world.resource::<AmbientLight>().brightness - 0.55).abs() < 1e-6);
+        assert_eq!(
+            app.world.get::<SpotLight>(spot_light).unwrap().intensity,
+            600.0
+        );
+
+        let mut lighting = LightingSettings::default();
+        lighting.adjust(-100.0);
+        assert_eq!((lighting.ambient, lighting.spot_intensity), (0.0, 0.0));
+    }
+
+    #[test]
+    fn materials_follow_the_state_precedence() {
+        let app = test_support::test_app();
+        let game_resources = app.world.resource::<GameResources>();
+
+        let mut piece = Piece {
+            highlighted: true,
+            chosen: true,
+            ..white_checker(1, 1)
+        };
+        assert_eq!(
+            piece.material(game_resources),
+            game_resources.chosen_material
+        );
+
+        piece.candidate = true;
+        assert_eq!(
+            piece.material(game_resources),
+            game_resources.candidate_material
+        );
+
+        piece.dimmed = true;
+        assert_eq!(
+            piece.material(game_resources),
+            game_resources.dimmed_material
+        );
+
+        let piece = Piece {
+            highlighted: true,
+            ..white_checker(1, 1)
+        };
+        assert_eq!(
+            piece.material(game_resources),
+            game_resources.highlighted_material
+        );
+        assert_eq!(
+            white_checker(1, 1).material(game_resources),
+            game_resources.white_material
+        );
+    }
+
+    #[test]
+    fn cube_sits_on_the_side_of_its_owner() {
+        let mut game = game::Game::new();
+        game.cube.value = 2;
+        game.cube.owner = Some(game::Color::White);
+
+        let mut app = test_support::test_app();
+        app.insert_resource(game).add_system(update_cube_model);
+        let cube = app
+            .world
+            .spawn((Transform::default(), Visibility::Hidden, CubeModel))
+            .id();
+
+        let board = SceneScale::default().board;
+        let mut cube_at = |owner| {
+            app.world.resource_mut::<game::Game>().cube.owner = owner;
+            app.update();
+            app.world.get::<Transform>(cube).unwrap().translation
+        };
+        assert_eq!(
+            cube_at(Some(game::Color::White)),
+            board.scene_position([CUBE_X, CUBE_SIDE_OFFSET], 0.0)
+        );
+        assert_eq!(
+            cube_at(Some(game::Color::Black)),
+            board.scene_position([CUBE_X, -CUBE_SIDE_OFFSET], 0.0)
+        );
+        assert_eq!(cube_at(None), board.scene_position([CUBE_X, 0.0], 0.0));
+        assert_eq!(
+            *app.world.get::<Visibility>(cube).unwrap(),
+            Visibility::Inherited
+        );
+
+        // no cube in a one point match
+        app.world
+            .resource_mut::<game::Game>()
+            .select_match_length(1);
+        app.update();
+        assert_eq!(
+            *app.world.get::<Visibility>(cube).unwrap(),
+            Visibility::Hidden
+        );
+    }
+
+    #[test]
+    fn checkers_past_the_threshold_use_the_low_mesh() {
+        let app = test_support::test_app();
+        let mut game_resources = app.world.resource::<GameResources>().clone();
+        assert_ne!(
+            game_resources.checkers_model,
+            game_resources.checkers_model_low
+        );
+        let threshold = game_resources.checker_lod.piece_threshold;
+
+        assert_eq!(
+            game_resources.checker_mesh(&white_checker(1, threshold)),
+            game_resources.checkers_model
+        );
+        assert_eq!(
+            game_resources.checker_mesh(&white_checker(1, threshold + 1)),
+            game_resources.checkers_model_low
+        );
+
+        game_resources.checker_lod.enabled = false;
+        assert_eq!(
+            game_resources.checker_mesh(&white_checker(1, threshold + 1)),
+            game_resources.checkers_model
+        );
+    }
+
+    #[test]
+    fn b_toggles_the_beginner_assist() {
+        let mut app = test_support::test_app();
+        app.init_resource::<Input<KeyCode>>()
+            .add_event::<ToastEvent>()
+            .init_resource::<BeginnerAssist>()
+            .add_system(toggle_beginner_assist);
+        assert!(!app.world.resource::<BeginnerAssist>().enabled);
+
+        press(&mut app, KeyCode::B);
+        assert!(app.world.resource::<BeginnerAssist>().enabled);
+        assert_eq!(app.world.resource::<Events<ToastEvent>>().len(), 1);
+
+        press(&mut app, KeyCode::B);
+        assert!(!app.world.resource::<BeginnerAssist>().enabled);
+    }
+
+    #[test]
+    fn o_toggles_the_auto_bear_off() {
+        let mut app = test_support::test_app();
+        app.init_resource::<Input<KeyCode>>()
+            .add_event::<ToastEvent>()
+            .init_resource::<AutoBearOff>()
+            .add_system(toggle_auto_bear_off);
+        assert!(!app.world.resource::<AutoBearOff>().enabled);
+
+        press(&mut app, KeyCode::O);
+        assert!(app.world.resource::<AutoBearOff>().enabled);
+        assert_eq!(app.world.resource::<Events<ToastEvent>>().len(), 1);
+
+        press(&mut app, KeyCode::O);
+        assert!(!app.world.resource::<AutoBearOff>().enabled);
+    }
+}
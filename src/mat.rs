@@ -0,0 +1,450 @@
+// Import of match transcripts in the `.mat` format written by analysis tools:
+//
+//  5 point match
+//
+//  Game 1
+//  Alice : 0                           Bob : 0
+//   1) 52: 13/8 24/22                  31: 8/5 6/5
+//   2) 64: 22/16 13/9                  Doubles => 2
+//   3)  Takes                          44: 24/20(2) 13/9*(2)
+//
+// The left column is played as White, the right one as Black. Points are
+// numbered from the mover's side, 25 being the bar and 0 borne off.
+use crate::game::{self, Game, GameLogEntry, BAR};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum MatAction {
+    Move {
+        dice: [usize; 2],
+        moves: Vec<game::Move>,
+    },
+    Double(u32),
+    Take,
+    Drop,
+    Win(u32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct MatTurn {
+    pub(crate) player: game::Color,
+    pub(crate) action: MatAction,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct MatGame {
+    pub(crate) score: [u32; 2],
+    pub(crate) turns: Vec<MatTurn>,
+}
+
+impl MatGame {
+    // Turns that move checkers, leaving out cube actions.
+    pub(crate) fn move_turns(&self) -> usize {
+        self.turns
+            .iter()
+            .filter(|turn| matches!(turn.action, MatAction::Move { .. }))
+            .count()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Match {
+    pub(crate) points: u32,
+    pub(crate) players: [String; 2],
+    pub(crate) games: Vec<MatGame>,
+}
+
+impl Match {
+    pub(crate) fn from_mat(text: &str) -> Result<Match, String> {
+        let mut points = None;
+        let mut players = [String::new(), String::new()];
+        let mut games: Vec<MatGame> = vec![];
+        // column where the right-hand (Black) player's entries start
+        let mut right_column = None;
+
+        for (line_number, line) in text.lines().enumerate() {
+            let tokens = tokens_with_offsets(line);
+            let words = tokens.iter().map(|(_, token)| *token).collect::<Vec<_>>();
+            let error = |message: &str| format!("Line {}: {}", line_number + 1, message);
+
+            match words.as_slice() {
+                [] => {}
+                [length, "point", "match"] => {
+                    points = Some(length.parse().map_err(|_| error("Bad match length"))?);
+                }
+                ["Game", _] => games.push(MatGame::default()),
+                [white, ":", white_score, black, ":", black_score] => {
+                    let game = games.last_mut().ok_or_else(|| error("Score before game"))?;
+                    players = [white.to_string(), black.to_string()];
+                    game.score = [
+                        white_score.parse().map_err(|_| error("Bad score"))?,
+                        black_score.parse().map_err(|_| error("Bad score"))?,
+                    ];
+                    right_column = Some(tokens[3].0);
+                }
+                [number, ..] if number.ends_with(')') => {
+                    let game = games.last_mut().ok_or_else(|| error("Move before game"))?;
+                    let right_column = right_column.ok_or_else(|| error("Move before score"))?;
+
+                    let (white_tokens, black_tokens): (Vec<_>, Vec<_>) = tokens[1..]
+                        .iter()
+                        .partition(|(offset, _)| *offset < right_column);
+
+                    for (player, tokens) in [
+                        (game::Color::White, white_tokens),
+                        (game::Color::Black, black_tokens),
+                    ] {
+                        let words = tokens.iter().map(|(_, token)| *token).collect::<Vec<_>>();
+                        if let Some(action) = parse_action(player, &words).map_err(|e| error(&e))? {
+                            game.turns.push(MatTurn { player, action });
+                        }
+                    }
+                }
+                _ => return Err(error("Unrecognized line")),
+            }
+        }
+
+        let points = points.ok_or("Missing match length")?;
+        if games.is_empty() {
+            return Err(String::from("No games in match"));
+        }
+
+        Ok(Match {
+            points,
+            players,
+            games,
+        })
+    }
+
+    // Play back the moves of a game from the starting position.
+    #[cfg(test)]
+    pub(crate) fn replay(&self, game_index: usize) -> Result<Game, String> {
+        self.replay_to(game_index, usize::MAX)
+    }
+
+    // Play back only the first `move_turns` turns that move checkers.
+    pub(crate) fn replay_to(&self, game_index: usize, move_turns: usize) -> Result<Game, String> {
+        let mat_game = self
+            .games
+            .get(game_index)
+            .ok_or_else(|| format!("No game {}", game_index + 1))?;
+
+        let mut game = Game::new();
+        game.opening_roll = false;
+
+        for (turn_number, turn) in mat_game.turns.iter().enumerate() {
+            let MatAction::Move { dice, moves } = &turn.action else {
+                continue;
+            };
+            if game.game_log.len() == move_turns {
+                break;
+            }
+
+            game.player = turn.player;
+            game.game_log.push(GameLogEntry {
+                player: turn.player,
+                dice_rolls: dice.to_vec(),
+                opening_tie: false,
+            });
+            game.dice_rolls = game::expand_roll(*dice);
+
+            for checker_move in moves.iter() {
+                game.play_move(turn.player, *checker_move)
+                    .map_err(|e| format!("Turn {}: {}", turn_number + 1, e))?;
+            }
+
+            game.player = turn.player.opposite();
+            game.dice_rolls = vec![];
+        }
+
+        Ok(game)
+    }
+
+    // Write a game back out in the `.mat` layout, with every point numbered
+    // from `perspective`'s side rather than each mover's own.
+    pub(crate) fn export_transcript(
+        &self,
+        game_index: usize,
+        perspective: game::Color,
+    ) -> Result<String, String> {
+        let mat_game = self
+            .games
+            .get(game_index)
+            .ok_or_else(|| format!("No game {}", game_index + 1))?;
+
+        let mut text = format!(" {} point match\n\n Game {}\n", self.points, game_index + 1);
+        let white = format!("{} : {}", self.players[0], mat_game.score[0]);
+        text += &format!(
+            " {:<width$}{} : {}\n",
+            white,
+            self.players[1],
+            mat_game.score[1],
+            width = RIGHT_COLUMN - 1
+        );
+
+        // White's action starts a row, Black's completes it
+        let mut rows: Vec<[String; 2]> = vec![];
+        for turn in mat_game.turns.iter() {
+            let action = format_action(&turn.action, perspective);
+            match (turn.player, rows.last_mut()) {
+                (game::Color::Black, Some(row)) if row[1].is_empty() => row[1] = action,
+                (game::Color::White, _) => rows.push([action, String::new()]),
+                (game::Color::Black, _) => rows.push([String::new(), action]),
+            }
+        }
+
+        for (number, [white, black]) in rows.iter().enumerate() {
+            let number = format!("{:>3})", number + 1);
+            let line = format!(
+                "{} {:<width$}{}",
+                number,
+                white,
+                black,
+                width = RIGHT_COLUMN - number.len() - 1
+            );
+            text += line.trim_end();
+            text += "\n";
+        }
+
+        Ok(text)
+    }
+}
+
+// Column the right-hand (Black) player's entries start at in exports.
+const RIGHT_COLUMN: usize = 37;
+
+fn format_action(action: &MatAction, perspective: game::Color) -> String {
+    match action {
+        MatAction::Move { dice, moves } => {
+            let mut text = format!("{}{}:", dice[0], dice[1]);
+            for checker_move in moves {
+                text += &format!(" {}", format_move(*checker_move, perspective));
+            }
+            text
+        }
+        MatAction::Double(value) => format!("Doubles => {}", value),
+        MatAction::Take => String::from("Takes"),
+        MatAction::Drop => String::from("Drops"),
+        MatAction::Win(points) => format!("Wins {} point", points),
+    }
+}
+
+// A move like `13/8`, with points numbered from `perspective`'s side.
+pub(crate) fn format_move(checker_move: game::Move, perspective: game::Color) -> String {
+    let from = match checker_move {
+        game::Move::Enter { .. } => String::from("bar"),
+        _ => point_number(perspective, checker_move.source()).to_string(),
+    };
+    let to = match checker_move.destination() {
+        Some(index) => point_number(perspective, index).to_string(),
+        None => String::from("off"),
+    };
+
+    format!("{}/{}", from, to)
+}
+
+// Point number of a board index seen from `perspective`'s side, the
+// counterpart of `parse_point`.
+pub(crate) fn point_number(perspective: game::Color, index: usize) -> usize {
+    match perspective {
+        game::Color::White => 24 - index,
+        game::Color::Black => index + 1,
+    }
+}
+
+// Whitespace separated tokens of a line with their starting column.
+fn tokens_with_offsets(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start = None;
+
+    for (offset, character) in line.char_indices() {
+        match (character.is_whitespace(), start) {
+            (false, None) => start = Some(offset),
+            (true, Some(token_start)) => {
+                tokens.push((token_start, &line[token_start..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(token_start) = start {
+        tokens.push((token_start, &line[token_start..]));
+    }
+
+    tokens
+}
+
+fn parse_action(player: game::Color, words: &[&str]) -> Result<Option<MatAction>, String> {
+    let action = match words {
+        [] => return Ok(None),
+        ["Doubles", "=>", value] => MatAction::Double(value.parse().map_err(|_| "Bad cube value")?),
+        ["Takes"] => MatAction::Take,
+        ["Drops"] => MatAction::Drop,
+        ["Wins", value, _] => MatAction::Win(value.parse().map_err(|_| "Bad points won")?),
+        [roll, moves @ ..] if roll.len() == 3 && roll.ends_with(':') => {
+            let dice = roll
+                .chars()
+                .take(2)
+                .map(|die| match die.to_digit(10) {
+                    Some(value @ 1..=6) => Ok(value as usize),
+                    _ => Err(format!("Bad roll {}", roll)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut parsed_moves = vec![];
+            for word in moves {
+                parsed_moves.extend(parse_move(player, word)?);
+            }
+
+            MatAction::Move {
+                dice: [dice[0], dice[1]],
+                moves: parsed_moves,
+            }
+        }
+        _ => return Err(format!("Unrecognized action {}", words.join(" "))),
+    };
+
+    Ok(Some(action))
+}
+
+// A move like `13/8`, `bar/22`, `6/off`, `24/18*/13` or `13/8(2)`.
+fn parse_move(player: game::Color, word: &str) -> Result<Vec<game::Move>, String> {
+    let (word, repeat) = match word.split_once('(') {
+        Some((word, repeat)) => (
+            word,
+            repeat
+                .trim_end_matches(')')
+                .parse()
+                .map_err(|_| format!("Bad move {}", word))?,
+        ),
+        None => (word, 1),
+    };
+
+    let points = word
+        .split('/')
+        .map(|point| parse_point(player, point.trim_end_matches('*')))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if points.len() < 2 {
+        return Err(format!("Bad move {}", word));
+    }
+
+    let mut moves = vec![];
+    for _ in 0..repeat {
+        for pair in points.windows(2) {
+            let from = match pair[0] {
+                MatPoint::Bar => BAR,
+                MatPoint::Index(index) if (0..24).contains(&index) => index as usize,
+                _ => return Err(format!("Bad move {}", word)),
+            };
+            let to = match pair[1] {
+                MatPoint::Index(index) => index,
+                MatPoint::Bar => return Err(format!("Bad move {}", word)),
+            };
+            moves.push(game::Move::between(from, to));
+        }
+    }
+
+    Ok(moves)
+}
+
+enum MatPoint {
+    Bar,
+    Index(i32),
+}
+
+// Convert a point numbered from `player`'s side into a board index;
+// borne off checkers land just past the end of the board.
+fn parse_point(player: game::Color, point: &str) -> Result<MatPoint, String> {
+    let number = match point {
+        "bar" => 25,
+        "off" => 0,
+        _ => point
+            .parse::<i32>()
+            .map_err(|_| format!("Bad point {}", point))?,
+    };
+
+    if number == 25 {
+        return Ok(MatPoint::Bar);
+    }
+
+    if !(0..25).contains(&number) {
+        return Err(format!("Bad point {}", point));
+    }
+
+    Ok(MatPoint::Index(match player {
+        game::Color::White => 24 - number,
+        game::Color::Black => number - 1,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = " 5 point match
+
+ Game 1
+ Alice : 0                           Bob : 0
+  1) 52: 13/8 24/22                  31: 8/5 6/5
+  2) 64: 22/16 13/9                  Doubles => 2
+  3)  Takes                          44: 24/20(2) 13/9*(2)
+  4) 62: bar/23 13/7
+";
+
+    #[test]
+    fn sample_match_replays_to_a_valid_board() {
+        let mat = Match::from_mat(SAMPLE).unwrap();
+        assert_eq!(mat.points, 5);
+        assert_eq!(mat.players, ["Alice".to_string(), "Bob".to_string()]);
+        assert_eq!(mat.games[0].turns.len(), 7);
+        assert_eq!(mat.games[0].move_turns(), 5);
+
+        let played_moves: usize = mat.games[0]
+            .turns
+            .iter()
+            .map(|turn| match &turn.action {
+                MatAction::Move { moves, .. } => moves.len(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(played_moves, 12);
+
+        let game = mat.replay(0).unwrap();
+        assert_eq!(game.board.checker_total(game::Color::White), 15);
+        assert_eq!(game.board.checker_total(game::Color::Black), 15);
+        assert_eq!(game.board.bar, [0, 0]);
+        assert_eq!(game.hits, [1, 0]);
+        assert_eq!(game.player, game::Color::Black);
+
+        assert!(Match::from_mat("garbage").is_err());
+    }
+
+    #[test]
+    fn transcript_numbers_moves_from_either_side() {
+        let checker_move = game::Move::Point { from: 0, to: 5 };
+        assert_eq!(format_move(checker_move, game::Color::White), "24/19");
+        assert_eq!(format_move(checker_move, game::Color::Black), "1/6");
+        assert_eq!(
+            format_move(game::Move::Enter { to: 2 }, game::Color::White),
+            "bar/22"
+        );
+        assert_eq!(
+            format_move(game::Move::BearOff { from: 20 }, game::Color::White),
+            "4/off"
+        );
+        assert_eq!(
+            format_move(game::Move::BearOff { from: 3 }, game::Color::Black),
+            "4/off"
+        );
+
+        let mat = Match::from_mat(SAMPLE).unwrap();
+        let white = mat.export_transcript(0, game::Color::White).unwrap();
+        let black = mat.export_transcript(0, game::Color::Black).unwrap();
+        assert!(white.contains(" Alice : 0                           Bob : 0"));
+        assert!(white.contains("  1) 52: 13/8 24/22                  31: 17/20 19/20"));
+        assert!(black.contains("  1) 52: 12/17 1/3                   31: 8/5 6/5"));
+
+        assert!(mat.export_transcript(3, game::Color::White).is_err());
+    }
+}
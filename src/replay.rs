@@ -0,0 +1,410 @@
+// Replay of a game imported from a `.mat` transcript, scrubbed with a slider.
+use bevy::{prelude::*, window::PrimaryWindow};
+use std::path::Path;
+
+use crate::{
+    clipboard::PositionClipboard, game, mat::Match, redraw_pieces, toast::ToastEvent,
+    ui::NORMAL_BUTTON, undo::UndoHistory, GameResources, Piece, SceneScale,
+};
+
+#[derive(Default, Resource)]
+pub(crate) struct ReplayState {
+    // `None` outside of replay mode
+    pub(crate) mat: Option<Match>,
+    pub(crate) game_index: usize,
+    // turns played on the shown board, 0 being the starting position
+    pub(crate) index: usize,
+}
+
+impl ReplayState {
+    pub(crate) fn load(path: &Path) -> Result<ReplayState, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Ok(ReplayState {
+            mat: Some(Match::from_mat(&text)?),
+            ..default()
+        })
+    }
+
+    // Turns in the replayed game that move checkers.
+    pub(crate) fn total(&self) -> usize {
+        self.mat
+            .as_ref()
+            .and_then(|mat| mat.games.get(self.game_index))
+            .map_or(0, |mat_game| mat_game.move_turns())
+    }
+
+    pub(crate) fn game_at_index(&self) -> Option<Result<game::Game, String>> {
+        let mat = self.mat.as_ref()?;
+        Some(mat.replay_to(self.game_index, self.index))
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct ReplayPanel;
+
+#[derive(Component)]
+pub(crate) struct ReplaySlider;
+
+#[derive(Component)]
+pub(crate) struct ReplaySliderThumb;
+
+#[derive(Component)]
+pub(crate) struct LabelReplayTurn;
+
+#[derive(Component)]
+pub(crate) struct ButtonPlayFromHere;
+
+const SLIDER_WIDTH: f32 = 400.0;
+const THUMB_WIDTH: f32 = 12.0;
+
+// Index under `fraction` of the slider's width, clamped to the track.
+pub(crate) fn slider_index(fraction: f32, total: usize) -> usize {
+    (fraction.clamp(0.0, 1.0) * total as f32).round() as usize
+}
+
+pub(crate) fn setup_replay_slider(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    replay_state: Res<ReplayState>,
+) {
+    if replay_state.mat.is_none() {
+        return;
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(20.0),
+                    left: Val::Px(20.0),
+                    ..default()
+                },
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(SLIDER_WIDTH), Val::Px(12.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.3, 0.3, 0.3).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                size: Size::new(Val::Px(THUMB_WIDTH), Val::Px(24.0)),
+                                position: UiRect {
+                                    top: Val::Px(-6.0),
+                                    left: Val::Px(-THUMB_WIDTH / 2.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            background_color: Color::rgb(0.9, 0.9, 0.9).into(),
+                            ..default()
+                        })
+                        .insert(ReplaySliderThumb);
+                })
+                .insert(ReplaySlider);
+
+            parent
+                .spawn(
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::left(Val::Px(20.0)),
+                        ..default()
+                    }),
+                )
+                .insert(LabelReplayTurn);
+
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        margin: UiRect::left(Val::Px(20.0)),
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Play from here",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                    ));
+                })
+                .insert(ButtonPlayFromHere);
+        })
+        .insert(ReplayPanel)
+        .insert(Name::new("ReplaySlider"));
+}
+
+// Dragging along the slider moves the replay to the turn under the cursor.
+pub(crate) fn handle_replay_slider(
+    slider_query: Query<(&Interaction, &Node, &GlobalTransform), With<ReplaySlider>>,
+    primary_window_query: Query<&Window, With<PrimaryWindow>>,
+    mut replay_state: ResMut<ReplayState>,
+) {
+    let Some(cursor) = primary_window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for (interaction, node, transform) in slider_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let left = transform.translation().x - node.size().x / 2.0;
+        let index = slider_index((cursor.x - left) / node.size().x, replay_state.total());
+        if replay_state.index != index {
+            replay_state.index = index;
+        }
+    }
+}
+
+// Rebuilds the board for the replay index whenever it changes.
+pub(crate) fn apply_replay_index(
+    mut commands: Commands,
+    replay_state: Res<ReplayState>,
+    mut game: ResMut<game::Game>,
+    mut pieces_query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
+    game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
+    mut thumb_query: Query<&mut Style, With<ReplaySliderThumb>>,
+    mut label_query: Query<&mut Text, With<LabelReplayTurn>>,
+) {
+    if !replay_state.is_changed() {
+        return;
+    }
+
+    let Some(replayed) = replay_state.game_at_index() else {
+        return;
+    };
+
+    match replayed {
+        Ok(replayed) => *game = replayed,
+        Err(e) => {
+            warn!("Couldn't replay turn {}: {}", replay_state.index, e);
+            return;
+        }
+    }
+
+    redraw_pieces(
+        &mut commands,
+        &mut pieces_query,
+        &game,
+        &game_resources,
+        *scene_scale,
+    );
+
+    let total = replay_state.total();
+    let fraction = replay_state.index as f32 / total.max(1) as f32;
+    for mut style in thumb_query.iter_mut() {
+        style.position.left = Val::Px(fraction * SLIDER_WIDTH - THUMB_WIDTH / 2.0);
+    }
+
+    for mut text in label_query.iter_mut() {
+        text.sections[0].value = format!("Turn {} / {}", replay_state.index, total);
+    }
+}
+
+// Leaves the replay for a live game from the shown position, with the shown
+// player on roll. The board edit check redraws the pieces.
+pub(crate) fn handle_play_from_here(
+    mut commands: Commands,
+    button_query: Query<Ref<Interaction>, With<ButtonPlayFromHere>>,
+    panel_query: Query<Entity, With<ReplayPanel>>,
+    mut replay_state: ResMut<ReplayState>,
+    mut game: ResMut<game::Game>,
+    mut undo_history: ResMut<UndoHistory>,
+) {
+    let clicked = button_query
+        .iter()
+        .any(|interaction| interaction.is_changed() && *interaction == Interaction::Clicked);
+    if !clicked {
+        return;
+    }
+
+    match replay_state.game_at_index() {
+        Some(Ok(replayed)) => *game = replayed.play_from_here(),
+        Some(Err(e)) => {
+            warn!("Couldn't replay turn {}: {}", replay_state.index, e);
+            return;
+        }
+        None => return,
+    }
+
+    replay_state.mat = None;
+    undo_history.clear();
+    for entity in panel_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// M copies the replayed game as a `.mat` transcript numbered from White's
+// side, Shift+M from Black's.
+pub(crate) fn copy_replay_transcript(
+    keyboard_input: Res<Input<KeyCode>>,
+    replay_state: Res<ReplayState>,
+    mut clipboard: ResMut<PositionClipboard>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::M) {
+        return;
+    }
+    let Some(mat) = &replay_state.mat else {
+        return;
+    };
+
+    let perspective = if keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+        game::Color::Black
+    } else {
+        game::Color::White
+    };
+    let copied = mat
+        .export_transcript(replay_state.game_index, perspective)
+        .and_then(|transcript| clipboard.0.set_text(&transcript));
+
+    toast_event_writer.send(ToastEvent::new(match copied {
+        Ok(()) => format!("Transcript copied, numbered from {:?}'s side", perspective),
+        Err(e) => format!("Couldn't copy transcript: {}", e),
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_app, FakeClipboard};
+
+    const SAMPLE: &str = " 5 point match
+
+ Game 1
+ Alice : 0                           Bob : 0
+  1) 52: 13/8 24/22                  31: 8/5 6/5
+  2) 64: 22/16 13/9                  Doubles => 2
+  3)  Takes                          44: 24/20(2) 13/9*(2)
+  4) 62: bar/23 13/7
+";
+
+    #[test]
+    fn slider_rebuilds_the_board_at_its_turn() {
+        assert_eq!(slider_index(0.5, 6), 3);
+        assert_eq!(slider_index(-1.0, 6), 0);
+        assert_eq!(slider_index(2.0, 6), 6);
+
+        let mat = Match::from_mat(SAMPLE).unwrap();
+        let mut app = test_app();
+        app.insert_resource(game::Game::new())
+            .insert_resource(ReplayState {
+                mat: Some(mat.clone()),
+                game_index: 0,
+                index: 0,
+            })
+            .add_system(apply_replay_index);
+        let label = app
+            .world
+            .spawn((
+                Text::from_section("", TextStyle::default()),
+                LabelReplayTurn,
+            ))
+            .id();
+        app.update();
+        assert_eq!(app.world.resource::<ReplayState>().total(), 5);
+        assert!(app
+            .world
+            .resource::<game::Game>()
+            .board
+            .is_opening_position(game::GameVariant::Standard));
+
+        app.world.resource_mut::<ReplayState>().index = 3;
+        app.update();
+
+        // 52: 13/8 24/22, 31: 8/5 6/5, 64: 22/16 13/9 played by hand
+        let mut expected = game::Game::new();
+        for (player, dice_rolls, moves) in [
+            (game::Color::White, vec![5, 2], [(11, 16), (0, 2)]),
+            (game::Color::Black, vec![3, 1], [(7, 4), (5, 4)]),
+            (game::Color::White, vec![6, 4], [(2, 8), (11, 15)]),
+        ] {
+            expected.dice_rolls = dice_rolls;
+            for (from, to) in moves {
+                expected
+                    .play_move(player, game::Move::between(from, to))
+                    .unwrap();
+            }
+        }
+        assert_eq!(app.world.resource::<game::Game>().board, expected.board);
+        assert_eq!(
+            app.world.get::<Text>(label).unwrap().sections[0].value,
+            "Turn 3 / 5"
+        );
+
+        app.world.resource_mut::<ReplayState>().index = 5;
+        app.update();
+        assert_eq!(
+            app.world.resource::<game::Game>().board,
+            mat.replay(0).unwrap().board
+        );
+    }
+
+    #[test]
+    fn m_copies_the_transcript_from_either_side() {
+        let mat = Match::from_mat(SAMPLE).unwrap();
+        let clipboard = FakeClipboard::default();
+        let mut app = test_app();
+        app.add_event::<ToastEvent>()
+            .init_resource::<Input<KeyCode>>()
+            .insert_resource(PositionClipboard(Box::new(clipboard.clone())))
+            .insert_resource(ReplayState {
+                mat: Some(mat.clone()),
+                ..default()
+            })
+            .add_system(copy_replay_transcript);
+
+        let copy = |app: &mut App, keys: &[KeyCode]| {
+            let mut input = app.world.resource_mut::<Input<KeyCode>>();
+            input.reset_all();
+            for key in keys {
+                input.press(*key);
+            }
+            app.update();
+            clipboard.0.lock().unwrap().take()
+        };
+
+        assert_eq!(
+            copy(&mut app, &[KeyCode::M]),
+            mat.export_transcript(0, game::Color::White).ok()
+        );
+        assert_eq!(
+            copy(&mut app, &[KeyCode::LShift, KeyCode::M]),
+            mat.export_transcript(0, game::Color::Black).ok()
+        );
+
+        // nothing to export outside of replay mode
+        app.world.resource_mut::<ReplayState>().mat = None;
+        assert_eq!(copy(&mut app, &[KeyCode::M]), None);
+    }
+}
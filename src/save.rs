@@ -0,0 +1,147 @@
+// Saved games. JSON is readable, the binary format is compact for storing many
+// states; both encode the same `SavedGame` model. Binary saves start with
+// `BINARY_MAGIC` so `Game::load` can tell the formats apart.
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cube::{DoublingCube, MatchScore},
+    game::{Board, Color, Game, GameLogEntry},
+};
+
+const BINARY_MAGIC: &[u8; 4] = b"BGMN";
+
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    board: Board,
+    dice_rolls: Vec<usize>,
+    dice_rolled: bool,
+    opening_roll: bool,
+    player: Color,
+    game_log: Vec<GameLogEntry>,
+    hits: [u32; 2],
+    #[serde(default)]
+    cube: DoublingCube,
+    #[serde(default)]
+    match_score: Option<MatchScore>,
+}
+
+impl From<&Game> for SavedGame {
+    fn from(game: &Game) -> Self {
+        SavedGame {
+            board: game.board.clone(),
+            dice_rolls: game.dice_rolls.clone(),
+            dice_rolled: game.dice_rolled,
+            opening_roll: game.opening_roll,
+            player: game.player,
+            game_log: game.game_log.clone(),
+            hits: game.hits,
+            cube: game.cube,
+            match_score: game.match_score,
+        }
+    }
+}
+
+impl From<SavedGame> for Game {
+    fn from(saved: SavedGame) -> Self {
+        let mut game = Game::new();
+        game.board = saved.board;
+        game.dice_rolls = saved.dice_rolls;
+        game.dice_rolled = saved.dice_rolled;
+        game.opening_roll = saved.opening_roll;
+        game.player = saved.player;
+        game.game_log = saved.game_log;
+        game.hits = saved.hits;
+        game.cube = saved.cube;
+        game.match_score = saved.match_score;
+        game
+    }
+}
+
+impl Game {
+    pub fn save_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&SavedGame::from(self)).map_err(|e| e.to_string())
+    }
+
+    pub fn load_json(json: &str) -> Result<Game, String> {
+        let saved: SavedGame = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Ok(saved.into())
+    }
+
+    pub fn save_binary(&self) -> Result<Vec<u8>, String> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bincode::serialize_into(&mut bytes, &SavedGame::from(self)).map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+
+    pub fn load_binary(bytes: &[u8]) -> Result<Game, String> {
+        let payload = bytes
+            .strip_prefix(BINARY_MAGIC)
+            .ok_or("Not a binary save")?;
+        let saved: SavedGame = bincode::deserialize(payload).map_err(|e| e.to_string())?;
+        Ok(saved.into())
+    }
+
+    // Load a save in either format.
+    pub fn load(bytes: &[u8]) -> Result<Game, String> {
+        if bytes.starts_with(BINARY_MAGIC) {
+            return Game::load_binary(bytes);
+        }
+
+        let json = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        Game::load_json(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A mid-game position with a checker on the bar, Black on roll.
+    fn saved_game() -> Game {
+        let mut game = Game::new();
+        game.opening_roll = false;
+        game.board.points[0] = 1;
+        game.board.points[5] = -4;
+        game.board.bar = [1, 1];
+        game.player = Color::Black;
+        game
+    }
+
+    #[test]
+    fn binary_save_round_trips_the_board() {
+        let game = saved_game();
+        let bytes = game.save_binary().unwrap();
+        assert!(bytes.starts_with(BINARY_MAGIC));
+        assert!(bytes.len() < game.save_json().unwrap().len());
+
+        let loaded = Game::load(&bytes).unwrap();
+        assert_eq!(loaded.board, game.board);
+        assert_eq!(loaded.player, Color::Black);
+        assert_eq!(Game::load_binary(&bytes).unwrap().board, game.board);
+
+        assert!(Game::load(b"junk").is_err());
+        assert!(Game::load_binary(game.save_json().unwrap().as_bytes()).is_err());
+    }
+
+    #[test]
+    fn saves_keep_the_cube_and_match_score() {
+        let mut game = saved_game();
+        game.cube = DoublingCube {
+            value: 4,
+            owner: Some(Color::Black),
+        };
+        game.match_score = Some(MatchScore {
+            length: 7,
+            score: [3, 5],
+            crawford: true,
+        });
+
+        for loaded in [
+            Game::load(&game.save_binary().unwrap()).unwrap(),
+            Game::load(game.save_json().unwrap().as_bytes()).unwrap(),
+        ] {
+            assert_eq!(loaded.cube, game.cube);
+            assert_eq!(loaded.match_score, game.match_score);
+        }
+    }
+}
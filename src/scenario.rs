@@ -0,0 +1,57 @@
+// Puzzle positions with a scripted dice sequence. Starting a scenario again
+// gives the same board and the same rolls, so a puzzle can be retried.
+use serde::{Deserialize, Serialize};
+
+use crate::game::{expand_roll, Board, Color, Game, GameLogEntry};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub board: Board,
+    pub player: Color,
+    // one roll per turn, `player`'s first
+    pub dice: Vec<[usize; 2]>,
+}
+
+impl Scenario {
+    pub fn from_json(json: &str) -> Result<Scenario, String> {
+        let scenario: Scenario = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if scenario
+            .dice
+            .iter()
+            .flatten()
+            .any(|die| !(1..=6).contains(die))
+        {
+            return Err(String::from("Dice must be between 1 and 6"));
+        }
+
+        Ok(scenario)
+    }
+
+    // The starting position with the first roll already made.
+    pub fn start(&self) -> Game {
+        let mut game = Game::new();
+        game.board = self.board.clone();
+        game.player = self.player;
+        game.opening_roll = false;
+
+        self.roll(&mut game);
+        game
+    }
+
+    // Make the scripted roll of the turn `game` is at, false once the script
+    // has run out.
+    pub fn roll(&self, game: &mut Game) -> bool {
+        let Some(&dice) = self.dice.get(game.game_log.len()) else {
+            return false;
+        };
+
+        game.dice_rolls = expand_roll(dice);
+        game.dice_rolled = true;
+        game.game_log.push(GameLogEntry {
+            player: game.player,
+            dice_rolls: dice.to_vec(),
+            opening_tie: false,
+        });
+        true
+    }
+}
@@ -0,0 +1,238 @@
+// Headless game engine driven over channels, for UIs outside of Bevy such as a
+// web frontend. Commands go in, domain events come out.
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+
+use crate::game::{expand_roll, BoardSnapshot, Color, Game, GameLogEntry, GamePhase, Move};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServiceCommand {
+    // dice rolled by the frontend, the opening roll takes one die per player
+    Roll([usize; 2]),
+    Move(Move),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DomainEvent {
+    Rolled { player: Color, dice: Vec<usize> },
+    Moved { player: Color, checker_move: Move },
+    // `point` is the board index the opponent's blot was hit on
+    Hit { player: Color, point: usize },
+    TurnEnded { player: Color },
+    GameOver { winner: Color },
+    // the command wasn't legal in the current position
+    Rejected { reason: String },
+    // first event of a reconnected handle
+    Synced(StateSync),
+}
+
+// Host state a reconnecting frontend resumes from, also in the middle of a
+// turn: `dice_rolls` holds the dice still to play.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateSync {
+    pub snapshot: BoardSnapshot,
+    // length of the host's game log
+    pub log_index: usize,
+    pub player: Color,
+    pub dice_rolls: Vec<usize>,
+    pub dice_rolled: bool,
+    pub opening_roll: bool,
+}
+
+impl StateSync {
+    // Rebuild a frontend's copy of the game. Rolls the host doesn't know of are
+    // dropped from the log, missed ones can't be filled in.
+    pub fn apply(&self, game: &mut Game) {
+        game.board = self.snapshot.into();
+        game.player = self.player;
+        game.dice_rolls = self.dice_rolls.clone();
+        game.dice_rolled = self.dice_rolled;
+        game.opening_roll = self.opening_roll;
+        game.game_log.truncate(self.log_index);
+    }
+}
+
+// A move submitted to the service and whether it was accepted, kept so
+// manipulated frontends can be reviewed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub player: Color,
+    pub checker_move: Move,
+    pub legal: bool,
+}
+
+// Frontend side of a service.
+pub struct ServiceHandle {
+    pub commands: Sender<ServiceCommand>,
+    pub events: Receiver<DomainEvent>,
+}
+
+pub struct GameService {
+    game: Game,
+    commands: Receiver<ServiceCommand>,
+    events: Sender<DomainEvent>,
+    audit_log: Vec<AuditEntry>,
+}
+
+impl GameService {
+    pub fn new(game: Game) -> (GameService, ServiceHandle) {
+        let (commands, events, handle) = connect();
+
+        let service = GameService {
+            game,
+            commands,
+            events,
+            audit_log: vec![],
+        };
+        (service, handle)
+    }
+
+    // Replace the handle of a frontend that lost its connection. The new
+    // handle hears the whole state first, commands sent to the old one are lost.
+    pub fn reconnect(&mut self) -> ServiceHandle {
+        let (commands, events, handle) = connect();
+        self.commands = commands;
+        self.events = events;
+
+        self.send(DomainEvent::Synced(self.state_sync()));
+        handle
+    }
+
+    pub fn state_sync(&self) -> StateSync {
+        StateSync {
+            snapshot: self.game.board_snapshot(),
+            log_index: self.game.game_log.len(),
+            player: self.game.player,
+            dice_rolls: self.game.dice_rolls.clone(),
+            dice_rolled: self.game.dice_rolled,
+            opening_roll: self.game.opening_roll,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        &self.audit_log
+    }
+
+    // Handle the commands sent so far, false once the handle is dropped.
+    pub fn process(&mut self) -> bool {
+        loop {
+            match self.commands.try_recv() {
+                Ok(command) => self.handle(command),
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    // Handle commands until the handle is dropped, for running on its own thread.
+    pub fn run(mut self) {
+        while let Ok(command) = self.commands.recv() {
+            self.handle(command);
+        }
+    }
+
+    fn handle(&mut self, command: ServiceCommand) {
+        let result = match command {
+            ServiceCommand::Roll(dice) => self.roll(dice),
+            ServiceCommand::Move(checker_move) => self.play(checker_move),
+        };
+
+        if let Err(reason) = result {
+            self.send(DomainEvent::Rejected { reason });
+        }
+    }
+
+    fn roll(&mut self, dice: [usize; 2]) -> Result<(), String> {
+        if !dice.iter().all(|die| (1..=6).contains(die)) {
+            return Err(format!("Bad dice {:?}", dice));
+        }
+
+        match self.game.phase() {
+            GamePhase::OpeningRoll => {
+                if !self.game.resolve_opening_roll(dice[0], dice[1]) {
+                    // a tie is rolled again
+                    return Ok(());
+                }
+            }
+            GamePhase::AwaitingRoll => {
+                self.game.dice_rolls = expand_roll(dice);
+            }
+            phase => return Err(format!("Can't roll during {:?}", phase)),
+        }
+
+        let player = self.game.player;
+        self.game.dice_rolled = true;
+        self.game.game_log.push(GameLogEntry {
+            player,
+            dice_rolls: dice.to_vec(),
+            opening_tie: false,
+        });
+        self.send(DomainEvent::Rolled {
+            player,
+            dice: self.game.dice_rolls.clone(),
+        });
+
+        self.end_turn_if_stuck();
+        Ok(())
+    }
+
+    fn play(&mut self, checker_move: Move) -> Result<(), String> {
+        let player = self.game.player;
+        let legal = self.game.is_legal_move(player, checker_move);
+        self.audit_log.push(AuditEntry {
+            player,
+            checker_move,
+            legal,
+        });
+        if !legal {
+            return Err(format!("Illegal move {:?}", checker_move));
+        }
+
+        let hit = self.game.play_move(player, checker_move)?;
+        self.send(DomainEvent::Moved {
+            player,
+            checker_move,
+        });
+        if let (true, Some(point)) = (hit, checker_move.destination()) {
+            self.send(DomainEvent::Hit { player, point });
+        }
+
+        if self.game.is_over() {
+            self.send(DomainEvent::GameOver { winner: player });
+            return Ok(());
+        }
+
+        self.end_turn_if_stuck();
+        Ok(())
+    }
+
+    // Passes the turn once the dice are used up or can't be played.
+    fn end_turn_if_stuck(&mut self) {
+        let player = self.game.player;
+        if self.game.can_move(player) {
+            return;
+        }
+
+        self.game.switch_turn();
+        self.send(DomainEvent::TurnEnded { player });
+    }
+
+    fn send(&self, event: DomainEvent) {
+        // nobody left to tell once the handle is dropped
+        let _ = self.events.send(event);
+    }
+}
+
+fn connect() -> (Receiver<ServiceCommand>, Sender<DomainEvent>, ServiceHandle) {
+    let (command_sender, command_receiver) = channel();
+    let (event_sender, event_receiver) = channel();
+
+    let handle = ServiceHandle {
+        commands: command_sender,
+        events: event_receiver,
+    };
+    (command_receiver, event_sender, handle)
+}
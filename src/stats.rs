@@ -0,0 +1,158 @@
+// Results of the games played since the app started. When `STATS_FILE` exists
+// in the working directory they are loaded from and saved back to it, so they
+// carry over to the next session.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{
+    events::{GameEndReason, GameOverEvent},
+    game,
+};
+
+const STARTING_PIP_COUNT: u32 = 167;
+pub(crate) const STATS_FILE: &str = "session_stats.json";
+
+#[derive(Clone, Debug, Default, PartialEq, Resource, Serialize, Deserialize)]
+pub(crate) struct SessionStats {
+    pub(crate) games_played: u32,
+    pub(crate) wins: [u32; 2], // Indexed like `Board::bar`.
+    pub(crate) gammons: [u32; 2],
+    // winner's starting pip count over the pips they rolled, summed over games
+    pip_efficiency_total: f32,
+    #[serde(skip)]
+    pub(crate) persist: bool,
+}
+
+impl SessionStats {
+    pub(crate) fn record(&mut self, winner: game::Color, gammon: bool, pips_rolled: u32) {
+        let index = match winner {
+            game::Color::White => 0,
+            game::Color::Black => 1,
+        };
+
+        self.games_played += 1;
+        self.wins[index] += 1;
+        if gammon {
+            self.gammons[index] += 1;
+        }
+        if pips_rolled > 0 {
+            self.pip_efficiency_total += STARTING_PIP_COUNT as f32 / pips_rolled as f32;
+        }
+    }
+
+    pub(crate) fn record_game(
+        &mut self,
+        game: &game::Game,
+        winner: game::Color,
+        reason: GameEndReason,
+    ) {
+        // a game lost on time is a single game whatever is left on the board
+        let loser = winner.opposite();
+        let gammon = reason == GameEndReason::BorneOff
+            && game.board.borne_off[game.board.bar_index(loser)] == 0;
+        let pips_rolled = game
+            .game_log
+            .iter()
+            .filter(|entry| entry.player == winner)
+            .map(|entry| match entry.dice_rolls.as_slice() {
+                [a, b] if a == b => 4 * a,
+                dice_rolls => dice_rolls.iter().sum(),
+            })
+            .sum::<usize>();
+
+        self.record(winner, gammon, pips_rolled as u32);
+    }
+
+    pub(crate) fn average_pip_efficiency(&self) -> Option<f32> {
+        if self.games_played == 0 {
+            return None;
+        }
+        Some(self.pip_efficiency_total / self.games_played as f32)
+    }
+
+    pub(crate) fn summary(&self) -> String {
+        let mut summary = format!(
+            "Games {}, White won {} ({} gammons), Black won {} ({} gammons)",
+            self.games_played, self.wins[0], self.gammons[0], self.wins[1], self.gammons[1]
+        );
+        if let Some(efficiency) = self.average_pip_efficiency() {
+            summary += &format!(", pip efficiency {:.0}%", efficiency * 100.0);
+        }
+        summary
+    }
+
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut stats: SessionStats = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        stats.persist = true;
+        Ok(stats)
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
+pub(crate) fn record_session_stats(
+    mut game_over_event_reader: EventReader<GameOverEvent>,
+    mut session_stats: ResMut<SessionStats>,
+    game: Res<game::Game>,
+) {
+    for event in game_over_event_reader.iter() {
+        session_stats.record_game(&game, event.player, event.reason);
+
+        if session_stats.persist {
+            if let Err(e) = session_stats.save(Path::new(STATS_FILE)) {
+                warn!("Could not save session stats: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_app;
+
+    #[test]
+    fn counts_wins_and_gammons() {
+        let mut stats = SessionStats::default();
+        stats.record(game::Color::White, false, 200);
+        stats.record(game::Color::White, false, 167);
+        stats.record(game::Color::Black, true, 0);
+
+        assert_eq!(stats.games_played, 3);
+        assert_eq!(stats.wins, [2, 1]);
+        assert_eq!(stats.gammons, [0, 1]);
+    }
+
+    #[test]
+    fn loss_on_time_is_not_a_gammon() {
+        // Black hasn't borne off a checker
+        let mut black = [0; 24];
+        black[5] = 15;
+        let mut white = [0; 24];
+        white[23] = 2;
+        let game = game::Game::from_position(white, black, [0, 0], game::Color::Black).unwrap();
+
+        let mut app = test_app();
+        app.add_event::<GameOverEvent>()
+            .init_resource::<SessionStats>()
+            .insert_resource(game)
+            .add_system(record_session_stats);
+
+        for reason in [GameEndReason::Time, GameEndReason::BorneOff] {
+            app.world.send_event(GameOverEvent {
+                player: game::Color::White,
+                reason,
+            });
+            app.update();
+        }
+
+        let stats = app.world.resource::<SessionStats>();
+        assert_eq!(stats.wins, [2, 0]);
+        assert_eq!(stats.gammons, [1, 0]);
+    }
+}
@@ -0,0 +1,40 @@
+// Headless app for testing systems, with assets and the game resources but
+// no window or renderer.
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{clipboard::Clipboard, GameResources, SceneScale};
+
+pub(crate) fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_asset::<StandardMaterial>()
+        .add_asset::<Mesh>()
+        .init_resource::<GameResources>()
+        .init_resource::<SceneScale>();
+
+    app
+}
+
+// Runs one update with the clock moved forward by exactly `delta`.
+pub(crate) fn update_after(app: &mut App, delta: Duration) {
+    let time = app.world.resource::<Time>();
+    let last_update = time.last_update().unwrap_or_else(|| time.startup());
+    app.insert_resource(TimeUpdateStrategy::ManualInstant(last_update + delta));
+    app.update();
+}
+
+// Keeps the copied text where the test can still read it.
+#[derive(Clone, Default)]
+pub(crate) struct FakeClipboard(pub(crate) Arc<Mutex<Option<String>>>);
+
+impl Clipboard for FakeClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        *self.0.lock().unwrap() = Some(text.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+
+const TOAST_SECONDS: f32 = 3.0;
+const TOAST_FADE_SECONDS: f32 = 1.0;
+
+// Transient message shown in the corner of the screen.
+#[derive(Clone)]
+pub(crate) struct ToastEvent {
+    pub(crate) text: String,
+    pub(crate) color: Color,
+}
+
+impl ToastEvent {
+    pub(crate) fn new(text: impl Into<String>) -> Self {
+        ToastEvent {
+            text: text.into(),
+            color: Color::rgb(0.9, 0.9, 0.9),
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct Toast {
+    pub(crate) text: String,
+    pub(crate) timer: Timer,
+    pub(crate) color: Color,
+}
+
+#[derive(Component)]
+pub(crate) struct ToastStack;
+
+pub(crate) fn setup_toasts(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(80.0),
+                    right: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ToastStack)
+        .insert(Name::new("Toasts"));
+}
+
+pub(crate) fn handle_toast_event(
+    mut commands: Commands,
+    mut toast_event_reader: EventReader<ToastEvent>,
+    toast_stack_query: Query<Entity, With<ToastStack>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in toast_event_reader.iter() {
+        for toast_stack in toast_stack_query.iter() {
+            let toast = Toast {
+                text: event.text.clone(),
+                timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+                color: event.color,
+            };
+
+            commands.entity(toast_stack).with_children(|parent| {
+                parent
+                    .spawn(TextBundle::from_section(
+                        toast.text.clone(),
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 30.0,
+                            color: toast.color,
+                        },
+                    ))
+                    .insert(toast);
+            });
+        }
+    }
+}
+
+pub(crate) fn update_toasts(
+    mut commands: Commands,
+    mut toast_query: Query<(Entity, &mut Toast, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut toast, mut text) in toast_query.iter_mut() {
+        toast.timer.tick(time.delta());
+
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // fade out over the last second
+        let remaining = toast.timer.duration().as_secs_f32() - toast.timer.elapsed_secs();
+        let alpha = (remaining / TOAST_FADE_SECONDS).min(1.0);
+        let mut color = toast.color;
+        color.set_a(toast.color.a() * alpha);
+        text.sections[0].style.color = color;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_app;
+
+    fn toasts(app: &mut App) -> Vec<Entity> {
+        app.world
+            .query_filtered::<Entity, With<Toast>>()
+            .iter(&app.world)
+            .collect()
+    }
+
+    #[test]
+    fn toast_is_shown_until_its_timer_runs_out() {
+        let mut app = test_app();
+        app.add_event::<ToastEvent>()
+            .add_startup_system(setup_toasts)
+            .add_system(handle_toast_event)
+            .add_system(update_toasts);
+        app.update();
+
+        app.world.send_event(ToastEvent::new("Point made"));
+        app.update();
+        let toast = toasts(&mut app);
+        assert_eq!(toast.len(), 1);
+        assert_eq!(app.world.get::<Toast>(toast[0]).unwrap().text, "Point made");
+
+        app.update();
+        assert_eq!(toasts(&mut app), toast);
+
+        app.world
+            .get_mut::<Toast>(toast[0])
+            .unwrap()
+            .timer
+            .set_elapsed(std::time::Duration::from_secs_f32(TOAST_SECONDS));
+        app.update();
+        assert!(toasts(&mut app).is_empty());
+    }
+}
@@ -1,19 +1,40 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
 use bevy_dice::*;
-
-use std::time::Duration;
+use itertools::Itertools;
+use rand::Rng;
 
 use crate::{
-    events::{DiceRollTimer, MovePieceEvent},
-    game, Piece,
+    clock::ChessClock,
+    confirm::ConfirmTurn,
+    cube::{DoublingCube, MATCH_LENGTHS},
+    events::{
+        pass_countdown_message, DiceRenderMode, DiceRollTimer, GhostPreview, InputSettings,
+        InstantDiceRollEvent, MovePieceEvent, PassTimer, PlaybackSpeed,
+    },
+    game, mat,
+    toast::ToastEvent,
+    MainCamera, Piece, SceneScale,
 };
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+pub(crate) const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub(crate) const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+const FOCUSED_BUTTON: Color = Color::rgb(0.2, 0.2, 0.45);
 
+// Single line with the turn, pip counts and cube value.
 #[derive(Component)]
-pub(crate) struct LabelPlayerTurn;
+pub(crate) struct StatusBar;
+
+// One of six marks after the status bar, lit when the player on roll has made
+// that home point; the 6-point comes first.
+#[derive(Component)]
+pub(crate) struct HomeBoardPip(usize);
+
+const HOME_POINT_MADE: Color = Color::rgb(0.95, 0.75, 0.2);
+const HOME_POINT_OPEN: Color = Color::rgb(0.3, 0.3, 0.3);
 
 #[derive(Component)]
 pub(crate) struct LabelGameOver;
@@ -23,12 +44,167 @@ pub(crate) struct ButtonRollDice;
 
 #[derive(Component)]
 pub(crate) struct ButtonBearOff {
-    pub(crate) position_to: Option<i32>,
+    pub(crate) bear_off: Option<game::Move>,
 }
 
+#[derive(Component)]
+pub(crate) struct ButtonBearOffAll;
+
+// Picks the match length, offered until the first roll.
+#[derive(Component)]
+pub(crate) struct ButtonMatchLength(pub(crate) u32);
+
 #[derive(Component)]
 pub(crate) struct LabelMoveStack;
 
+// Face of the n-th die of the last roll, shown instead of the physics dice.
+#[derive(Component)]
+pub(crate) struct LabelDieFace(usize);
+
+#[derive(Component)]
+pub(crate) struct LabelMovesLeft;
+
+#[derive(Component)]
+pub(crate) struct LabelLegalPlays;
+
+#[derive(Component)]
+pub(crate) struct LabelPassCountdown;
+
+// Pip distance of the hovered candidate move, shown next to the cursor.
+#[derive(Component)]
+pub(crate) struct LabelPipDistance;
+
+#[derive(Component)]
+pub(crate) struct LabelPointTooltip;
+
+#[derive(Component)]
+pub(crate) struct LabelClock;
+
+// Play combined moves with the second die first.
+#[derive(Default, Resource)]
+pub(crate) struct SwapDice(pub(crate) bool);
+
+// Button selected for keyboard activation.
+#[derive(Default, Resource)]
+pub(crate) struct FocusedButton(pub(crate) Option<Entity>);
+
+// Window width the UI sizes are designed for, narrower windows scale them down.
+const REFERENCE_WINDOW_WIDTH: f32 = 1280.0;
+const MIN_UI_SCALE: f32 = 0.4;
+
+// Font sizes and node size an element was spawned with, before scaling.
+#[derive(Component)]
+pub(crate) struct BaseUiSize {
+    font_sizes: Vec<f32>,
+    size: Size,
+}
+
+pub(crate) fn ui_scale(window_width: f32) -> f32 {
+    (window_width / REFERENCE_WINDOW_WIDTH).clamp(MIN_UI_SCALE, 1.0)
+}
+
+fn scale_val(val: Val, scale: f32) -> Val {
+    match val {
+        Val::Px(px) => Val::Px(px * scale),
+        val => val,
+    }
+}
+
+// How die values are written in the move stack.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub(crate) enum DieDisplay {
+    #[default]
+    Numeral,
+    // Unicode die faces ⚀–⚅
+    Faces,
+}
+
+impl DieDisplay {
+    fn format(&self, die: usize) -> String {
+        match self {
+            DieDisplay::Numeral => die.to_string(),
+            DieDisplay::Faces => char::from_u32(0x2680 + die as u32 - 1)
+                .map_or_else(|| die.to_string(), String::from),
+        }
+    }
+}
+
+// Doubles are shown as value × remaining count, e.g. "4 ×3".
+fn format_move_stack(dice_rolls: &[usize], die_display: DieDisplay) -> String {
+    if dice_rolls.len() > 1 && dice_rolls.iter().all(|&roll| roll == dice_rolls[0]) {
+        return format!(
+            "{} ×{}",
+            die_display.format(dice_rolls[0]),
+            dice_rolls.len()
+        );
+    }
+
+    match die_display {
+        DieDisplay::Numeral => format!("{:?}", dice_rolls),
+        DieDisplay::Faces => dice_rolls
+            .iter()
+            .map(|&die| die_display.format(die))
+            .join(" "),
+    }
+}
+
+fn format_moves_left(game: &game::Game) -> String {
+    match game.moves_left() {
+        Some((left, total)) => format!("Moves left: {} of {}", left, total),
+        None => String::new(),
+    }
+}
+
+pub(crate) fn format_status(game: &game::Game, cube: &DoublingCube) -> String {
+    let mut turn = if game.opening_roll {
+        String::from("Opening Roll")
+    } else {
+        format!("Turn: {:?}", game.player)
+    };
+    if !game.opening_roll && game.board.is_player_home_complete(game.player) {
+        turn.push_str(&format!(
+            " — Bear Off! (wastage {})",
+            game.board.bear_off_wastage(game.player)
+        ));
+    }
+
+    let pips = format!(
+        "Pips: White {}, Black {}",
+        game.board.pip_count(game::Color::White),
+        game.board.pip_count(game::Color::Black)
+    );
+
+    if !game.cube_enabled() {
+        return format!("{}  |  {}", turn, pips);
+    }
+
+    // the player on roll may double before a roll that likely ends the game
+    let last_roll = game.phase() == game::GamePhase::AwaitingRoll
+        && cube.owner.is_none_or(|owner| owner == game.player)
+        && game.is_last_roll_situation(game.player);
+
+    let mut cube = match cube.owner {
+        Some(owner) => format!("Cube: {} ({:?})", cube.value, owner),
+        None => format!("Cube: {}", cube.value),
+    };
+    if last_roll {
+        cube.push_str(" — last roll, double?");
+    }
+    format!("{}  |  {}  |  {}", turn, pips, cube)
+}
+
+// Number of legal full-turn sequences for the dice still to play.
+fn format_legal_plays(game: &game::Game) -> String {
+    if game.phase() != game::GamePhase::AwaitingMove {
+        return String::new();
+    }
+
+    let count = game
+        .legal_turn_sequences(game.player, &game.dice_rolls)
+        .len();
+    format!("Legal plays: {}", count)
+}
+
 pub(crate) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
         .spawn(NodeBundle {
@@ -70,14 +246,27 @@ pub(crate) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         })
         .with_children(|parent| {
             parent
-                .spawn(TextBundle::from_section(
-                    "",
-                    TextStyle {
-                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                        font_size: 120.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                ))
+                .spawn(
+                    TextBundle::from_sections([
+                        TextSection::new(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 120.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ),
+                        TextSection::new(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 40.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ),
+                    ])
+                    .with_text_alignment(TextAlignment::Center),
+                )
                 .insert(LabelGameOver);
         })
         .insert(Name::new("GameOver"));
@@ -95,16 +284,34 @@ pub(crate) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         .with_children(|parent| {
             parent
                 .spawn(TextBundle::from_section(
-                    "Turn: White",
+                    "Opening Roll",
                     TextStyle {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(0.9, 0.9, 0.9),
                     },
                 ))
-                .insert(LabelPlayerTurn);
+                .insert(StatusBar);
+
+            for point in (1..=6).rev() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(14.0), Val::Px(14.0)),
+                            margin: UiRect {
+                                left: Val::Px(6.0),
+                                bottom: Val::Px(12.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        background_color: HOME_POINT_OPEN.into(),
+                        ..default()
+                    })
+                    .insert(HomeBoardPip(point));
+            }
         })
-        .insert(Name::new("TurnIndicator"));
+        .insert(Name::new("StatusBar"));
 
     commands
         .spawn(NodeBundle {
@@ -117,6 +324,28 @@ pub(crate) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         })
         .with_children(|parent| {
+            for die in 0..2 {
+                parent
+                    .spawn(
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 40.0,
+                                color: Color::rgb(0.1, 0.1, 0.1),
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::right(Val::Px(10.0)),
+                            padding: UiRect::horizontal(Val::Px(12.0)),
+                            ..default()
+                        })
+                        .with_background_color(Color::rgb(0.95, 0.95, 0.95)),
+                    )
+                    .insert(LabelDieFace(die))
+                    .insert(Visibility::Hidden);
+            }
+
             parent
                 .spawn(TextBundle::from_section(
                     "",
@@ -127,9 +356,85 @@ pub(crate) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     },
                 ))
                 .insert(LabelMoveStack);
+
+            parent
+                .spawn(
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 40.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::left(Val::Px(40.0)),
+                        ..default()
+                    }),
+                )
+                .insert(LabelMovesLeft);
+
+            parent
+                .spawn(
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(0.7, 0.7, 0.7),
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::left(Val::Px(40.0)),
+                        ..default()
+                    }),
+                )
+                .insert(LabelLegalPlays);
+
+            parent
+                .spawn(
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 24.0,
+                            color: Color::rgb(0.9, 0.6, 0.3),
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::left(Val::Px(40.0)),
+                        ..default()
+                    }),
+                )
+                .insert(LabelPassCountdown);
         })
         .insert(Name::new("Move Stack"));
 
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::width(Val::Percent(100.0)),
+                align_items: AlignItems::Start,
+                justify_content: JustifyContent::Start,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 30.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ))
+                .insert(LabelClock);
+        })
+        .insert(Name::new("Clock"));
+
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -187,14 +492,553 @@ pub(crate) fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                         },
                     ));
                 })
-                .insert(ButtonBearOff { position_to: None });
+                .insert(ButtonBearOff { bear_off: None });
+
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(220.0), Val::Px(65.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        display: Display::None,
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Bear Off All",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 40.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                    ));
+                })
+                .insert(ButtonBearOffAll);
         })
         .insert(Name::new("BottomBar"));
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                gap: Size::width(Val::Px(10.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for length in MATCH_LENGTHS {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(65.0), Val::Px(65.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            length.to_string(),
+                            TextStyle {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 40.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ));
+                    })
+                    .insert(ButtonMatchLength(length));
+            }
+        })
+        .insert(Name::new("MatchLength"));
+
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 28.0,
+                    color: Color::rgb(0.0, 0.9, 0.0),
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            }),
+        )
+        .insert(Visibility::Hidden)
+        .insert(LabelPipDistance)
+        .insert(Name::new("PipDistance"));
+
+    commands
+        .spawn(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 24.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            }),
+        )
+        .insert(Visibility::Hidden)
+        .insert(LabelPointTooltip)
+        .insert(Name::new("PointTooltip"));
+}
+
+fn match_length_message(game: &game::Game) -> String {
+    match game.match_score {
+        Some(match_score) if !game.cube_enabled() => {
+            format!("{} point match, no cube", match_score.length)
+        }
+        Some(match_score) => format!("{} point match", match_score.length),
+        None => String::from("Money game"),
+    }
+}
+
+pub(crate) fn handle_match_length_buttons(
+    mut button_query: Query<(
+        Ref<Interaction>,
+        &mut BackgroundColor,
+        &mut Visibility,
+        &ButtonMatchLength,
+    )>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+    mut game: ResMut<game::Game>,
+) {
+    for (interaction, mut color, mut visibility, button) in button_query.iter_mut() {
+        if game.is_changed() {
+            *visibility = if game.game_log.is_empty() {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+
+        if interaction.is_changed() && *interaction == Interaction::Clicked {
+            game.select_match_length(button.0);
+            toast_event_writer.send(ToastEvent::new(match_length_message(&game)));
+        }
+
+        if !interaction.is_changed() && !game.is_changed() {
+            continue;
+        }
+
+        let selected = game
+            .match_score
+            .is_some_and(|match_score| match_score.length == button.0);
+        *color = match *interaction {
+            _ if selected => PRESSED_BUTTON.into(),
+            Interaction::Hovered => HOVERED_BUTTON.into(),
+            _ => NORMAL_BUTTON.into(),
+        };
+    }
+}
+
+// Start a roll, ignored while the dice of this turn are rolled or still rolling.
+fn roll_dice(
+    commands: &mut Commands,
+    dice_roll_start_event_writer: &mut EventWriter<DiceRollStartEvent>,
+    instant_dice_roll_event_writer: &mut EventWriter<InstantDiceRollEvent>,
+    game: &mut game::Game,
+    roll_pending: bool,
+    playback_speed: PlaybackSpeed,
+    dice_render_mode: DiceRenderMode,
+) {
+    if game.dice_rolled || roll_pending {
+        return;
+    }
+
+    if dice_render_mode == DiceRenderMode::Instant2D {
+        let mut rng = rand::thread_rng();
+        let player = game.player;
+        game.game_log.push(game::GameLogEntry {
+            player,
+            dice_rolls: vec![rng.gen_range(1..=6), rng.gen_range(1..=6)],
+            opening_tie: false,
+        });
+        game.dice_rolled = true;
+        instant_dice_roll_event_writer.send(InstantDiceRollEvent);
+        return;
+    }
+
+    let num_dice: Vec<usize> = vec![2, 2];
+
+    dice_roll_start_event_writer.send(DiceRollStartEvent { num_dice });
+    game.dice_rolled = true;
+
+    commands
+        .spawn(())
+        .insert(DiceRollTimer::new(game.game_log.len(), playback_speed));
+}
+
+// Bear off the chosen piece, returns false when no piece is chosen.
+fn bear_off(
+    move_piece_event_writer: &mut EventWriter<MovePieceEvent>,
+    button_bear_off: &ButtonBearOff,
+) -> bool {
+    let Some(checker_move) = button_bear_off.bear_off else {
+        return false;
+    };
+
+    move_piece_event_writer.send(MovePieceEvent { checker_move });
+    true
+}
+
+pub(crate) fn record_base_ui_sizes(
+    mut commands: Commands,
+    ui_query: Query<
+        (Entity, Option<&Text>, &Style),
+        (Or<(With<Text>, With<Button>)>, Without<BaseUiSize>),
+    >,
+) {
+    for (entity, text, style) in ui_query.iter() {
+        let font_sizes = text
+            .map(|text| {
+                text.sections
+                    .iter()
+                    .map(|section| section.style.font_size)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        commands.entity(entity).insert(BaseUiSize {
+            font_sizes,
+            size: style.size,
+        });
+    }
+}
+
+pub(crate) fn scale_ui_to_window(
+    mut window_resized_reader: EventReader<WindowResized>,
+    primary_window_query: Query<&Window, With<PrimaryWindow>>,
+    added_query: Query<(), Added<BaseUiSize>>,
+    mut ui_query: Query<(&BaseUiSize, Option<&mut Text>, &mut Style)>,
+) {
+    let resized = window_resized_reader.iter().count() > 0;
+    if !resized && added_query.is_empty() {
+        return;
+    }
+
+    let Ok(window) = primary_window_query.get_single() else {
+        return;
+    };
+    let scale = ui_scale(window.width());
+
+    for (base_ui_size, text, mut style) in ui_query.iter_mut() {
+        if let Some(mut text) = text {
+            for (section, base_font_size) in
+                text.sections.iter_mut().zip(base_ui_size.font_sizes.iter())
+            {
+                section.style.font_size = base_font_size * scale;
+            }
+        }
+
+        style.size = Size::new(
+            scale_val(base_ui_size.size.width, scale),
+            scale_val(base_ui_size.size.height, scale),
+        );
+    }
+}
+
+// Moves bearing off a checker with every die of the roll, offered as a single
+// action when that's the only way to play it.
+fn bear_off_all_moves(game: &game::Game) -> Option<Vec<game::Move>> {
+    game.forced_bear_off(game.player)
+        .filter(|moves| moves.len() == game.dice_rolls.len())
+}
+
+fn bear_off_all(move_piece_event_writer: &mut EventWriter<MovePieceEvent>, moves: Vec<game::Move>) {
+    for checker_move in moves {
+        move_piece_event_writer.send(MovePieceEvent { checker_move });
+    }
+}
+
+pub(crate) fn handle_bear_off_all_button(
+    mut button_query: Query<
+        (
+            Ref<Interaction>,
+            &mut BackgroundColor,
+            &mut Visibility,
+            &mut Style,
+        ),
+        With<ButtonBearOffAll>,
+    >,
+    mut move_piece_event_writer: EventWriter<MovePieceEvent>,
+    game: Res<game::Game>,
+) {
+    for (interaction, mut color, mut visibility, mut style) in button_query.iter_mut() {
+        if game.is_changed() {
+            let offered = game.dice_rolled && bear_off_all_moves(&game).is_some();
+            *visibility = if offered {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            style.display = if offered {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
+
+        if !interaction.is_changed() {
+            continue;
+        }
+
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+
+                if let Some(moves) = bear_off_all_moves(&game) {
+                    bear_off_all(&mut move_piece_event_writer, moves);
+                    *visibility = Visibility::Hidden;
+                }
+            }
+            Interaction::Hovered => *color = HOVERED_BUTTON.into(),
+            Interaction::None => *color = NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+// Tab moves focus through the visible buttons, Enter activates the focused one.
+pub(crate) fn keyboard_navigation(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut focused_button: ResMut<FocusedButton>,
+    mut buttons_query: Query<
+        (
+            Entity,
+            &Interaction,
+            &mut BackgroundColor,
+            &mut Visibility,
+            &Style,
+            Option<&ButtonRollDice>,
+            Option<&ButtonBearOff>,
+            Option<&ButtonBearOffAll>,
+        ),
+        With<Button>,
+    >,
+    mut dice_roll_start_event_writer: EventWriter<DiceRollStartEvent>,
+    mut instant_dice_roll_event_writer: EventWriter<InstantDiceRollEvent>,
+    mut move_piece_event_writer: EventWriter<MovePieceEvent>,
+    mut game: ResMut<game::Game>,
+    dice_roll_timer_query: Query<(), With<DiceRollTimer>>,
+    playback_speed: Res<PlaybackSpeed>,
+    dice_render_mode: Res<DiceRenderMode>,
+) {
+    let mut visible_buttons = buttons_query
+        .iter()
+        .filter(|(_, _, _, visibility, style, ..)| {
+            **visibility != Visibility::Hidden && style.display != Display::None
+        })
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    visible_buttons.sort();
+
+    if focused_button
+        .0
+        .is_some_and(|entity| !visible_buttons.contains(&entity))
+    {
+        focused_button.0 = None;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Tab) && !visible_buttons.is_empty() {
+        let next = match focused_button.0 {
+            Some(entity) => {
+                let index = visible_buttons.iter().position(|e| *e == entity).unwrap();
+                visible_buttons[(index + 1) % visible_buttons.len()]
+            }
+            None => visible_buttons[0],
+        };
+        focused_button.0 = Some(next);
+    }
+
+    for (
+        entity,
+        interaction,
+        mut color,
+        mut visibility,
+        _,
+        roll_dice_button,
+        bear_off_button,
+        bear_off_all_button,
+    ) in buttons_query.iter_mut()
+    {
+        let focused = focused_button.0 == Some(entity);
+
+        if *interaction == Interaction::None {
+            *color = if focused {
+                FOCUSED_BUTTON.into()
+            } else {
+                NORMAL_BUTTON.into()
+            };
+        }
+
+        if !focused || !keyboard_input.just_pressed(KeyCode::Return) {
+            continue;
+        }
+
+        if roll_dice_button.is_some() {
+            roll_dice(
+                &mut commands,
+                &mut dice_roll_start_event_writer,
+                &mut instant_dice_roll_event_writer,
+                &mut game,
+                !dice_roll_timer_query.is_empty(),
+                *playback_speed,
+                *dice_render_mode,
+            );
+        }
+
+        if let Some(button_bear_off) = bear_off_button {
+            if bear_off(&mut move_piece_event_writer, button_bear_off) {
+                *visibility = Visibility::Hidden;
+            }
+        }
+
+        if bear_off_all_button.is_some() {
+            if let Some(moves) = bear_off_all_moves(&game) {
+                bear_off_all(&mut move_piece_event_writer, moves);
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+// D switches between rolling physics dice and showing the faces right away.
+pub(crate) fn toggle_dice_render_mode(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut dice_render_mode: ResMut<DiceRenderMode>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::D) {
+        *dice_render_mode = match *dice_render_mode {
+            DiceRenderMode::Physics3D => DiceRenderMode::Instant2D,
+            DiceRenderMode::Instant2D => DiceRenderMode::Physics3D,
+        };
+        toast_event_writer.send(ToastEvent::new(match *dice_render_mode {
+            DiceRenderMode::Physics3D => "Dice: 3D",
+            DiceRenderMode::Instant2D => "Dice: instant 2D",
+        }));
+    }
+}
+
+pub(crate) fn update_die_faces(
+    game: Res<game::Game>,
+    dice_render_mode: Res<DiceRenderMode>,
+    mut face_query: Query<(&LabelDieFace, &mut Text, &mut Visibility)>,
+) {
+    if !game.is_changed() && !dice_render_mode.is_changed() {
+        return;
+    }
+
+    // faces of this turn's roll, none before rolling
+    let roll = game
+        .game_log
+        .last()
+        .filter(|_| game.dice_rolled && *dice_render_mode == DiceRenderMode::Instant2D)
+        .map(|entry| entry.dice_rolls.clone())
+        .unwrap_or_default();
+
+    for (face, mut text, mut visibility) in face_query.iter_mut() {
+        match roll.get(face.0) {
+            Some(value) => {
+                text.sections[0].value = value.to_string();
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+// N switches the move stack between numerals and die faces.
+pub(crate) fn toggle_die_display(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut die_display: ResMut<DieDisplay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::N) {
+        *die_display = match *die_display {
+            DieDisplay::Numeral => DieDisplay::Faces,
+            DieDisplay::Faces => DieDisplay::Numeral,
+        };
+    }
+}
+
+pub(crate) fn toggle_swap_dice(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut swap_dice: ResMut<SwapDice>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::S) {
+        swap_dice.0 = !swap_dice.0;
+        toast_event_writer.send(ToastEvent::new(if swap_dice.0 {
+            "Combined moves: second die first"
+        } else {
+            "Combined moves: first die first"
+        }));
+    }
+}
+
+// ] plays faster, [ slower.
+pub(crate) fn change_playback_speed(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut playback_speed: ResMut<PlaybackSpeed>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    let speed = if keyboard_input.just_pressed(KeyCode::RBracket) {
+        playback_speed.0 * 2.0
+    } else if keyboard_input.just_pressed(KeyCode::LBracket) {
+        playback_speed.0 / 2.0
+    } else {
+        return;
+    };
+
+    playback_speed.set(speed);
+    toast_event_writer.send(ToastEvent::new(format!(
+        "Playback speed {}x",
+        playback_speed.0
+    )));
+}
+
+pub(crate) fn change_double_click_time(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut input_settings: ResMut<InputSettings>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+) {
+    let steps = if keyboard_input.just_pressed(KeyCode::Period) {
+        1
+    } else if keyboard_input.just_pressed(KeyCode::Comma) {
+        -1
+    } else {
+        return;
+    };
+
+    input_settings.adjust_double_click(steps);
+    toast_event_writer.send(ToastEvent::new(format!(
+        "Double click within {} ms",
+        input_settings.double_click_ms
+    )));
 }
 
 pub(crate) fn ui_logic(
     mut commands: Commands,
-    pieces_query: Query<(Entity, &Piece)>,
     mut button_param_set: ParamSet<(
         Query<
             (Entity, &Interaction, &mut BackgroundColor),
@@ -212,26 +1056,35 @@ pub(crate) fn ui_logic(
         >,
     )>,
     mut label_set: ParamSet<(
-        Query<&mut Text, With<LabelPlayerTurn>>,
         Query<&mut Text, With<LabelMoveStack>>,
+        Query<&mut Text, With<LabelClock>>,
+        Query<&mut Text, With<LabelMovesLeft>>,
     )>,
     mut dice_roll_start_event_writer: EventWriter<DiceRollStartEvent>,
+    mut instant_dice_roll_event_writer: EventWriter<InstantDiceRollEvent>,
     mut move_piece_event_writer: EventWriter<MovePieceEvent>,
     mut game: ResMut<game::Game>,
+    dice_roll_timer_query: Query<(), With<DiceRollTimer>>,
+    clock: Res<ChessClock>,
+    playback_speed: Res<PlaybackSpeed>,
+    dice_render_mode: Res<DiceRenderMode>,
+    die_display: Res<DieDisplay>,
+    confirm_turn: Res<ConfirmTurn>,
 ) {
     for (_entity, interaction, mut color) in &mut button_param_set.p0() {
         match *interaction {
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
 
-                let num_dice: Vec<usize> = vec![2, 2];
-
-                dice_roll_start_event_writer.send(DiceRollStartEvent { num_dice });
-                game.dice_rolled = true;
-
-                commands.spawn(()).insert(DiceRollTimer {
-                    timer: Timer::new(Duration::from_secs(2), TimerMode::Once),
-                });
+                roll_dice(
+                    &mut commands,
+                    &mut dice_roll_start_event_writer,
+                    &mut instant_dice_roll_event_writer,
+                    &mut game,
+                    !dice_roll_timer_query.is_empty(),
+                    *playback_speed,
+                    *dice_render_mode,
+                );
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -247,22 +1100,9 @@ pub(crate) fn ui_logic(
     {
         match *interaction {
             Interaction::Clicked => {
-                let all_pieces = pieces_query
-                    .iter()
-                    .map(|(_, piece)| *piece)
-                    .collect::<Vec<_>>();
-                let chosen_piece = all_pieces.iter().find(|p| p.chosen);
-
-                if chosen_piece.is_none() {
-                    continue;
+                if bear_off(&mut move_piece_event_writer, button_bear_off) {
+                    *visibility = Visibility::Hidden;
                 }
-
-                move_piece_event_writer.send(MovePieceEvent {
-                    from: chosen_piece.unwrap().position,
-                    to: button_bear_off.position_to.unwrap(),
-                });
-
-                *visibility = Visibility::Hidden;
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -273,19 +1113,622 @@ pub(crate) fn ui_logic(
         }
     }
 
+    let dice_rolls = &confirm_turn.shown(&game).dice_rolls;
     for mut text in &mut label_set.p0().iter_mut() {
-        text.sections[0].value = format!("Turn: {:?}", game.player);
-
-        if game.board.is_player_home_complete(game.player) {
-            text.sections[0].value = format!("{} \t Bear Off!", text.sections[0].value);
+        if !dice_rolls.is_empty() {
+            text.sections[0].value = format!(
+                "Move Stack: {}",
+                format_move_stack(dice_rolls, *die_display)
+            );
+        } else {
+            text.sections[0].value = "".to_string();
         }
     }
 
     for mut text in &mut label_set.p1().iter_mut() {
-        if !game.dice_rolls.is_empty() {
-            text.sections[0].value = format!("Move Stack: {:?}", game.dice_rolls);
+        if clock.enabled {
+            text.sections[0].value = clock.format();
         } else {
             text.sections[0].value = "".to_string();
         }
     }
+
+    for mut text in &mut label_set.p2().iter_mut() {
+        text.sections[0].value = format_moves_left(&game);
+    }
+}
+
+pub(crate) fn update_status_bar(
+    game: Res<game::Game>,
+    mut status_bar_query: Query<&mut Text, With<StatusBar>>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+
+    let status = format_status(&game, &game.cube);
+
+    for mut text in status_bar_query.iter_mut() {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
+
+pub(crate) fn update_home_board_pips(
+    game: Res<game::Game>,
+    mut pip_query: Query<(&HomeBoardPip, &mut BackgroundColor, &mut Visibility)>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+
+    let made = game.board.home_points_made(game.player);
+    for (pip, mut color, mut visibility) in pip_query.iter_mut() {
+        *color = if made[pip.0 - 1] {
+            HOME_POINT_MADE
+        } else {
+            HOME_POINT_OPEN
+        }
+        .into();
+        *visibility = if game.opening_roll {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+// Enumerating the sequences is costly, so the label only follows game changes.
+pub(crate) fn update_legal_plays_label(
+    game: Res<game::Game>,
+    mut label_query: Query<&mut Text, With<LabelLegalPlays>>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+
+    for mut text in label_query.iter_mut() {
+        text.sections[0].value = format_legal_plays(&game);
+    }
+}
+
+pub(crate) fn update_pass_countdown_label(
+    pass_timer_query: Query<&PassTimer>,
+    mut label_query: Query<&mut Text, With<LabelPassCountdown>>,
+) {
+    let message = pass_timer_query
+        .iter()
+        .next()
+        .map(|pass_timer| pass_countdown_message(pass_timer.seconds_left()))
+        .unwrap_or_default();
+
+    for mut text in label_query.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+    }
+}
+
+// Pips from the chosen checker to a candidate, combined moves count both dice.
+pub(crate) fn candidate_pip_distance(
+    game: &game::Game,
+    chosen: &Piece,
+    candidate: &Piece,
+) -> usize {
+    let checker_move = game::Move::between(
+        chosen.board_index().get(),
+        candidate.board_index().get() as i32,
+    );
+    game.board.move_distance(game.player, checker_move)
+}
+
+const PIP_DISTANCE_CURSOR_OFFSET: f32 = 16.0;
+
+pub(crate) fn update_pip_distance_label(
+    ghost_preview: Res<GhostPreview>,
+    game: Res<game::Game>,
+    pieces_query: Query<&Piece>,
+    primary_window_query: Query<&Window, With<PrimaryWindow>>,
+    mut label_query: Query<(&mut Text, &mut Style, &mut Visibility), With<LabelPipDistance>>,
+) {
+    let candidate = ghost_preview
+        .candidate
+        .and_then(|candidate| pieces_query.get(candidate).ok());
+    let chosen = pieces_query.iter().find(|piece| piece.chosen);
+    let cursor = primary_window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+
+    for (mut text, mut style, mut visibility) in label_query.iter_mut() {
+        let (Some(candidate), Some(chosen), Some(cursor)) = (candidate, chosen, cursor) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        text.sections[0].value = candidate_pip_distance(&game, chosen, candidate).to_string();
+        // cursor coordinates start at the bottom left of the window
+        style.position = UiRect {
+            left: Val::Px(cursor.x + PIP_DISTANCE_CURSOR_OFFSET),
+            bottom: Val::Px(cursor.y + PIP_DISTANCE_CURSOR_OFFSET),
+            ..default()
+        };
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// Says whether the player on roll can reach board index `point` and with which dice.
+pub(crate) fn point_tooltip(game: &game::Game, point: usize) -> String {
+    let sources = (0..=game::BAR).collect::<Vec<_>>();
+    let dice = game.dice_reaching(&sources, point);
+    let number = mat::point_number(game.player, point);
+
+    if dice.is_empty() {
+        return format!("Point {}: no move here", number);
+    }
+
+    format!(
+        "Point {}: {:?} moves here with {}",
+        number,
+        game.player,
+        dice.iter().join(" or ")
+    )
+}
+
+// How close in pixels the cursor has to be to a point's outermost checker.
+const POINT_HOVER_RADIUS: f32 = 30.0;
+
+pub(crate) fn update_point_tooltip(
+    game: Res<game::Game>,
+    scene_scale: Res<SceneScale>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    primary_window_query: Query<&Window, With<PrimaryWindow>>,
+    mut label_query: Query<(&mut Text, &mut Style, &mut Visibility), With<LabelPointTooltip>>,
+) {
+    let cursor = primary_window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let camera = camera_query.get_single().ok();
+
+    let hovered = match (cursor, camera) {
+        (Some(cursor), Some((camera, camera_transform)))
+            if game.phase() == game::GamePhase::AwaitingMove =>
+        {
+            (0..24)
+                .filter_map(|point| {
+                    let outermost = Piece {
+                        position: point + 1,
+                        row: 1,
+                        color: game.player,
+                        highlighted: false,
+                        candidate: false,
+                        dimmed: false,
+                        chosen: false,
+                    };
+                    let translation = outermost.transform(*scene_scale).translation;
+                    // viewport coordinates start at the bottom left, like the cursor's
+                    let viewport = camera.world_to_viewport(camera_transform, translation)?;
+                    Some((point, viewport.distance(cursor)))
+                })
+                .filter(|(_, distance)| *distance < POINT_HOVER_RADIUS)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(point, _)| (point, cursor))
+        }
+        _ => None,
+    };
+
+    for (mut text, mut style, mut visibility) in label_query.iter_mut() {
+        let Some((point, cursor)) = hovered else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        text.sections[0].value = point_tooltip(&game, point);
+        style.position = UiRect {
+            left: Val::Px(cursor.x + PIP_DISTANCE_CURSOR_OFFSET),
+            bottom: Val::Px(cursor.y - PIP_DISTANCE_CURSOR_OFFSET),
+            ..default()
+        };
+        *visibility = Visibility::Inherited;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white_to_play(white: &[(usize, i32)], dice_rolls: Vec<usize>) -> game::Game {
+        let mut points = [0; 24];
+        for &(index, checkers) in white {
+            points[index] = checkers;
+        }
+        let mut black = [0; 24];
+        black[0] = 15;
+
+        let mut game =
+            game::Game::from_position(points, black, [0, 0], game::Color::White).unwrap();
+        game.opening_roll = false;
+        game.dice_rolled = true;
+        game.dice_rolls = dice_rolls;
+        game
+    }
+
+    #[test]
+    fn doubles_show_the_dice_left() {
+        let mut game = white_to_play(&[(18, 15)], vec![4, 4, 4, 4]);
+        assert_eq!(
+            format_move_stack(&game.dice_rolls, DieDisplay::Numeral),
+            "4 ×4"
+        );
+
+        game.use_dice_roll(4);
+        assert_eq!(
+            format_move_stack(&game.dice_rolls, DieDisplay::Numeral),
+            "4 ×3"
+        );
+        assert_eq!(format_move_stack(&[3, 5], DieDisplay::Numeral), "[3, 5]");
+    }
+
+    #[test]
+    fn tab_cycles_through_the_visible_buttons() {
+        let mut app = crate::test_support::test_app();
+        app.add_event::<DiceRollStartEvent>()
+            .add_event::<InstantDiceRollEvent>()
+            .add_event::<MovePieceEvent>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<FocusedButton>()
+            .init_resource::<PlaybackSpeed>()
+            .init_resource::<DiceRenderMode>()
+            .insert_resource(game::Game::new())
+            .add_system(keyboard_navigation);
+
+        let mut spawn_button = |visibility: Visibility, display: Display| {
+            app.world
+                .spawn((
+                    Button,
+                    Interaction::None,
+                    BackgroundColor(NORMAL_BUTTON),
+                    visibility,
+                    Style {
+                        display,
+                        ..default()
+                    },
+                ))
+                .id()
+        };
+        let roll = spawn_button(Visibility::Inherited, Display::Flex);
+        spawn_button(Visibility::Hidden, Display::Flex);
+        spawn_button(Visibility::Inherited, Display::None);
+        let bear_off = spawn_button(Visibility::Visible, Display::Flex);
+
+        let mut focused = vec![];
+        for _ in 0..3 {
+            let mut input = app.world.resource_mut::<Input<KeyCode>>();
+            input.clear();
+            input.press(KeyCode::Tab);
+            app.update();
+            app.world
+                .resource_mut::<Input<KeyCode>>()
+                .release(KeyCode::Tab);
+            focused.push(app.world.resource::<FocusedButton>().0.unwrap());
+        }
+        assert_eq!(focused, vec![roll, bear_off, roll]);
+        assert_eq!(
+            app.world.get::<BackgroundColor>(roll).unwrap().0,
+            FOCUSED_BUTTON
+        );
+
+        // a focused button that gets hidden loses the focus
+        *app.world.get_mut::<Visibility>(roll).unwrap() = Visibility::Hidden;
+        app.world.resource_mut::<Input<KeyCode>>().clear();
+        app.update();
+        assert_eq!(app.world.resource::<FocusedButton>().0, None);
+    }
+
+    #[test]
+    fn moves_left_counts_down_a_double() {
+        let mut game = white_to_play(&[(12, 15)], vec![]);
+        assert_eq!(format_moves_left(&game), "");
+
+        game.game_log.push(game::GameLogEntry {
+            player: game::Color::White,
+            dice_rolls: vec![3, 3],
+            opening_tie: false,
+        });
+        game.dice_rolls = vec![3, 3, 3, 3];
+        assert_eq!(format_moves_left(&game), "Moves left: 4 of 4");
+
+        game.play_move(game::Color::White, game::Move::between(12, 15))
+            .unwrap();
+        assert_eq!(format_moves_left(&game), "Moves left: 3 of 4");
+    }
+
+    #[test]
+    fn title_font_scales_with_the_window_width() {
+        // the title is spawned at 60 for the reference width
+        let title_size = |window_width: f32| 60.0 * ui_scale(window_width);
+        assert_eq!(title_size(REFERENCE_WINDOW_WIDTH), 60.0);
+        assert_eq!(title_size(1920.0), 60.0);
+        assert_eq!(title_size(640.0), 30.0);
+        assert_eq!(title_size(100.0), 60.0 * MIN_UI_SCALE);
+    }
+
+    #[test]
+    fn legal_plays_count_the_turn_sequences() {
+        let mut game = game::Game::new();
+        assert_eq!(format_legal_plays(&game), "");
+
+        game.opening_roll = false;
+        game.dice_rolled = true;
+        for dice_rolls in [vec![3, 1], vec![6, 5], vec![2, 2, 2, 2]] {
+            game.dice_rolls = dice_rolls.clone();
+            let count = game.legal_turn_sequences(game.player, &dice_rolls).len();
+            assert!(count > 0);
+            assert_eq!(format_legal_plays(&game), format!("Legal plays: {}", count));
+        }
+
+        // a lone checker plays the 6-5 in either order
+        let mut game = white_to_play(&[(12, 1)], vec![6, 5]);
+        assert_eq!(format_legal_plays(&game), "Legal plays: 2");
+
+        // and has one play left after the 6
+        game.play_move(game::Color::White, game::Move::between(12, 18))
+            .unwrap();
+        assert_eq!(format_legal_plays(&game), "Legal plays: 1");
+    }
+
+    #[test]
+    fn status_shows_bear_off_wastage() {
+        let game = white_to_play(&[(22, 7), (23, 8)], vec![]);
+        let wastage = game.board.bear_off_wastage(game::Color::White);
+        assert!(format_status(&game, &game.cube)
+            .starts_with(&format!("Turn: White — Bear Off! (wastage {})", wastage)));
+    }
+
+    #[test]
+    fn status_offers_a_last_roll_double() {
+        let mut game = white_to_play(&[(22, 1), (23, 1)], vec![]);
+        game.dice_rolled = false;
+        assert!(format_status(&game, &game.cube).ends_with("Cube: 1 — last roll, double?"));
+
+        // not once the dice are rolled
+        game.dice_rolled = true;
+        game.dice_rolls = vec![2, 1];
+        assert!(format_status(&game, &game.cube).ends_with("Cube: 1"));
+    }
+
+    #[test]
+    fn bear_off_all_plays_every_die() {
+        let mut game = white_to_play(&[(20, 2), (22, 2)], vec![4, 4, 4, 4]);
+        let moves = bear_off_all_moves(&game).unwrap();
+        assert_eq!(moves.len(), 4);
+        for checker_move in moves {
+            assert!(game.play_move(game::Color::White, checker_move).is_ok());
+        }
+        assert!(game.is_over());
+
+        // checkers still to bring home
+        let game = white_to_play(&[(10, 2), (22, 2)], vec![4, 4, 4, 4]);
+        assert!(bear_off_all_moves(&game).is_none());
+
+        // the 5-point checker has to move before the 2-point one can go off
+        let game = white_to_play(&[(19, 1), (22, 1)], vec![4, 3]);
+        assert!(bear_off_all_moves(&game).is_none());
+    }
+
+    #[test]
+    fn match_length_buttons_start_a_match() {
+        let mut app = crate::test_support::test_app();
+        app.add_event::<ToastEvent>()
+            .insert_resource(game::Game::new())
+            .add_system(handle_match_length_buttons);
+
+        let mut spawn_button = |length| {
+            app.world
+                .spawn((
+                    Interaction::None,
+                    BackgroundColor(NORMAL_BUTTON),
+                    Visibility::Inherited,
+                    ButtonMatchLength(length),
+                ))
+                .id()
+        };
+        let five = spawn_button(5);
+        let one = spawn_button(1);
+        app.update();
+        assert!(app.world.resource::<game::Game>().cube_enabled());
+
+        *app.world.get_mut::<Interaction>(five).unwrap() = Interaction::Clicked;
+        app.update();
+        let game = app.world.resource::<game::Game>();
+        assert_eq!(
+            game.match_score,
+            Some(crate::cube::MatchScore {
+                length: 5,
+                score: [0, 0],
+                crawford: false,
+            })
+        );
+        assert!(game.cube_enabled());
+        assert_eq!(match_length_message(game), "5 point match");
+        assert_eq!(
+            app.world.get::<BackgroundColor>(five).unwrap().0,
+            PRESSED_BUTTON
+        );
+
+        *app.world.get_mut::<Interaction>(five).unwrap() = Interaction::None;
+        *app.world.get_mut::<Interaction>(one).unwrap() = Interaction::Clicked;
+        app.update();
+        let game = app.world.resource::<game::Game>();
+        assert_eq!(game.match_score.unwrap().length, 1);
+        assert!(!game.cube_enabled());
+        assert_eq!(match_length_message(game), "1 point match, no cube");
+    }
+
+    #[test]
+    fn rolling_twice_starts_one_roll() {
+        let mut app = crate::test_support::test_app();
+        app.add_event::<DiceRollStartEvent>()
+            .add_event::<InstantDiceRollEvent>()
+            .add_event::<MovePieceEvent>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<FocusedButton>()
+            .init_resource::<PlaybackSpeed>()
+            .init_resource::<DiceRenderMode>()
+            .insert_resource(game::Game::new())
+            .add_system(keyboard_navigation);
+
+        let roll = app
+            .world
+            .spawn((
+                Button,
+                ButtonRollDice,
+                Interaction::None,
+                BackgroundColor(NORMAL_BUTTON),
+                Visibility::Inherited,
+                Style::default(),
+            ))
+            .id();
+        app.world.resource_mut::<FocusedButton>().0 = Some(roll);
+
+        let mut reader = app
+            .world
+            .resource::<Events<DiceRollStartEvent>>()
+            .get_reader();
+        // rolls started by pressing enter on the focused roll button
+        let mut press_return = |app: &mut App| {
+            let mut input = app.world.resource_mut::<Input<KeyCode>>();
+            input.clear();
+            input.press(KeyCode::Return);
+            app.update();
+            app.world
+                .resource_mut::<Input<KeyCode>>()
+                .release(KeyCode::Return);
+            let events = app.world.resource::<Events<DiceRollStartEvent>>();
+            reader.iter(events).count()
+        };
+        assert_eq!(press_return(&mut app), 1);
+        assert_eq!(press_return(&mut app), 0);
+        // a pending timer blocks the roll even when the dice are not marked rolled
+        app.world.resource_mut::<game::Game>().dice_rolled = false;
+        assert_eq!(press_return(&mut app), 0);
+
+        assert_eq!(
+            app.world.query::<&DiceRollTimer>().iter(&app.world).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn status_shows_turn_pips_and_cube() {
+        let mut game = game::Game::new();
+        assert_eq!(
+            format_status(&game, &game.cube),
+            "Opening Roll  |  Pips: White 167, Black 167  |  Cube: 1"
+        );
+
+        game.opening_roll = false;
+        game.player = game::Color::Black;
+        let cube = DoublingCube {
+            value: 4,
+            owner: Some(game::Color::White),
+        };
+        assert_eq!(
+            format_status(&game, &cube),
+            "Turn: Black  |  Pips: White 167, Black 167  |  Cube: 4 (White)"
+        );
+
+        // no cube in a one point match
+        game.select_match_length(1);
+        assert_eq!(
+            format_status(&game, &cube),
+            "Turn: Black  |  Pips: White 167, Black 167"
+        );
+    }
+
+    #[test]
+    fn pip_distance_counts_in_the_players_direction() {
+        let checker = |position, color| Piece {
+            position,
+            row: 1,
+            color,
+            highlighted: false,
+            candidate: false,
+            dimmed: false,
+            chosen: false,
+        };
+        let white = |position| checker(position, game::Color::White);
+        let black = |position| checker(position, game::Color::Black);
+
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+        assert_eq!(candidate_pip_distance(&game, &white(1), &white(9)), 8);
+        // entering from the bar, at position 25
+        assert_eq!(candidate_pip_distance(&game, &white(25), &white(4)), 4);
+
+        game.player = game::Color::Black;
+        assert_eq!(candidate_pip_distance(&game, &black(24), &black(19)), 5);
+        assert_eq!(candidate_pip_distance(&game, &black(25), &black(22)), 3);
+    }
+
+    #[test]
+    fn instant_dice_roll_without_a_timer() {
+        let mut game = game::Game::new();
+        game.opening_roll = false;
+
+        let mut app = crate::test_support::test_app();
+        app.add_event::<DiceRollStartEvent>()
+            .add_event::<InstantDiceRollEvent>()
+            .add_event::<MovePieceEvent>()
+            .add_event::<crate::events::TurnStartEvent>()
+            .add_event::<crate::events::HighlightPickablePiecesEvent>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<FocusedButton>()
+            .init_resource::<PlaybackSpeed>()
+            .init_resource::<crate::AutoBearOff>()
+            .insert_resource(DiceRenderMode::Instant2D)
+            .insert_resource(game)
+            .add_system(keyboard_navigation)
+            .add_system(crate::events::event_dice_rolls_complete.after(keyboard_navigation));
+
+        let roll = app
+            .world
+            .spawn((
+                Button,
+                ButtonRollDice,
+                Interaction::None,
+                BackgroundColor(NORMAL_BUTTON),
+                Visibility::Inherited,
+                Style::default(),
+            ))
+            .id();
+        app.world.resource_mut::<FocusedButton>().0 = Some(roll);
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Return);
+        app.update();
+
+        assert_eq!(
+            app.world.query::<&DiceRollTimer>().iter(&app.world).count(),
+            0
+        );
+        assert!(app
+            .world
+            .resource::<Events<DiceRollStartEvent>>()
+            .is_empty());
+        let game = app.world.resource::<game::Game>();
+        assert!(game.dice_rolled);
+        assert!(matches!(game.dice_rolls.len(), 2 | 4));
+        assert!(game.dice_rolls.iter().all(|die| (1..=6).contains(die)));
+    }
+
+    #[test]
+    fn move_stack_shows_die_faces_or_numerals() {
+        assert_eq!(format_move_stack(&[3, 5], DieDisplay::Faces), "⚂ ⚄");
+        assert_eq!(format_move_stack(&[3, 5], DieDisplay::Numeral), "[3, 5]");
+        assert_eq!(format_move_stack(&[6, 6, 6], DieDisplay::Faces), "⚅ ×3");
+        assert_eq!(format_move_stack(&[1], DieDisplay::Faces), "⚀");
+    }
 }
@@ -0,0 +1,160 @@
+// Taking back moves within the current turn. A snapshot is pushed before every
+// move and the history is cleared when the turn passes.
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::{
+    confirm::ConfirmTurn,
+    events::{HighlightPickablePiecesEvent, TurnStartEvent},
+    game::{self, Board},
+    redraw_pieces,
+    toast::ToastEvent,
+    GameResources, Piece, SceneScale,
+};
+
+const DEFAULT_MAX_UNDO_DEPTH: usize = 20;
+
+#[derive(Clone)]
+pub(crate) struct UndoSnapshot {
+    board: Board,
+    dice_rolls: Vec<usize>,
+    hits: [u32; 2],
+}
+
+impl UndoSnapshot {
+    fn restore(self, game: &mut game::Game) {
+        game.board = self.board;
+        game.dice_rolls = self.dice_rolls;
+        game.hits = self.hits;
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct UndoHistory {
+    snapshots: VecDeque<UndoSnapshot>,
+    // oldest snapshots are dropped past this many
+    pub(crate) max_undo_depth: usize,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        UndoHistory {
+            snapshots: VecDeque::new(),
+            max_undo_depth: DEFAULT_MAX_UNDO_DEPTH,
+        }
+    }
+}
+
+impl UndoHistory {
+    pub(crate) fn push(&mut self, game: &game::Game) {
+        self.snapshots.push_back(UndoSnapshot {
+            board: game.board.clone(),
+            dice_rolls: game.dice_rolls.clone(),
+            hits: game.hits,
+        });
+
+        while self.snapshots.len() > self.max_undo_depth {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<UndoSnapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+}
+
+pub(crate) fn clear_undo_history(
+    mut turn_start_event_reader: EventReader<TurnStartEvent>,
+    mut undo_history: ResMut<UndoHistory>,
+) {
+    if turn_start_event_reader.iter().count() > 0 {
+        undo_history.clear();
+    }
+}
+
+// U takes back the last move of the turn.
+pub(crate) fn undo_move(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut undo_history: ResMut<UndoHistory>,
+    mut game: ResMut<game::Game>,
+    mut pieces_query: Query<(Entity, &mut Piece, &mut Handle<StandardMaterial>)>,
+    mut highlight_pickable_pieces_event_writer: EventWriter<HighlightPickablePiecesEvent>,
+    mut toast_event_writer: EventWriter<ToastEvent>,
+    game_resources: Res<GameResources>,
+    scene_scale: Res<SceneScale>,
+    mut confirm_turn: ResMut<ConfirmTurn>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::U) {
+        return;
+    }
+
+    // staged moves are taken back on the working copy
+    if let Some(staged) = confirm_turn.staged.as_mut() {
+        if staged.undo().is_none() {
+            return;
+        }
+
+        redraw_pieces(
+            &mut commands,
+            &mut pieces_query,
+            staged.working(),
+            &game_resources,
+            *scene_scale,
+        );
+        highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
+        toast_event_writer.send(ToastEvent::new("Move undone"));
+        return;
+    }
+
+    let Some(snapshot) = undo_history.pop() else {
+        return;
+    };
+
+    snapshot.restore(&mut game);
+    redraw_pieces(
+        &mut commands,
+        &mut pieces_query,
+        &game,
+        &game_resources,
+        *scene_scale,
+    );
+
+    highlight_pickable_pieces_event_writer.send(HighlightPickablePiecesEvent);
+    toast_event_writer.send(ToastEvent::new("Move undone"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_keeps_only_the_latest_snapshots() {
+        let mut undo_history = UndoHistory {
+            max_undo_depth: 3,
+            ..default()
+        };
+        let mut game = game::Game::new();
+        for die in 1..=5 {
+            game.dice_rolls = vec![die];
+            undo_history.push(&game);
+        }
+        assert_eq!(undo_history.len(), 3);
+
+        let mut restored = Vec::new();
+        while let Some(snapshot) = undo_history.pop() {
+            snapshot.restore(&mut game);
+            restored.push(game.dice_rolls[0]);
+        }
+        assert_eq!(restored, vec![5, 4, 3]);
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::{prelude::*, window::PrimaryWindow, winit::WinitWindows};
+use winit::window::Icon;
+
+use crate::{
+    events::{GameOverEvent, TurnStartEvent},
+    game,
+};
+
+const WINDOW_ICON: &str = "assets/icon.png";
+
+pub(crate) fn window_title(game: &game::Game) -> String {
+    if game.opening_roll {
+        String::from("Backgammon — Opening roll")
+    } else {
+        format!("Backgammon — {:?} to move", game.player)
+    }
+}
+
+pub(crate) fn game_over_title(winner: game::Color) -> String {
+    format!("Backgammon — {:?} wins", winner)
+}
+
+pub(crate) fn set_window_icon(
+    primary_window_query: Query<Entity, With<PrimaryWindow>>,
+    winit_windows: NonSend<WinitWindows>,
+) {
+    let Some(window) = primary_window_query
+        .get_single()
+        .ok()
+        .and_then(|entity| winit_windows.get_window(entity))
+    else {
+        return;
+    };
+
+    // the icon is decoded outside of the asset server, winit wants it right away
+    let Ok(image) = image::open(WINDOW_ICON) else {
+        warn!("Could not load window icon {}", WINDOW_ICON);
+        return;
+    };
+    let image = image.into_rgba8();
+    let (width, height) = image.dimensions();
+
+    if let Ok(icon) = Icon::from_rgba(image.into_raw(), width, height) {
+        window.set_window_icon(Some(icon));
+    }
+}
+
+pub(crate) fn update_window_title(
+    mut turn_start_event_reader: EventReader<TurnStartEvent>,
+    mut game_over_event_reader: EventReader<GameOverEvent>,
+    mut primary_window_query: Query<&mut Window, With<PrimaryWindow>>,
+    game: Res<game::Game>,
+) {
+    let mut title = None;
+
+    for _ in turn_start_event_reader.iter() {
+        title = Some(window_title(&game));
+    }
+
+    for event in game_over_event_reader.iter() {
+        title = Some(game_over_title(event.player));
+    }
+
+    if let (Some(title), Ok(mut window)) = (title, primary_window_query.get_single_mut()) {
+        window.title = title;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{events::GameEndReason, test_support::test_app};
+
+    fn title(app: &mut App) -> String {
+        app.world
+            .query_filtered::<&Window, With<PrimaryWindow>>()
+            .single(&app.world)
+            .title
+            .clone()
+    }
+
+    #[test]
+    fn title_follows_the_player_on_roll() {
+        let mut game = game::Game::new();
+        assert_eq!(window_title(&game), "Backgammon — Opening roll");
+        game.opening_roll = false;
+        game.player = game::Color::Black;
+
+        let mut app = test_app();
+        app.add_event::<TurnStartEvent>()
+            .add_event::<GameOverEvent>()
+            .insert_resource(game)
+            .add_system(update_window_title);
+        app.world.spawn((Window::default(), PrimaryWindow));
+
+        app.world.send_event(TurnStartEvent {
+            player: game::Color::Black,
+        });
+        app.update();
+        assert_eq!(title(&mut app), "Backgammon — Black to move");
+
+        app.world.send_event(GameOverEvent {
+            player: game::Color::White,
+            reason: GameEndReason::BorneOff,
+        });
+        app.update();
+        assert_eq!(title(&mut app), "Backgammon — White wins");
+    }
+}
@@ -0,0 +1,82 @@
+// Helpers for scripting games against the engine. Points are 0-based board
+// indices as in `game::Move`, `BAR` enters and past the end of the board bears off.
+#![allow(dead_code)]
+use bevy_backgammon::game::{Color, Game, GameLogEntry, Move};
+
+// Game with `player` on roll, from `(index, checkers)` pairs for each color.
+pub fn position(
+    white: &[(usize, i32)],
+    black: &[(usize, i32)],
+    bar: [i32; 2],
+    player: Color,
+) -> Game {
+    let mut white_points = [0; 24];
+    for &(index, checkers) in white {
+        white_points[index] = checkers;
+    }
+    let mut black_points = [0; 24];
+    for &(index, checkers) in black {
+        black_points[index] = checkers;
+    }
+
+    Game::from_position(white_points, black_points, bar, player).unwrap()
+}
+
+// Force the dice of the player on roll, doubles are played four times.
+pub fn roll(game: &mut Game, dice: &[usize]) {
+    game.dice_rolls = if dice.len() == 2 && dice[0] == dice[1] {
+        vec![dice[0]; 4]
+    } else {
+        dice.to_vec()
+    };
+    game.dice_rolled = true;
+    game.game_log.push(GameLogEntry {
+        player: game.player,
+        dice_rolls: dice.to_vec(),
+        opening_tie: false,
+    });
+}
+
+// Play one checker of the player on roll, returns whether it hit.
+pub fn play(game: &mut Game, from: usize, to: i32) -> bool {
+    let player = game.player;
+    game.play_move(player, Move::between(from, to))
+        .unwrap_or_else(|e| panic!("{:?} {} to {}: {}", player, from, to, e))
+}
+
+// Pass the turn once the dice are used up or can't be played.
+pub fn end_turn(game: &mut Game) {
+    assert!(
+        !game.can_move(game.player),
+        "{:?} can still play {:?}",
+        game.player,
+        game.dice_rolls
+    );
+    game.switch_turn();
+}
+
+// Signed checker count on a point, positive for White.
+pub fn checkers(game: &Game, index: usize) -> i32 {
+    game.board.points[index]
+}
+
+// Dice from a fixed seed, so scripted games play out the same every run.
+pub struct Dice(u64);
+
+impl Dice {
+    pub fn new(seed: u64) -> Self {
+        Dice(seed)
+    }
+
+    pub fn roll(&mut self) -> [usize; 2] {
+        [self.die(), self.die()]
+    }
+
+    fn die(&mut self) -> usize {
+        // xorshift64
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % 6) as usize + 1
+    }
+}
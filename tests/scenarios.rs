@@ -0,0 +1,1222 @@
+// End-to-end games played through the engine with scripted dice and moves.
+mod common;
+
+use bevy_backgammon::game::{
+    expand_roll, stack_rows, Board, BoardIndex, Color, Game, GamePhase, GameSummary, GameVariant,
+    Move, MoveInfo, PointLabel, StagedTurn, WinKind, BAR,
+};
+use bevy_backgammon::scenario::Scenario;
+use bevy_backgammon::service::{AuditEntry, DomainEvent, GameService, ServiceCommand};
+use common::{checkers, end_turn, play, position, roll, Dice};
+
+#[test]
+fn opening_three_one_makes_the_five_point() {
+    let mut game = Game::new();
+    assert_eq!(game.phase(), GamePhase::OpeningRoll);
+
+    assert!(game.resolve_opening_roll(3, 1));
+    game.dice_rolled = true;
+    assert_eq!(game.player, Color::White);
+    assert_eq!(game.phase(), GamePhase::AwaitingMove);
+
+    play(&mut game, 16, 19);
+    play(&mut game, 18, 19);
+    assert_eq!(checkers(&game, 16), 2);
+    assert_eq!(checkers(&game, 18), 4);
+    assert_eq!(checkers(&game, 19), 2);
+    assert!(game.dice_rolls.is_empty());
+
+    end_turn(&mut game);
+    assert_eq!(game.player, Color::Black);
+    assert_eq!(game.phase(), GamePhase::AwaitingRoll);
+}
+
+#[test]
+fn hitting_a_blot_sends_it_to_the_bar() {
+    let mut game = position(
+        &[(10, 1), (18, 14)],
+        &[(14, 2), (5, 13)],
+        [0, 0],
+        Color::Black,
+    );
+    roll(&mut game, &[4, 2]);
+
+    assert!(play(&mut game, 14, 10));
+    assert_eq!(checkers(&game, 10), -1);
+    assert_eq!(game.board.bar, [1, 0]);
+    assert_eq!(game.hits, [1, 0]);
+
+    assert!(!play(&mut game, 14, 12));
+    end_turn(&mut game);
+    assert_eq!(game.player, Color::White);
+}
+
+#[test]
+fn checkers_on_the_bar_enter_first() {
+    let mut game = position(
+        &[(12, 14)],
+        &[(2, 1), (3, 2), (20, 12)],
+        [1, 0],
+        Color::White,
+    );
+    roll(&mut game, &[3, 4]);
+
+    // nothing else moves while a checker is on the bar
+    let player = game.player;
+    assert!(game.play_move(player, Move::between(12, 15)).is_err());
+    // the 4 lands on a held point
+    assert!(game.play_move(player, Move::between(BAR, 3)).is_err());
+
+    assert!(play(&mut game, BAR, 2));
+    assert_eq!(game.board.bar, [0, 1]);
+    assert_eq!(checkers(&game, 2), 1);
+
+    play(&mut game, 12, 16);
+    end_turn(&mut game);
+}
+
+#[test]
+fn entering_on_a_blot_hits_it() {
+    let mut game = position(&[(10, 14)], &[(2, 1), (20, 14)], [1, 0], Color::White);
+    roll(&mut game, &[3, 5]);
+    assert!(play(&mut game, BAR, 2));
+    assert_eq!(game.board.bar, [0, 1]);
+    assert_eq!(checkers(&game, 2), 1);
+    assert_eq!(game.dice_rolls, vec![5]);
+
+    let mut game = position(&[(10, 14), (21, 1)], &[(20, 14)], [0, 1], Color::Black);
+    roll(&mut game, &[3, 5]);
+    assert!(play(&mut game, BAR, 21));
+    assert_eq!(game.board.bar, [1, 0]);
+    assert_eq!(checkers(&game, 21), -1);
+    assert_eq!(game.hits, [1, 0]);
+}
+
+#[test]
+fn bearing_off_every_checker_wins() {
+    let mut game = position(&[(23, 2), (21, 2)], &[(0, 15)], [0, 0], Color::White);
+    roll(&mut game, &[3, 3]);
+
+    for from in [21, 21, 23, 23] {
+        play(&mut game, from, 24);
+    }
+
+    assert!(game.is_over());
+    assert_eq!(game.phase(), GamePhase::GameOver);
+    assert_eq!(game.board.borne_off, [15, 0]);
+}
+
+#[test]
+fn complete_game_reaches_a_winner() {
+    const MAX_TURNS: usize = 1000;
+
+    let mut game = Game::new();
+    let mut dice = Dice::new(0x5eed);
+
+    while game.opening_roll {
+        let [white, black] = dice.roll();
+        game.resolve_opening_roll(white, black);
+    }
+    game.dice_rolled = true;
+
+    for _ in 0..MAX_TURNS {
+        // checker by checker like the board does, taking the first legal move
+        let player = game.player;
+        while let Some(&checker_move) = game
+            .get_possible_moves(player, game.dice_rolls.clone())
+            .first()
+        {
+            game.play_move(player, checker_move).unwrap();
+            if game.is_over() {
+                break;
+            }
+        }
+
+        if game.is_over() {
+            break;
+        }
+
+        end_turn(&mut game);
+        roll(&mut game, &dice.roll());
+    }
+
+    assert!(game.is_over(), "no winner after {} turns", MAX_TURNS);
+    let winner = game.player;
+    assert_eq!(game.board.borne_off[game.board.bar_index(winner)], 15);
+    assert_eq!(game.board.checkers_in_play(winner), 0);
+    assert!(game.board.checkers_in_play(winner.opposite()) > 0);
+}
+
+#[test]
+fn last_roll_races() {
+    // the 1- and 2-points come off with any roll
+    let game = position(&[(22, 1), (23, 1)], &[(0, 5), (1, 5)], [0, 0], Color::White);
+    assert!(game.is_last_roll_situation(Color::White));
+    // the 6- and 5-points only with big rolls
+    let game = position(&[(18, 1), (19, 1)], &[(0, 5)], [0, 0], Color::White);
+    assert!(!game.is_last_roll_situation(Color::White));
+    // Black's side of the board
+    let game = position(&[(23, 5)], &[(0, 1), (1, 1)], [0, 0], Color::White);
+    assert!(game.is_last_roll_situation(Color::Black));
+
+    // contact, too many checkers, or checkers still outside home are contested
+    let game = position(&[(21, 1), (23, 1)], &[(22, 1)], [0, 0], Color::White);
+    assert!(!game.is_last_roll_situation(Color::White));
+    let game = position(&[(23, 5)], &[(0, 5)], [0, 0], Color::White);
+    assert!(!game.is_last_roll_situation(Color::White));
+    let game = position(&[(10, 1)], &[(0, 5)], [0, 0], Color::White);
+    assert!(!game.is_last_roll_situation(Color::White));
+}
+
+#[test]
+fn decided_race_finishes_itself() {
+    // a few pips left against a whole army still on its way home
+    let mut game = position(&[(22, 1), (23, 2)], &[(11, 15)], [0, 0], Color::Black);
+    assert_eq!(game.decided_race_winner(), Some(Color::White));
+
+    let mut dice = Dice::new(7);
+    assert_eq!(game.finish_race(|| dice.roll()), Some(Color::White));
+    assert!(game.is_over());
+    assert_eq!(game.board.borne_off, [15, 0]);
+    assert_eq!(game.player, Color::White);
+
+    // close races and contact are left to the players
+    let game = position(&[(20, 5)], &[(3, 5)], [0, 0], Color::White);
+    assert_eq!(game.decided_race_winner(), None);
+    // so are leaders with enough checkers to fill a point and block a die
+    let game = position(&[(22, 1), (23, 4)], &[(11, 15)], [0, 0], Color::Black);
+    assert_eq!(game.decided_race_winner(), None);
+    let mut game = Game::new();
+    assert_eq!(game.decided_race_winner(), None);
+    assert_eq!(game.finish_race(|| [6, 5]), None);
+}
+
+#[test]
+fn dice_reaching_a_point() {
+    let mut game = Game::new();
+    assert!(game.resolve_opening_roll(3, 1));
+    game.dice_rolled = true;
+
+    let sources = (0..=BAR).collect::<Vec<_>>();
+    // the 5-point is made from the 8 with the 3 and the 6 with the 1
+    assert_eq!(game.dice_reaching(&sources, 19), vec![1, 3]);
+    assert_eq!(game.dice_reaching(&[16], 19), vec![3]);
+    assert_eq!(game.dice_reaching(&[18], 19), vec![1]);
+    assert_eq!(game.dice_reaching(&sources, 17), vec![1]);
+    // Black's 6-point is held
+    assert!(game.dice_reaching(&sources, 5).is_empty());
+}
+
+#[test]
+fn seeded_opening_is_reproducible() {
+    for seed in [0, 1, 42, 0x5eed] {
+        let mut first = Game::new();
+        let mut second = Game::new();
+
+        let opener = first.seeded_opening_roll(seed).unwrap();
+        assert_eq!(second.seeded_opening_roll(seed), Some(opener));
+        assert_eq!(first.player, second.player);
+        assert_eq!(first.game_log, second.game_log);
+
+        // ties are logged before the deciding roll
+        let (deciding, ties) = first.game_log.split_last().unwrap();
+        assert_eq!(deciding.dice_rolls, opener.to_vec());
+        assert!(ties
+            .iter()
+            .all(|entry| entry.dice_rolls[0] == entry.dice_rolls[1]));
+        assert_ne!(opener[0], opener[1]);
+        assert_eq!(first.phase(), GamePhase::AwaitingMove);
+    }
+
+    let mut started = Game::new();
+    started.seeded_opening_roll(1);
+    assert_eq!(started.seeded_opening_roll(1), None);
+}
+
+#[test]
+fn home_points_made_from_the_one_point() {
+    let game = position(
+        &[(23, 2), (22, 1), (20, 3), (18, 4), (11, 5)],
+        &[(0, 3), (2, 2), (4, 1), (5, 5), (12, 4)],
+        [0, 0],
+        Color::White,
+    );
+
+    assert_eq!(
+        game.board.home_points_made(Color::White),
+        [true, false, false, true, false, true]
+    );
+    assert_eq!(
+        game.board.home_points_made(Color::Black),
+        [true, false, true, false, false, true]
+    );
+    assert_eq!(
+        Game::new().board.home_points_made(Color::Black),
+        [false, false, false, false, false, true]
+    );
+}
+
+#[test]
+fn every_checker_on_a_choosable_point_can_move() {
+    let mut game = Game::new();
+    assert!(game.resolve_opening_roll(6, 4));
+    game.dice_rolled = true;
+
+    let (choosable_points, _) = game.get_choosable_pieces();
+    assert!(!choosable_points.is_empty());
+
+    for [position, top_row] in choosable_points {
+        let source = position - 1;
+        // every drawn checker of the stack belongs to the player on roll ...
+        let player_checkers = checkers(&game, source) * game.player.sign();
+        assert_eq!(top_row, stack_rows(player_checkers as usize));
+        // ... and the point they share is a source of legal moves
+        assert!(!game
+            .get_possible_moves_for_piece(game.player, source)
+            .is_empty());
+    }
+}
+
+#[test]
+fn point_labels_and_board_indices_convert_both_ways() {
+    assert_eq!(BoardIndex(0).label(), PointLabel(1));
+    assert_eq!(BoardIndex(23).label(), PointLabel(24));
+    assert_eq!(BoardIndex(BAR as u8).label(), PointLabel(25));
+    assert_eq!(PointLabel(1).index(), BoardIndex(0));
+    assert_eq!(BoardIndex::from(PointLabel(25)).get(), BAR);
+
+    // a point picked on the board, moved to by the engine and drawn again
+    let mut game = Game::new();
+    assert!(game.resolve_opening_roll(3, 1));
+    game.dice_rolled = true;
+    for label in (1..=24).map(PointLabel) {
+        assert_eq!(PointLabel::from(BoardIndex::from(label)), label);
+    }
+
+    let picked = PointLabel(17);
+    let checker_move = game
+        .get_possible_moves_for_piece(game.player, picked.index().get())
+        .into_iter()
+        .find(|checker_move| checker_move.destination() == Some(19))
+        .unwrap();
+    play(&mut game, checker_move.source(), 19);
+    let landed = BoardIndex(checker_move.destination().unwrap() as u8).label();
+    assert_eq!(landed, PointLabel(20));
+    assert_eq!(checkers(&game, landed.index().get()), 1);
+    assert_eq!(BoardIndex(checker_move.source() as u8).label(), picked);
+}
+
+#[test]
+fn reconnected_client_resyncs_mid_turn() {
+    let (mut service, handle) = GameService::new(Game::new());
+    let mut client = Game::new();
+
+    // White opens with 3-1 and plays only the 3 before dropping out
+    handle.commands.send(ServiceCommand::Roll([3, 1])).unwrap();
+    handle
+        .commands
+        .send(ServiceCommand::Move(Move::between(16, 19)))
+        .unwrap();
+    assert!(service.process());
+    drop(handle);
+    assert!(!service.process());
+
+    let handle = service.reconnect();
+    let Ok(DomainEvent::Synced(sync)) = handle.events.try_recv() else {
+        panic!("reconnect didn't sync");
+    };
+    sync.apply(&mut client);
+
+    let host = service.game();
+    assert_eq!(client.board, host.board);
+    assert_eq!(client.board_snapshot(), host.board_snapshot());
+    assert_eq!(client.player, Color::White);
+    assert_eq!(client.dice_rolls, vec![1]);
+    assert_eq!(sync.log_index, host.game_log.len());
+
+    // the rest of the turn goes through the new handle
+    handle
+        .commands
+        .send(ServiceCommand::Move(Move::between(18, 19)))
+        .unwrap();
+    assert!(service.process());
+    assert_eq!(checkers(service.game(), 19), 2);
+}
+
+#[test]
+fn detailed_moves_flag_hits_and_made_points() {
+    // White blots on 2 and 4, a Black blot on 5
+    let game = position(
+        &[(2, 1), (4, 1), (18, 13)],
+        &[(5, 1), (23, 14)],
+        [0, 0],
+        Color::White,
+    );
+    let moves = game.get_possible_moves_detailed(Color::White, vec![1, 2]);
+
+    let hit = moves
+        .iter()
+        .find(|info| info.from == 4 && info.to == Some(5));
+    assert_eq!(
+        hit,
+        Some(&MoveInfo {
+            from: 4,
+            to: Some(5),
+            hits: true,
+            makes_point: false,
+        })
+    );
+
+    let made = moves
+        .iter()
+        .find(|info| info.from == 2 && info.to == Some(4));
+    assert!(made.is_some_and(|info| info.makes_point && !info.hits));
+
+    // landing on an empty point does neither
+    let quiet = moves
+        .iter()
+        .find(|info| info.from == 2 && info.to == Some(3));
+    assert!(quiet.is_some_and(|info| !info.makes_point && !info.hits));
+}
+
+#[test]
+fn restarting_a_scenario_gives_the_same_start() {
+    let puzzle = position(&[(20, 2), (22, 13)], &[(2, 15)], [0, 0], Color::White);
+    let scenario = Scenario {
+        board: puzzle.board.clone(),
+        player: Color::White,
+        dice: vec![[6, 5], [2, 1], [4, 4]],
+    };
+
+    let mut game = scenario.start();
+    let start = (game.board.clone(), game.dice_rolls.clone());
+    assert_eq!(start.1, vec![6, 5]);
+
+    // play the turn out, then retry
+    play(&mut game, 20, 24);
+    play(&mut game, 20, 24);
+    end_turn(&mut game);
+    assert!(scenario.roll(&mut game));
+    assert_eq!(game.dice_rolls, vec![2, 1]);
+
+    let retry = scenario.start();
+    assert_eq!((retry.board.clone(), retry.dice_rolls.clone()), start);
+    assert_eq!(retry.game_log.len(), 1);
+    assert_eq!(retry.player, Color::White);
+
+    let json = serde_json::to_string(&scenario).unwrap();
+    assert_eq!(Scenario::from_json(&json), Ok(scenario));
+}
+
+#[test]
+fn empty_home_has_no_highest_point() {
+    // White only has checkers outside its home, Black none at all
+    let mut game = position(&[(5, 2)], &[], [0, 0], Color::White);
+    assert_eq!(game.highest_point_in_home_zone(), None);
+
+    game.player = Color::Black;
+    assert_eq!(game.highest_point_in_home_zone(), None);
+    assert_eq!(game.board.pip_count(Color::Black), 0);
+    assert_eq!(game.board.checkers_in_play(Color::Black), 0);
+    assert!(game.board.is_race());
+    roll(&mut game, &[6, 5]);
+    assert!(!game.can_move(Color::Black));
+
+    // Black's home point with the most checkers, not point 0
+    let game = position(&[], &[(0, 1), (3, 4), (5, 2)], [0, 0], Color::Black);
+    assert_eq!(game.highest_point_in_home_zone(), Some((3, -4)));
+}
+
+#[test]
+fn illegal_remote_move_is_rejected_and_audited() {
+    let (mut service, handle) = GameService::new(Game::new());
+    handle.commands.send(ServiceCommand::Roll([3, 1])).unwrap();
+    assert!(service.process());
+    let board = service.game().board.clone();
+
+    // a 5 wasn't rolled
+    let illegal = Move::between(11, 16);
+    handle.commands.send(ServiceCommand::Move(illegal)).unwrap();
+    assert!(service.process());
+
+    let events = handle.events.try_iter().collect::<Vec<_>>();
+    assert!(matches!(events.last(), Some(DomainEvent::Rejected { .. })));
+    assert_eq!(service.game().board, board);
+    assert_eq!(
+        service.audit_log(),
+        [AuditEntry {
+            player: Color::White,
+            checker_move: illegal,
+            legal: false,
+        }]
+    );
+
+    // the same handle still plays
+    let legal = Move::between(16, 19);
+    handle.commands.send(ServiceCommand::Move(legal)).unwrap();
+    assert!(service.process());
+    assert_eq!(checkers(service.game(), 19), 1);
+    assert!(service.audit_log()[1].legal);
+}
+
+#[test]
+fn mutual_passes_end_when_a_die_plays() {
+    // White is shut out on the bar, Black can only enter on White's open 1-point
+    let mut game = position(
+        &[(10, 4), (18, 2), (19, 2), (20, 2), (21, 2), (22, 2)],
+        &[(0, 2), (1, 2), (2, 2), (3, 2), (4, 2), (5, 2), (12, 2)],
+        [1, 1],
+        Color::White,
+    );
+
+    // nothing to pass before rolling
+    assert!(!game.pass_turn());
+
+    let rolls = [[6, 5], [6, 5], [4, 3], [3, 2], [2, 2], [4, 1]];
+    let mut passes = 0;
+    for dice in rolls {
+        roll(&mut game, &dice);
+        if !game.pass_turn() {
+            break;
+        }
+        passes += 1;
+    }
+
+    assert_eq!(passes, 5);
+    assert_eq!(game.player, Color::Black);
+    assert!(!play(&mut game, BAR, 23));
+    assert_eq!(checkers(&game, 23), -1);
+}
+
+#[test]
+fn forced_bear_off_needs_every_way_to_end_alike() {
+    let mut game = position(&[(21, 1), (22, 1)], &[(0, 15)], [0, 0], Color::White);
+    roll(&mut game, &[6, 5]);
+
+    let moves = game.forced_bear_off(Color::White).unwrap();
+    assert_eq!(moves.len(), 2);
+    for checker_move in moves {
+        assert!(game.play_move(Color::White, checker_move).is_ok());
+    }
+    assert!(game.is_over());
+
+    // the 4-3 can't bear off the 2-point checker past the 5-point one
+    let mut game = position(&[(19, 1), (22, 1)], &[(0, 15)], [0, 0], Color::White);
+    roll(&mut game, &[4, 3]);
+    assert!(game.forced_bear_off(Color::White).is_none());
+
+    // a checker outside the home board
+    let mut game = position(&[(10, 1), (21, 1)], &[(0, 15)], [0, 0], Color::White);
+    roll(&mut game, &[6, 5]);
+    assert!(game.forced_bear_off(Color::White).is_none());
+}
+
+#[test]
+fn stacked_home_board_wastes_more_pips() {
+    let smooth = position(
+        &[(18, 5), (19, 2), (20, 2), (21, 2), (22, 2), (23, 2)],
+        &[(0, 15)],
+        [0, 0],
+        Color::White,
+    );
+    let stacked = position(&[(22, 7), (23, 8)], &[(0, 15)], [0, 0], Color::White);
+    assert!(
+        smooth.board.bear_off_wastage(Color::White) < stacked.board.bear_off_wastage(Color::White)
+    );
+
+    // every checker on the 1-point wastes 3 of an average die's 4 pips
+    let game = position(&[(23, 15)], &[(0, 15)], [0, 0], Color::Black);
+    assert_eq!(game.board.bear_off_wastage(Color::Black), 45);
+}
+
+#[test]
+fn suggested_bear_off_clears_the_six_point() {
+    // two on the 6-point and one on the 5-point, 2-1 to play
+    let game = position(&[(18, 2), (19, 1)], &[(0, 15)], [0, 0], Color::White);
+    let mut suggestion = game.board.suggest_bear_off(Color::White, &[2, 1]);
+    suggestion.sort_by_key(|checker_move| checker_move.destination());
+
+    // 6/5 6/4 leaves no wastage and no gap above the 4-point, unlike 6/4 5/4
+    // or moving a checker down to the 3-point
+    assert_eq!(
+        suggestion,
+        vec![Move::between(18, 19), Move::between(18, 20)]
+    );
+
+    // nothing to suggest before every checker is home
+    let game = position(&[(10, 1), (18, 2)], &[(0, 15)], [0, 0], Color::White);
+    assert!(game
+        .board
+        .suggest_bear_off(Color::White, &[2, 1])
+        .is_empty());
+}
+
+#[test]
+fn suggested_bear_off_moves_the_farthest_checker_first() {
+    // 4-3 with checkers on the 5- and 2-points: neither die bears off the
+    // 2-point checker while the 5-point one is behind it
+    let mut game = position(&[(19, 1), (22, 1)], &[(0, 15)], [0, 0], Color::White);
+    roll(&mut game, &[4, 3]);
+
+    // the 5-point checker moves down, then the other die bears off one checker
+    let suggestion = game.board.suggest_bear_off(Color::White, &game.dice_rolls);
+    assert_eq!(suggestion.len(), 2);
+    assert_eq!(suggestion[0].source(), 19);
+    assert!(matches!(suggestion[1], Move::BearOff { .. }));
+
+    for checker_move in suggestion {
+        assert!(game.play_move(Color::White, checker_move).is_ok());
+    }
+    assert_eq!(game.board.borne_off, [14, 0]);
+}
+
+#[test]
+fn mirrored_positions_generate_mirrored_moves() {
+    let positions = [
+        position(
+            &[(0, 2), (11, 5), (16, 3), (18, 5)],
+            &[(23, 2), (12, 5), (7, 3), (5, 5)],
+            [0, 0],
+            Color::White,
+        ),
+        position(
+            &[(4, 1), (18, 4), (20, 3)],
+            &[(2, 2), (3, 2), (10, 1), (22, 2)],
+            [1, 0],
+            Color::White,
+        ),
+        position(
+            &[(18, 2), (19, 1), (22, 4)],
+            &[(1, 3), (3, 2)],
+            [0, 0],
+            Color::White,
+        ),
+        // bearing off against an anchor on White's 1-point
+        position(
+            &[(19, 2), (20, 3), (21, 1)],
+            &[(23, 2), (0, 5), (4, 4)],
+            [0, 0],
+            Color::White,
+        ),
+    ];
+
+    for game in positions.iter() {
+        let board = &game.board;
+        let mirrored = board.mirror();
+        assert_eq!(mirrored.mirror(), *board);
+
+        for dice in [[3, 1], [6, 5], [2, 2]] {
+            let dice_rolls = expand_roll(dice);
+            let mut moves = board.get_possible_moves(Color::White, &dice_rolls);
+            let mut mirrored_moves = mirrored
+                .get_possible_moves(Color::Black, &dice_rolls)
+                .into_iter()
+                .map(Move::mirror)
+                .collect::<Vec<_>>();
+            moves.sort_by_key(|m| format!("{:?}", m));
+            mirrored_moves.sort_by_key(|m| format!("{:?}", m));
+            assert_eq!(moves, mirrored_moves, "{:?}", dice);
+
+            let sequences = board.legal_turn_sequences(Color::White, &dice_rolls);
+            let mirrored_sequences = mirrored.legal_turn_sequences(Color::Black, &dice_rolls);
+            assert_eq!(sequences.len(), mirrored_sequences.len(), "{:?}", dice);
+            for sequence in mirrored_sequences {
+                let sequence = sequence.into_iter().map(Move::mirror).collect::<Vec<_>>();
+                assert!(sequences.contains(&sequence), "{:?}", sequence);
+            }
+        }
+    }
+}
+
+#[test]
+fn game_summary_of_a_backgammon() {
+    let mut game = position(&[(23, 2)], &[(5, 14)], [0, 1], Color::Black);
+    game.hits = [2, 1];
+    game.cube.value = 2;
+    roll(&mut game, &[1, 1]);
+    end_turn(&mut game);
+    roll(&mut game, &[1, 1]);
+    play(&mut game, 23, 24);
+    play(&mut game, 23, 24);
+
+    assert!(game.is_over());
+    let summary = game.summary(Color::White);
+    assert_eq!(
+        summary,
+        GameSummary {
+            winner: Color::White,
+            win_kind: WinKind::Backgammon,
+            cube_value: 2,
+            pip_counts: [0, 25 + 6 * 14],
+            hits: [2, 1],
+            turns: 2,
+        }
+    );
+    assert_eq!(summary.points(), 6);
+}
+
+#[test]
+fn tied_opening_rolls_are_not_turns() {
+    // a seeded opening that Black wins after a tie logged under White
+    let mut game = (0..)
+        .map(|seed| {
+            let mut game = Game::new();
+            game.seeded_opening_roll(seed);
+            game
+        })
+        .find(|game| game.game_log.len() > 1 && game.player == Color::Black)
+        .unwrap();
+    assert!(game.game_log[0].opening_tie);
+
+    play_first_moves(&mut game);
+    roll(&mut game, &[6, 5]);
+    assert_eq!(game.summary(Color::White).turns, 2);
+}
+
+// Play the first legal move until the dice run out, then pass the turn.
+fn play_first_moves(game: &mut Game) {
+    let player = game.player;
+    while let Some(&checker_move) = game
+        .get_possible_moves(player, game.dice_rolls.clone())
+        .first()
+    {
+        assert!(game.play_move(player, checker_move).is_ok());
+    }
+    end_turn(game);
+}
+
+#[test]
+fn playing_on_from_a_replayed_position() {
+    let mut replayed = Game::new();
+    assert!(replayed.resolve_opening_roll(5, 2));
+    replayed.dice_rolled = true;
+    play(&mut replayed, 11, 16);
+    play(&mut replayed, 0, 2);
+    end_turn(&mut replayed);
+    roll(&mut replayed, &[3, 1]);
+    play(&mut replayed, 7, 4);
+    play(&mut replayed, 5, 4);
+    end_turn(&mut replayed);
+    replayed.cube.value = 2;
+
+    let game = replayed.play_from_here();
+    assert_eq!(game.board, replayed.board);
+    assert_eq!(game.player, Color::White);
+    assert_eq!(game.phase(), GamePhase::AwaitingRoll);
+    assert!(game.game_log.is_empty());
+    assert_eq!(game.cube.value, 1);
+}
+
+#[test]
+fn staged_moves_wait_for_confirmation() {
+    let mut game = Game::new();
+    assert!(game.resolve_opening_roll(3, 1));
+    game.dice_rolled = true;
+    let start = game.board.clone();
+
+    let mut staged = StagedTurn::new(&game);
+    assert!(!staged.stage(Move::between(16, 19)).unwrap());
+    assert!(!staged.is_complete());
+    assert!(staged.stage(Move::between(16, 22)).is_err());
+    assert!(!staged.stage(Move::between(18, 19)).unwrap());
+    assert!(staged.is_complete());
+    assert_eq!(staged.working().board.points[19], 2);
+
+    // the real game is untouched until the turn is confirmed
+    assert_eq!(game.board, start);
+    assert_eq!(game.dice_rolls, vec![3, 1]);
+
+    assert_eq!(staged.undo(), Some(Move::between(18, 19)));
+    assert_eq!(staged.moves(), [Move::between(16, 19)]);
+    assert_eq!(staged.working().dice_rolls, vec![1]);
+    assert!(!staged.stage(Move::between(18, 19)).unwrap());
+    assert_eq!(game.board, start);
+
+    staged.confirm(&mut game).unwrap();
+    assert_eq!(checkers(&game, 19), 2);
+    assert_eq!(checkers(&game, 16), 2);
+    assert!(game.dice_rolls.is_empty());
+}
+
+#[test]
+fn moves_must_cover_a_die() {
+    let mut game = Game::new();
+    game.opening_roll = false;
+    roll(&mut game, &[3, 5]);
+    let board = game.board.clone();
+
+    assert!(!game
+        .board
+        .is_die_sized_move(Color::White, 0, 4, &game.dice_rolls));
+    assert!(game.play_move(Color::White, Move::between(0, 4)).is_err());
+    assert_eq!(game.board, board);
+    assert_eq!(game.dice_rolls, vec![3, 5]);
+
+    assert!(game
+        .board
+        .is_die_sized_move(Color::White, 0, 3, &game.dice_rolls));
+    assert!(game
+        .board
+        .is_die_sized_move(Color::White, 0, 5, &game.dice_rolls));
+}
+
+#[test]
+fn black_bears_off_from_the_one_point() {
+    let mut game = position(&[(23, 15)], &[(0, 1), (3, 1)], [0, 0], Color::Black);
+    roll(&mut game, &[1, 6]);
+
+    play(&mut game, 0, -1);
+    assert_eq!(game.board.borne_off, [0, 14]);
+
+    // the 6 carries off the farthest checker
+    play(&mut game, 3, -1);
+    assert_eq!(game.board.borne_off, [0, 15]);
+    assert!(game.is_over());
+}
+
+#[test]
+fn larger_die_bears_off_only_the_farthest_checker() {
+    let mut game = position(&[(20, 1), (22, 1)], &[(0, 15)], [0, 0], Color::White);
+    roll(&mut game, &[6, 1]);
+
+    let possible = game.get_possible_moves(Color::White, vec![6]);
+    assert!(!possible.contains(&Move::BearOff { from: 22 }));
+    assert!(game
+        .play_move(Color::White, Move::BearOff { from: 22 })
+        .is_err());
+    assert_eq!(game.board.borne_off, [13, 0]);
+
+    play(&mut game, 20, 24);
+    play(&mut game, 22, 23);
+    assert_eq!(game.board.borne_off, [14, 0]);
+}
+
+#[test]
+fn opponent_anchor_in_home_board_does_not_block_bearing_off() {
+    let mut game = position(
+        &[(18, 14), (23, 1)],
+        &[(19, 2), (0, 13)],
+        [0, 0],
+        Color::White,
+    );
+    roll(&mut game, &[1, 2]);
+
+    play(&mut game, 23, 24);
+    assert_eq!(game.board.borne_off, [1, 0]);
+}
+
+#[test]
+fn sixteenth_checker_is_rejected() {
+    let mut game = Game::new();
+    let board = game.board.clone();
+    assert_eq!(game.board.checker_total(Color::White), 15);
+    assert!(game.board.place_checker(Color::White, 3).is_err());
+    assert_eq!(game.board, board);
+
+    // borne off checkers count towards the limit
+    let mut game = position(&[(18, 13)], &[(0, 15)], [0, 0], Color::White);
+    let board = game.board.clone();
+    assert_eq!(game.board.checker_total(Color::White), 15);
+    assert!(game.board.place_checker(Color::White, 18).is_err());
+    assert_eq!(game.board, board);
+
+    game.board.borne_off = [1, 0];
+    assert!(game.board.place_checker(Color::White, 18).is_ok());
+    assert_eq!(checkers(&game, 18), 14);
+    assert!(game.board.place_checker(Color::Black, 18).is_err());
+}
+
+#[test]
+fn higher_opening_die_moves_first() {
+    let mut game = Game::new();
+
+    // a tie rolls again
+    assert!(!game.resolve_opening_roll(3, 3));
+    assert_eq!(game.phase(), GamePhase::OpeningRoll);
+
+    assert!(game.resolve_opening_roll(2, 6));
+    game.dice_rolled = true;
+    assert_eq!(game.player, Color::Black);
+    assert_eq!(game.dice_rolls, vec![2, 6]);
+    assert_eq!(game.phase(), GamePhase::AwaitingMove);
+}
+
+#[test]
+fn no_bearing_off_with_a_checker_on_the_bar() {
+    let mut game = position(
+        &[(18, 5), (19, 5), (20, 4)],
+        &[(10, 2), (0, 13)],
+        [1, 0],
+        Color::White,
+    );
+    roll(&mut game, &[6, 5]);
+
+    // entering with the 5 would leave the 6 blocked, and nothing bears off
+    assert_eq!(
+        game.get_possible_moves(Color::White, game.dice_rolls.clone()),
+        vec![Move::Enter { to: 5 }]
+    );
+    let player = game.player;
+    assert!(game.play_move(player, Move::between(18, 24)).is_err());
+
+    play(&mut game, BAR, 5);
+    assert_eq!(game.board.bar, [0, 0]);
+    assert!(!game
+        .get_possible_moves(Color::White, game.dice_rolls.clone())
+        .iter()
+        .any(|m| matches!(m, Move::BearOff { .. })));
+}
+
+#[test]
+fn impossible_positions_are_rejected() {
+    let rejection = |white: &[(usize, i32)], black: &[(usize, i32)], bar: [i32; 2]| {
+        let mut white_points = [0; 24];
+        for &(index, checkers) in white {
+            white_points[index] = checkers;
+        }
+        let mut black_points = [0; 24];
+        for &(index, checkers) in black {
+            black_points[index] = checkers;
+        }
+        match Game::from_position(white_points, black_points, bar, Color::White) {
+            Ok(_) => panic!("position accepted"),
+            Err(e) => e,
+        }
+    };
+
+    assert_eq!(
+        rejection(&[(0, 14)], &[], [2, 0]),
+        "White has 16 checkers, at most 15 allowed"
+    );
+    assert_eq!(
+        rejection(&[(3, 2)], &[(3, 1)], [0, 0]),
+        "Both colors on point 4"
+    );
+    assert_eq!(
+        rejection(&[], &[(7, -1)], [0, 0]),
+        "Negative checker count on point 8"
+    );
+    assert_eq!(
+        rejection(&[], &[], [0, -1]),
+        "Negative checker count on the bar"
+    );
+
+    // a mid-game position with checkers hit and borne off
+    let game = position(
+        &[(0, 2), (11, 3), (18, 6)],
+        &[(5, 5), (12, 4), (23, 2)],
+        [1, 1],
+        Color::Black,
+    );
+    assert_eq!(game.player, Color::Black);
+    assert_eq!(checkers(&game, 5), -5);
+    assert_eq!(game.board.bar, [1, 1]);
+    assert_eq!(game.board.borne_off, [3, 3]);
+    assert_eq!(game.phase(), GamePhase::AwaitingRoll);
+}
+
+#[test]
+fn hits_on_black_are_counted() {
+    let mut game = position(
+        &[(0, 2), (18, 13)],
+        &[(3, 1), (5, 1), (12, 13)],
+        [0, 0],
+        Color::White,
+    );
+    roll(&mut game, &[3, 5]);
+
+    assert!(play(&mut game, 0, 3));
+    assert!(play(&mut game, 0, 5));
+    assert_eq!(game.hits, [0, 2]);
+    assert_eq!(game.board.bar, [0, 2]);
+}
+
+#[test]
+fn double_with_two_playable_moves_ends_after_two() {
+    // the runner steps up to the prime and the home checkers can't bear off yet
+    let mut game = position(
+        &[(0, 1), (21, 5), (22, 5), (23, 4)],
+        &[(4, 5), (9, 2), (10, 2), (11, 2), (12, 2), (13, 2)],
+        [0, 0],
+        Color::White,
+    );
+    roll(&mut game, &[3, 3]);
+
+    assert_eq!(
+        game.legal_turn_sequences(Color::White, &game.dice_rolls),
+        vec![vec![Move::between(0, 3), Move::between(3, 6)]]
+    );
+
+    play(&mut game, 0, 3);
+    assert!(game.can_move(Color::White));
+    play(&mut game, 3, 6);
+    assert_eq!(game.dice_rolls, vec![3, 3]);
+    end_turn(&mut game);
+    assert_eq!(game.player, Color::Black);
+}
+
+#[test]
+fn snapshot_of_a_known_position() {
+    let game = position(
+        &[(0, 2), (18, 10)],
+        &[(5, 4), (23, 1)],
+        [1, 2],
+        Color::White,
+    );
+    let snapshot = game.board_snapshot();
+
+    assert_eq!(snapshot.points[0], (Some(Color::White), 2));
+    assert_eq!(snapshot.points[18], (Some(Color::White), 10));
+    assert_eq!(snapshot.points[5], (Some(Color::Black), 4));
+    assert_eq!(snapshot.points[23], (Some(Color::Black), 1));
+    assert_eq!(
+        snapshot
+            .points
+            .iter()
+            .filter(|(color, _)| color.is_none())
+            .count(),
+        20
+    );
+    assert!(snapshot
+        .points
+        .iter()
+        .all(|&(color, count)| color.is_some() == (count > 0)));
+    assert_eq!(snapshot.bar, [1, 2]);
+    assert_eq!(snapshot.off, [2, 8]);
+}
+
+#[test]
+fn combined_move_offers_both_dice_orders() {
+    let mut game = position(&[(0, 1), (20, 14)], &[(22, 15)], [0, 0], Color::White);
+    roll(&mut game, &[3, 5]);
+
+    assert_eq!(
+        game.get_combined_moves_for_piece(Color::White, 0),
+        vec![
+            [Move::between(0, 3), Move::between(3, 8)],
+            [Move::between(0, 5), Move::between(5, 8)],
+        ]
+    );
+
+    // with the 3 landing on a held point only the 5 can go first
+    let mut game = position(
+        &[(0, 1), (20, 14)],
+        &[(3, 2), (22, 13)],
+        [0, 0],
+        Color::White,
+    );
+    roll(&mut game, &[3, 5]);
+    assert_eq!(
+        game.get_combined_moves_for_piece(Color::White, 0),
+        vec![[Move::between(0, 5), Move::between(5, 8)]]
+    );
+}
+
+#[test]
+fn all_checkers_on_the_bar_is_not_a_win() {
+    // White has no checkers on the points but none borne off either
+    let game = position(&[], &[(5, 15)], [15, 0], Color::White);
+    assert_eq!(game.board.borne_off, [0, 0]);
+    assert!(!game.is_over());
+    assert_ne!(game.phase(), GamePhase::GameOver);
+
+    let mut game = position(&[(20, 1)], &[(0, 1)], [0, 0], Color::Black);
+    assert!(!game.is_over());
+    roll(&mut game, &[1, 2]);
+    play(&mut game, 0, -1);
+    assert!(game.is_over());
+    assert_eq!(game.board.borne_off, [14, 15]);
+}
+
+#[test]
+fn opening_position_until_a_checker_moves() {
+    let mut game = Game::new();
+    assert!(game.board.is_opening_position(GameVariant::Standard));
+    assert!(!game.board.is_opening_position(GameVariant::Nackgammon));
+
+    let nackgammon = Board {
+        points: GameVariant::Nackgammon.opening_points(),
+        bar: [0, 0],
+        borne_off: [0, 0],
+    };
+    assert!(nackgammon.is_opening_position(GameVariant::Nackgammon));
+    assert_eq!(nackgammon.checker_total(Color::White), 15);
+    assert_eq!(nackgammon.checker_total(Color::Black), 15);
+
+    assert!(game.resolve_opening_roll(3, 1));
+    game.dice_rolled = true;
+    play(&mut game, 16, 19);
+    assert!(!game.board.is_opening_position(GameVariant::Standard));
+}
+
+#[test]
+fn race_once_the_armies_have_passed() {
+    assert!(!Game::new().board.is_race());
+
+    // one White checker still behind Black's back checker
+    let game = position(&[(2, 1), (20, 14)], &[(3, 15)], [0, 0], Color::White);
+    assert!(!game.board.is_race());
+
+    let game = position(&[(20, 15)], &[(3, 15)], [0, 0], Color::White);
+    assert!(game.board.is_race());
+}
+
+#[test]
+fn each_kind_of_move_applies_to_the_board() {
+    assert_eq!(Move::between(BAR, 4), Move::Enter { to: 4 });
+    assert_eq!(Move::between(0, 3), Move::Point { from: 0, to: 3 });
+    assert_eq!(Move::between(3, 24), Move::BearOff { from: 3 });
+    assert_eq!(Move::between(3, -1), Move::BearOff { from: 3 });
+    assert_eq!(
+        Move::BearOff { from: 22 }.destination_index(Color::White),
+        24
+    );
+    assert_eq!(
+        Move::BearOff { from: 1 }.destination_index(Color::Black),
+        -1
+    );
+
+    let mut board = Board {
+        points: [0; 24],
+        bar: [1, 0],
+        borne_off: [0, 0],
+    };
+    board.points[20] = 1;
+    board.points[22] = 1;
+
+    board
+        .make_move(Color::White, Move::Enter { to: 2 })
+        .unwrap();
+    assert_eq!((board.bar[0], board.points[2]), (0, 1));
+
+    board
+        .make_move(Color::White, Move::Point { from: 2, to: 5 })
+        .unwrap();
+    assert_eq!((board.points[2], board.points[5]), (0, 1));
+
+    // bearing off takes the die covering the distance, or a higher one for the
+    // farthest checker
+    assert_eq!(
+        board.die_for_move(Color::White, Move::BearOff { from: 22 }, &[1, 4]),
+        None
+    );
+    assert_eq!(
+        board.die_for_move(Color::White, Move::BearOff { from: 20 }, &[1, 6]),
+        Some(6)
+    );
+    board
+        .make_move(Color::White, Move::Point { from: 5, to: 18 })
+        .unwrap();
+    board
+        .make_move(Color::White, Move::BearOff { from: 22 })
+        .unwrap();
+    assert_eq!((board.points[22], board.borne_off[0]), (0, 1));
+}
+
+#[test]
+fn phase_follows_the_roll_and_board_state() {
+    let mut game = Game::new();
+    assert_eq!(game.phase(), GamePhase::OpeningRoll);
+
+    game.opening_roll = false;
+    assert_eq!(game.phase(), GamePhase::AwaitingRoll);
+
+    // dice still rolling
+    game.dice_rolled = true;
+    assert_eq!(game.phase(), GamePhase::AwaitingRoll);
+
+    game.dice_rolls = vec![3, 1];
+    assert_eq!(game.phase(), GamePhase::AwaitingMove);
+
+    // a finished game stays over whatever the other fields say
+    let mut game = position(&[], &[(0, 15)], [0, 0], Color::White);
+    game.dice_rolls = vec![3, 1];
+    assert_eq!(game.phase(), GamePhase::GameOver);
+    game.opening_roll = true;
+    assert_eq!(game.phase(), GamePhase::GameOver);
+}
+
+#[test]
+fn service_streams_the_events_of_submitted_moves() {
+    let mut game = Game::new();
+    game.opening_roll = false;
+    let (mut service, handle) = GameService::new(game);
+
+    handle.commands.send(ServiceCommand::Roll([3, 1])).unwrap();
+    for (from, to) in [(16, 19), (18, 19), (0, 1)] {
+        handle
+            .commands
+            .send(ServiceCommand::Move(Move::between(from, to)))
+            .unwrap();
+    }
+    assert!(service.process());
+    assert_eq!(
+        handle.events.try_iter().collect::<Vec<_>>(),
+        vec![
+            DomainEvent::Rolled {
+                player: Color::White,
+                dice: vec![3, 1],
+            },
+            DomainEvent::Moved {
+                player: Color::White,
+                checker_move: Move::between(16, 19),
+            },
+            DomainEvent::Moved {
+                player: Color::White,
+                checker_move: Move::between(18, 19),
+            },
+            DomainEvent::TurnEnded {
+                player: Color::White,
+            },
+            DomainEvent::Rejected {
+                reason: String::from("Illegal move Point { from: 0, to: 1 }"),
+            },
+        ]
+    );
+    assert_eq!(service.game().player, Color::Black);
+
+    drop(handle);
+    assert!(!service.process());
+}
+
+#[test]
+fn service_reports_hits_from_its_own_thread() {
+    let game = position(
+        &[(0, 1), (23, 14)],
+        &[(3, 1), (4, 14)],
+        [0, 0],
+        Color::White,
+    );
+    let (service, handle) = GameService::new(game);
+    let thread = std::thread::spawn(move || service.run());
+
+    handle.commands.send(ServiceCommand::Roll([3, 5])).unwrap();
+    handle
+        .commands
+        .send(ServiceCommand::Move(Move::between(0, 3)))
+        .unwrap();
+    assert_eq!(
+        handle.events.recv().unwrap(),
+        DomainEvent::Rolled {
+            player: Color::White,
+            dice: vec![3, 5],
+        }
+    );
+    assert_eq!(
+        handle.events.recv().unwrap(),
+        DomainEvent::Moved {
+            player: Color::White,
+            checker_move: Move::between(0, 3),
+        }
+    );
+    assert_eq!(
+        handle.events.recv().unwrap(),
+        DomainEvent::Hit {
+            player: Color::White,
+            point: 3,
+        }
+    );
+
+    drop(handle);
+    thread.join().unwrap();
+}